@@ -0,0 +1,165 @@
+# The placeholder of the empty search bar.
+search-placeholder = Nach einem Programm suchen
+
+## Messages shown in the search bar
+
+case-sensitive = Groß-/Kleinschreibung
+caps-lock-ignored = Feststelltaste ignoriert
+indexing-icons = Symbole werden indiziert { $finished }/{ $total } (kann kurz hängen)
+
+## Sections of the empty query view
+
+section-pinned = Angeheftet
+section-recent = Zuletzt verwendet
+section-most-used = Am häufigsten verwendet
+
+## Sections of the category view, the freedesktop main categories
+
+category-audio-video = Multimedia
+category-development = Entwicklung
+category-education = Bildung
+category-game = Spiele
+category-graphics = Grafik
+category-network = Internet
+category-office = Büro
+category-science = Wissenschaft
+category-settings = Einstellungen
+category-system = System
+category-utility = Zubehör
+category-other = Sonstige
+
+## The details tooltip of an app
+
+details-path = Pfad
+details-exec = Befehl
+details-categories = Kategorien
+details-source = Quelle
+source-desktop-entry = Desktop-Eintrag
+source-link = Link
+source-start-menu = Startmenü-Verknüpfung
+source-start-apps = Paketierte App
+source-settings = Einstellungen
+source-appimage = AppImage
+source-app-bundle = Programmpaket
+
+## The usage statistics view
+
+stats-launches = { $count } Starts in den letzten 30 Tagen
+stats-average-time = Im Schnitt { $seconds }s vom Öffnen bis zum Starten
+stats-most-used = Am häufigsten gestartet
+stats-per-day = Starts pro Tag
+
+## The context menu of an app
+
+tags-menu-title = Tags
+tags-menu-empty = Noch keine Tags, tippe einen #tag in die Suche, um einen anzulegen
+
+edit-entry = Desktop-Eintrag bearbeiten
+edit-entry-copy = Kopie in ~/.local/share/applications bearbeiten
+edit-entry-system = Systemdatei bearbeiten
+
+switch-to-app = Zu seinem Fenster wechseln
+show-launch-log = Ausgabe des letzten Starts anzeigen
+
+add-to-autostart = Beim Anmelden starten
+autostart-failed = Autostart konnte nicht geändert werden, siehe Log
+
+package-info = Paketinformationen anzeigen
+package-uninstall = Deinstallieren…
+package-unknown = Das Paket von { $app } wurde nicht gefunden
+package-no-terminal = Setze [terminal] command oder $TERMINAL, um Pakete zu verwalten
+package-press-enter = Enter drücken zum Schließen
+
+## Launching with the clipboard (Alt+Enter)
+
+clipboard-invalid = Die Zwischenablage enthält keine Datei und keine URL
+clipboard-unsupported = { $app } kann den Inhalt der Zwischenablage nicht öffnen
+
+## Entries that aren't apps
+
+open-terminal = Terminal in { $dir } öffnen
+run-command = { $command } ausführen
+run-command-terminal = { $command } in einem Terminal ausführen
+web-search = Im Web nach „{ $query }“ suchen
+web-search-site = Auf { $site } nach „{ $query }“ suchen
+copy-secret = Passwort von { $name } kopieren
+secrets-loading = Passwörter werden geladen
+capture-region = Bildschirmfoto eines Bereichs aufnehmen
+capture-screen = Bildschirmfoto des Bildschirms aufnehmen
+pick-color = Farbe auf dem Bildschirm auswählen
+toggle-night-light = Nachtlicht
+toggle-do-not-disturb = Bitte nicht stören
+toggle-dark-mode = Dunkler Modus
+toggle-airplane-mode = Flugmodus
+toggle-on = An
+toggle-off = Aus
+open-dir = { $dir } im Dateimanager öffnen
+open-dir-terminal = { $dir } in einem Terminal öffnen
+open-dir-editor = { $dir } im Editor öffnen
+open-project = { $name } in { $editor } öffnen
+media-play = Abspielen
+media-pause = Pausieren
+media-next = Nächster Titel
+media-previous = Vorheriger Titel
+copy-color = { $color } kopieren
+base-binary = Binär
+base-octal = Oktal
+base-decimal = Dezimal
+base-hexadecimal = Hexadezimal
+app-new = neu
+app-running = Läuft bereits, über das Menü dorthin wechseln
+app-crashed = Direkt nach dem Start beendet, die Ausgabe ist im Menü
+confirm-entry = Zum Bestätigen erneut Enter drücken
+
+## Notifications
+
+launch-failed = { $name } konnte nicht gestartet werden
+launching = { $name } wird gestartet…
+launch-failed-log = Die Ausgabe steht in { $path }
+
+## The settings view (Ctrl+,)
+
+settings-search = Suche
+settings-matcher = Suchverfahren
+settings-deterministic = Gleich bewertete Apps in fester Reihenfolge zeigen
+settings-phonetic = Auch ähnlich klingende Namen finden
+settings-time-of-day = Apps bevorzugen, die zu dieser Tageszeit genutzt werden
+settings-half-life = Tage, bis ein Start nur noch halb zählt
+settings-min-score = Apps unter diesem Anteil der besten Bewertung auslassen
+settings-results = Weitere Ergebnisse
+settings-web = Websuche anbieten, wenn keine App gut passt
+settings-providers = Ergebnisse der Suchanbieter anderer Apps zeigen
+settings-web-threshold = Websuche unter dieser Bewertung
+settings-providers-max = Ergebnisse pro Suchanbieter
+settings-home = Leere Suche
+settings-home-enabled = Angeheftete, zuletzt und meist genutzte Apps zeigen
+settings-home-pinned = Angeheftete Apps
+settings-home-recent = Zuletzt genutzte Apps
+settings-home-most-used = Meist genutzte Apps
+settings-new-apps = Neue Apps
+settings-new-apps-days = Tage, die eine App als neu gilt
+settings-new-apps-boost = Bewertungsbonus
+settings-launch = Starten
+settings-launch-notify = Benachrichtigen, wenn eine App startet
+settings-launch-login-env = Umgebung einer Login-Shell verwenden
+settings-window = Fenster
+settings-running-indicator = Laufende Apps markieren
+settings-reduce-motion = Bewegung reduzieren
+settings-transition = Dauer der Überblendung in Millisekunden
+settings-theme = Farben
+settings-theme-reset = Zurücksetzen
+settings-keys = Tastenkürzel
+settings-key-invalid = Kein Tastenkürzel
+settings-key-pin = Ausgewählte App anheften oder lösen
+settings-key-details = Details der ausgewählten App
+settings-key-explain = Wie die Bewertungen zustande kamen
+settings-key-stats = Nutzungsstatistik
+settings-key-history = Startverlauf
+settings-key-settings = Einstellungen
+settings-key-reload-icons = Symbole neu laden
+settings-key-rescan = Anwendungen neu suchen
+settings-apps = Anwendungen
+settings-merge-duplicates = Doppelt installierte Apps nur einmal zeigen
+settings-ignore = Apps auslassen, die auf diese Globs passen, einer pro Zeile
+settings-save = Speichern
+settings-save-failed = Die Einstellungen konnten nicht gespeichert werden
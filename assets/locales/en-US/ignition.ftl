@@ -0,0 +1,165 @@
+# The placeholder of the empty search bar.
+search-placeholder = Search for a program
+
+## Messages shown in the search bar
+
+case-sensitive = Case-sensitive
+caps-lock-ignored = CapsLock Ignored
+indexing-icons = Indexing Icons { $finished }/{ $total } (this may freeze)
+
+## Sections of the empty query view
+
+section-pinned = Pinned
+section-recent = Recently used
+section-most-used = Most used
+
+## Sections of the category view, the freedesktop main categories
+
+category-audio-video = Multimedia
+category-development = Development
+category-education = Education
+category-game = Games
+category-graphics = Graphics
+category-network = Internet
+category-office = Office
+category-science = Science
+category-settings = Settings
+category-system = System
+category-utility = Accessories
+category-other = Other
+
+## The details tooltip of an app
+
+details-path = Path
+details-exec = Exec
+details-categories = Categories
+details-source = Source
+source-desktop-entry = Desktop entry
+source-link = Link
+source-start-menu = Start Menu shortcut
+source-start-apps = Packaged app
+source-settings = Settings
+source-appimage = AppImage
+source-app-bundle = Application bundle
+
+## The usage statistics view
+
+stats-launches = { $count } launches in the last 30 days
+stats-average-time = { $seconds }s from opening to launching on average
+stats-most-used = Most launched
+stats-per-day = Launches per day
+
+## The context menu of an app
+
+tags-menu-title = Tags
+tags-menu-empty = No tags yet, type a #tag into the search to create one
+
+edit-entry = Edit desktop entry
+edit-entry-copy = Edit a copy in ~/.local/share/applications
+edit-entry-system = Edit the system file
+
+switch-to-app = Switch to its window
+show-launch-log = Show the output of its last start
+
+add-to-autostart = Start at login
+autostart-failed = Could not change what starts at login, see the log
+
+package-info = Show package info
+package-uninstall = Uninstall…
+package-unknown = Could not find the package { $app } was installed with
+package-no-terminal = Set [terminal] command or $TERMINAL to manage packages
+package-press-enter = Press Enter to close
+
+## Launching with the clipboard (Alt+Enter)
+
+clipboard-invalid = The clipboard holds no file or URL
+clipboard-unsupported = { $app } can't open what's on the clipboard
+
+## Entries that aren't apps
+
+open-terminal = Open terminal in { $dir }
+run-command = Run { $command }
+run-command-terminal = Run { $command } in a terminal
+web-search = Search the web for “{ $query }”
+web-search-site = Search { $site } for “{ $query }”
+copy-secret = Copy the password of { $name }
+secrets-loading = Loading passwords
+capture-region = Take a screenshot of a region
+capture-screen = Take a screenshot of the screen
+pick-color = Pick a color on the screen
+toggle-night-light = Night light
+toggle-do-not-disturb = Do not disturb
+toggle-dark-mode = Dark mode
+toggle-airplane-mode = Airplane mode
+toggle-on = On
+toggle-off = Off
+open-dir = Open { $dir } in the file manager
+open-dir-terminal = Open { $dir } in a terminal
+open-dir-editor = Open { $dir } in the editor
+open-project = Open { $name } in { $editor }
+media-play = Play
+media-pause = Pause
+media-next = Next track
+media-previous = Previous track
+copy-color = Copy { $color }
+base-binary = Binary
+base-octal = Octal
+base-decimal = Decimal
+base-hexadecimal = Hexadecimal
+app-new = new
+app-running = Running, switch to it from the menu
+app-crashed = Quit right after starting, its output is in the menu
+confirm-entry = Press Enter again to confirm
+
+## Notifications
+
+launch-failed = Could not launch { $name }
+launching = Launching { $name }…
+launch-failed-log = Its output is in { $path }
+
+## The settings view (Ctrl+,)
+
+settings-search = Search
+settings-matcher = Matcher
+settings-deterministic = Keep apps that score the same in a fixed order
+settings-phonetic = Also find names that sound like the search
+settings-time-of-day = Prefer apps you use around this time of day
+settings-half-life = Days until a launch counts half as much
+settings-min-score = Leave out apps below this share of the best score
+settings-results = Other results
+settings-web = Offer a web search when no app matches well
+settings-providers = Show results of other apps' search providers
+settings-web-threshold = Web search below this score
+settings-providers-max = Results per search provider
+settings-home = Empty search
+settings-home-enabled = Show pinned, recent and most used apps
+settings-home-pinned = Pinned apps
+settings-home-recent = Recent apps
+settings-home-most-used = Most used apps
+settings-new-apps = New apps
+settings-new-apps-days = Days an app counts as new
+settings-new-apps-boost = Score boost
+settings-launch = Launching
+settings-launch-notify = Notify when an app starts
+settings-launch-login-env = Use the environment of a login shell
+settings-window = Window
+settings-running-indicator = Mark apps that are running
+settings-reduce-motion = Reduce motion
+settings-transition = Fade duration in milliseconds
+settings-theme = Colors
+settings-theme-reset = Reset
+settings-keys = Shortcuts
+settings-key-invalid = Not a shortcut
+settings-key-pin = Pin or unpin the selected app
+settings-key-details = Details of the selected app
+settings-key-explain = How the scores came together
+settings-key-stats = Usage statistics
+settings-key-history = Launch history
+settings-key-settings = Settings
+settings-key-reload-icons = Reload the icons
+settings-key-rescan = Scan the applications again
+settings-apps = Applications
+settings-merge-duplicates = Show apps installed twice once
+settings-ignore = Leave out apps matching these globs, one per line
+settings-save = Save
+settings-save-failed = Could not save the settings
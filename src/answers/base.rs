@@ -0,0 +1,99 @@
+//! Numbers in other bases, like `0xff` in decimal or `255 to hex`.
+
+use crate::i18n::tr;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Base {
+    Binary,
+    Octal,
+    Decimal,
+    Hexadecimal,
+}
+
+impl Base {
+    const ALL: [Base; 4] = [Base::Decimal, Base::Hexadecimal, Base::Binary, Base::Octal];
+
+    fn radix(self) -> u32 {
+        match self {
+            Base::Binary => 2,
+            Base::Octal => 8,
+            Base::Decimal => 10,
+            Base::Hexadecimal => 16,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Base> {
+        match name {
+            "bin" | "binary" => Some(Base::Binary),
+            "oct" | "octal" => Some(Base::Octal),
+            "dec" | "decimal" => Some(Base::Decimal),
+            "hex" | "hexadecimal" => Some(Base::Hexadecimal),
+            _ => None,
+        }
+    }
+
+    fn format(self, number: u128) -> String {
+        match self {
+            Base::Binary => format!("0b{number:b}"),
+            Base::Octal => format!("0o{number:o}"),
+            Base::Decimal => number.to_string(),
+            Base::Hexadecimal => format!("0x{number:x}"),
+        }
+    }
+
+    fn label(self) -> String {
+        match self {
+            Base::Binary => tr!("base-binary"),
+            Base::Octal => tr!("base-octal"),
+            Base::Decimal => tr!("base-decimal"),
+            Base::Hexadecimal => tr!("base-hexadecimal"),
+        }
+    }
+}
+
+/// Numbers like `0xff` in the other bases, or `255 to hex` in the one asked for.
+pub fn convert(query: &str) -> Option<Vec<(String, String)>> {
+    let query = query.trim().to_lowercase();
+    let words: Vec<&str> = query.split_whitespace().collect();
+    let (number, from, to) = match words.as_slice() {
+        [number] => {
+            let (number, from) = parse_number(number)?;
+            // Plain numbers would show conversions for every number typed.
+            if from == Base::Decimal {
+                return None;
+            }
+            (number, from, None)
+        }
+        [number, "to" | "in" | "as", base] => {
+            let (number, from) = parse_number(number)?;
+            (number, from, Some(Base::from_name(base)?))
+        }
+        _ => return None,
+    };
+
+    let answers = Base::ALL
+        .into_iter()
+        .filter(|base| match to {
+            Some(to) => *base == to,
+            None => *base != from,
+        })
+        .map(|base| (base.format(number), base.label()))
+        .collect();
+    Some(answers)
+}
+
+/// Reads `0b`, `0o` and `0x` numbers and decimal ones, `_` can separate digits.
+fn parse_number(text: &str) -> Option<(u128, Base)> {
+    let (digits, base) = match text.get(..2) {
+        Some("0b") => (&text[2..], Base::Binary),
+        Some("0o") => (&text[2..], Base::Octal),
+        Some("0x") => (&text[2..], Base::Hexadecimal),
+        _ => (text, Base::Decimal),
+    };
+    let digits = digits.replace('_', "");
+    if digits.is_empty() {
+        return None;
+    }
+    let number = u128::from_str_radix(&digits, base.radix()).ok()?;
+    Some((number, base))
+}
@@ -0,0 +1,140 @@
+//! The time in other places, like `time in tokyo`, from the system's time zone database.
+
+use chrono::{FixedOffset, Utc};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tracing::{debug, warn};
+
+/// How many zones a country or a partial city name shows.
+const MAX_ZONES: usize = 3;
+
+/// A zone of the database, like `America/New_York`.
+struct Zone {
+    name: String,
+    /// The last part of the name, like `New York`.
+    city: String,
+    /// The name of the country it's in, from `iso3166.tab`.
+    country: Option<String>,
+}
+
+/// The time in the places found by queries like `time in tokyo`, `tokyo time` or
+/// `time japan`.
+pub fn times(query: &str) -> Vec<(String, String)> {
+    let query = query.trim().to_lowercase();
+    let place = query
+        .strip_prefix("time in ")
+        .or_else(|| query.strip_suffix(" time"))
+        .or_else(|| query.strip_prefix("time "))
+        .map(str::trim);
+    let Some(place) = place.filter(|v| v.chars().count() >= 3) else {
+        return Vec::new();
+    };
+
+    let zones = zones();
+    let city = |zone: &&Zone| zone.city.to_lowercase();
+    let mut found: Vec<&Zone> = zones.iter().filter(|zone| city(zone) == place).collect();
+    if found.is_empty() {
+        found = zones
+            .iter()
+            .filter(|zone| {
+                let country = zone.country.as_deref().unwrap_or_default();
+                city(zone).starts_with(place) || country.to_lowercase() == place
+            })
+            .collect();
+    }
+
+    found
+        .into_iter()
+        .take(MAX_ZONES)
+        .filter_map(|zone| match time_in(zone) {
+            Ok(time) => Some(time),
+            Err(error) => {
+                debug!("Could not read the time zone {}: {error:?}", zone.name);
+                None
+            }
+        })
+        .collect()
+}
+
+/// The time as `14:32` and where and when that is.
+fn time_in(zone: &Zone) -> eyre::Result<(String, String)> {
+    let data = fs::read(zoneinfo_dir().join(&zone.name))?;
+    let time_zone = tz::TimeZone::from_tz_data(&data)?;
+    let now = Utc::now();
+    let local = time_zone.find_local_time_type(now.timestamp())?;
+    let offset = FixedOffset::east_opt(local.ut_offset())
+        .ok_or_else(|| eyre::eyre!("Invalid offset {}", local.ut_offset()))?;
+    let time = now.with_timezone(&offset);
+
+    let mut detail = format!("{}, {}", zone.city, time.format("%a %-d %b"));
+    // Zones without an abbreviation use their offset, like `+04`.
+    let abbreviation = local.time_zone_designation();
+    if !abbreviation.starts_with(['+', '-']) {
+        detail.push_str(&format!(", {abbreviation}"));
+    }
+    detail.push_str(&format!(" (UTC{offset})"));
+    Ok((time.format("%H:%M").to_string(), detail))
+}
+
+fn zoneinfo_dir() -> PathBuf {
+    env::var_os("TZDIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/usr/share/zoneinfo"))
+}
+
+fn zones() -> &'static [Zone] {
+    static ZONES: OnceLock<Vec<Zone>> = OnceLock::new();
+    ZONES.get_or_init(load_zones)
+}
+
+/// The zones of `zone1970.tab`, or the older `zone.tab`, and UTC.
+fn load_zones() -> Vec<Zone> {
+    let dir = zoneinfo_dir();
+    let countries: HashMap<String, String> = fs::read_to_string(dir.join("iso3166.tab"))
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(code, name)| (code.to_string(), name.trim().to_string()))
+        .collect();
+    let table = fs::read_to_string(dir.join("zone1970.tab"))
+        .or_else(|_| fs::read_to_string(dir.join("zone.tab")));
+    let table = match table {
+        Ok(table) => table,
+        Err(error) => {
+            warn!("Could not read the time zones in {dir:?}: {error}");
+            return Vec::new();
+        }
+    };
+
+    let mut zones: Vec<Zone> = table
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            // Country codes, coordinates, zone and comments. `zone1970.tab` lists all
+            // countries of a zone.
+            let mut fields = line.split('\t');
+            let codes = fields.next()?;
+            let name = fields.nth(1)?;
+            let city = name.rsplit('/').next()?.replace('_', " ");
+            let country = codes
+                .split(',')
+                .next()
+                .and_then(|code| countries.get(code).cloned());
+            Some(Zone {
+                name: name.to_string(),
+                city,
+                country,
+            })
+        })
+        .collect();
+    zones.push(Zone {
+        name: "UTC".to_string(),
+        city: "UTC".to_string(),
+        country: None,
+    });
+    zones
+}
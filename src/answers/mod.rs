@@ -0,0 +1,26 @@
+//! Instant answers, computed from the query and shown above everything else, like `0xff`
+//! in decimal. Enter copies them.
+
+mod base;
+mod clock;
+
+use crate::apps::AppId;
+use crate::search::{EntryAction, SearchResultEntry, SearchScore};
+
+/// The answers to `query`, in the order they are shown. Each answer is the text to copy and
+/// what it is.
+pub fn search(query: &str) -> Vec<SearchResultEntry> {
+    let mut answers = base::convert(query).unwrap_or_default();
+    answers.extend(clock::times(query));
+    answers
+        .into_iter()
+        .map(|(text, detail)| SearchResultEntry {
+            id: AppId::from_properties(&["answer", &text, &detail]),
+            score: SearchScore::default(),
+            detail: Some(detail),
+            broken: false,
+            action: Some(EntryAction::Answer(text)),
+            confirm: false,
+        })
+        .collect()
+}
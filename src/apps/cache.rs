@@ -0,0 +1,201 @@
+//! Keeps the results of slow app providers (like `Get-StartApps` on Windows) between runs,
+//! so opening ignition doesn't wait for them every time. What is read from files, like
+//! desktop entries, is kept in a [FileCache] until they change.
+
+use crate::config::Config;
+use crate::paths::{create_private_dir, write_private};
+use chrono::{DateTime, Local, Utc};
+use eyre::{Context, ContextCompat};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+use tracing::{debug, warn};
+
+static CACHE: OnceLock<ProviderCache> = OnceLock::new();
+
+struct ProviderCache {
+    dir: PathBuf,
+    /// Overrides of the default time to live, in seconds by provider name.
+    #[cfg_attr(not(windows), allow(dead_code))]
+    ttls: HashMap<String, u64>,
+}
+
+// Only the Windows providers are slow enough to cache so far.
+#[cfg_attr(not(windows), allow(dead_code))]
+#[derive(Serialize, Deserialize)]
+struct CachedResults<T> {
+    scanned_at: DateTime<Utc>,
+    values: T,
+}
+
+/// Caches providers in `dir`, without this they are scanned every time.
+pub fn init(dir: PathBuf, ttls: HashMap<String, u64>) {
+    let _ = CACHE.set(ProviderCache { dir, ttls });
+}
+
+/// The results of `provider`, scanned again with `scan` once they are older than its time
+/// to live. When scanning fails the outdated results are used.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn cached<T, F>(provider: &str, ttl: Duration, scan: F) -> eyre::Result<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> eyre::Result<T>,
+{
+    let Some(cache) = CACHE.get() else {
+        return scan();
+    };
+    let ttl = cache
+        .ttls
+        .get(provider)
+        .map(|v| Duration::from_secs(*v))
+        .unwrap_or(ttl);
+    let path = cache.dir.join(format!("{provider}.json"));
+
+    let now = Local::now().to_utc();
+    let is_fresh = |at: DateTime<Utc>| (now - at).to_std().is_ok_and(|age| age < ttl);
+    let cached = Config::<Option<CachedResults<T>>>::read_file(&path)
+        .inspect_err(|error| warn!("Could not read the {provider} cache: {error:?}"))
+        .ok()
+        .flatten();
+    match cached {
+        Some(cached) if is_fresh(cached.scanned_at) => {
+            debug!("Using the cached {provider} results");
+            Ok(cached.values)
+        }
+        cached => match scan() {
+            Ok(values) => {
+                let results = CachedResults {
+                    scanned_at: now,
+                    values: &values,
+                };
+                let written = create_private_dir(&cache.dir)
+                    .wrap_err("Failed to create cache dir")
+                    .and_then(|_| Config::write_file(&path, &results));
+                if let Err(error) = written {
+                    warn!("Could not cache the {provider} results: {error:?}");
+                }
+                Ok(values)
+            }
+            Err(error) => {
+                let Some(cached) = cached else {
+                    return Err(error);
+                };
+                warn!("Scanning {provider} failed, using older results: {error:?}");
+                Ok(cached.values)
+            }
+        },
+    }
+}
+
+/// A dir of its own for `provider` to keep more than its results in, like extracted icons.
+/// [clear] empties it too.
+pub fn provider_dir(provider: &str) -> Option<PathBuf> {
+    CACHE.get().map(|cache| cache.dir.join(provider))
+}
+
+/// Forgets every cached result, the next scan of each provider is a fresh one.
+pub fn clear() {
+    let Some(cache) = CACHE.get() else {
+        return;
+    };
+    if let Err(error) = fs::remove_dir_all(&cache.dir) {
+        if error.kind() != ErrorKind::NotFound {
+            warn!("Could not clear the provider cache: {error}");
+        }
+    }
+}
+
+/// Bumped when what a [FileCache] stores changes, like a new field of [crate::apps::App],
+/// older caches are then read again from the files.
+const FILE_CACHE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct CachedFile<T> {
+    modified: SystemTime,
+    value: T,
+}
+
+/// What was read from files, like parsed desktop entries, kept until the file changes.
+///
+/// It's read on every start, so it's stored with bincode instead of JSON.
+pub struct FileCache<T> {
+    path: Option<PathBuf>,
+    cached: HashMap<PathBuf, CachedFile<T>>,
+    /// The files asked for since [FileCache::load], the others are gone.
+    used: HashMap<PathBuf, CachedFile<T>>,
+}
+
+impl<T: Serialize + DeserializeOwned + Clone> FileCache<T> {
+    /// Loads the cache called `name`, it starts out empty when the cache isn't initialized.
+    pub fn load(name: &str) -> FileCache<T> {
+        let path = CACHE
+            .get()
+            .map(|cache| cache.dir.join(format!("{name}.bin")));
+        let cached = path
+            .as_deref()
+            .and_then(|path| match fs::read(path) {
+                Ok(bytes) => Some(bytes),
+                Err(error) if error.kind() == ErrorKind::NotFound => None,
+                Err(error) => {
+                    warn!("Could not read the {name} cache: {error}");
+                    None
+                }
+            })
+            .and_then(|bytes| {
+                let (version, cached): (u32, _) = bincode::deserialize(&bytes)
+                    .inspect_err(|error| debug!("Discarding the {name} cache: {error}"))
+                    .ok()?;
+                (version == FILE_CACHE_VERSION).then_some(cached)
+            })
+            .unwrap_or_default();
+        FileCache {
+            path,
+            cached,
+            used: HashMap::new(),
+        }
+    }
+
+    /// The cached value of `path`, or what `read` gives when the file changed since. Errors
+    /// aren't cached, the file is read again next time.
+    pub fn get_or_read<F>(&mut self, path: &Path, read: F) -> eyre::Result<T>
+    where
+        F: FnOnce() -> eyre::Result<T>,
+    {
+        let modified = fs::metadata(path)
+            .and_then(|v| v.modified())
+            .wrap_err("Failed to get the modification time")?;
+        let cached = self.cached.remove(path).filter(|v| v.modified == modified);
+        let value = match cached {
+            Some(cached) => cached.value,
+            None => read()?,
+        };
+        let file = CachedFile {
+            modified,
+            value: value.clone(),
+        };
+        self.used.insert(path.to_path_buf(), file);
+        Ok(value)
+    }
+
+    /// Writes the values of the files asked for since loading, the others are forgotten.
+    pub fn save(self) {
+        let Some(path) = self.path else {
+            return;
+        };
+        let written = bincode::serialize(&(FILE_CACHE_VERSION, &self.used))
+            .wrap_err("Failed to serialize")
+            .and_then(|bytes| {
+                let dir = path.parent().wrap_err("No cache dir")?;
+                create_private_dir(dir).wrap_err("Failed to create cache dir")?;
+                write_private(&path, bytes).wrap_err("Failed to write")
+            });
+        if let Err(error) = written {
+            warn!("Could not write the cache {path:?}: {error:?}");
+        }
+    }
+}
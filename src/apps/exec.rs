@@ -0,0 +1,252 @@
+//! The Exec key of desktop entries: its escapes, quoting and field codes like `%f` and
+//! `%U`, as the Desktop Entry spec describes them.
+
+use std::path::Path;
+
+/// What the field codes besides the files stand for.
+pub struct ExecContext<'a> {
+    /// For `%c`.
+    pub name: &'a str,
+    /// For `%i`, which becomes `--icon <icon>`.
+    pub icon: Option<&'a str>,
+    /// The desktop entry, for `%k`.
+    pub path: &'a Path,
+}
+
+/// Splits an Exec line into the program and its arguments.
+pub fn split(exec: &str) -> eyre::Result<Vec<String>> {
+    let exec = unescape_string(exec);
+    let mut args = Vec::new();
+    let mut current = String::new();
+    // Tells `""` apart from no argument.
+    let mut in_arg = false;
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            '"' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        // Inside quotes these four need a backslash.
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '`' | '$' | '\\')) => current.push(c),
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => eyre::bail!("Exec line ends in a backslash: {exec}"),
+                        },
+                        Some(c) => current.push(c),
+                        None => eyre::bail!("Exec line has an unclosed quote: {exec}"),
+                    }
+                }
+            }
+            c => {
+                in_arg = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    if args.is_empty() {
+        eyre::bail!("Exec line is empty");
+    }
+    Ok(args)
+}
+
+/// `exec` with its program replaced by `program`, the arguments are kept as they are.
+pub fn with_program(exec: &str, program: &str) -> String {
+    let exec = exec.trim_start();
+    let rest = match exec.strip_prefix('"') {
+        // The closing quote isn't preceded by a backslash.
+        Some(quoted) => quoted
+            .char_indices()
+            .find(|&(i, c)| c == '"' && !quoted[..i].ends_with('\\'))
+            .map(|(i, _)| &quoted[i + 1..]),
+        None => exec.find([' ', '\t']).map(|i| &exec[i..]),
+    };
+    format!("{}{}", quote(program), rest.unwrap_or_default())
+}
+
+/// Quotes `arg` for an Exec line when it has reserved characters.
+fn quote(arg: &str) -> String {
+    let arg = arg.replace('%', "%%");
+    let reserved = |c: char| c.is_whitespace() || "\"'\\><~|&;$*?#()`".contains(c);
+    if !arg.contains(reserved) {
+        return arg;
+    }
+    let mut quoted = String::from('"');
+    for c in arg.chars() {
+        match c {
+            // Backslashes are escaped once for the quoting and once for the string.
+            '\\' => quoted.push_str("\\\\\\\\"),
+            '"' | '`' | '$' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// The commands that open `files` with the arguments from [split], usually one. Apps that
+/// take a single file or URL (`%f`, `%u`) are started once for each.
+pub fn commands(args: &[String], files: &[String], context: &ExecContext) -> Vec<Vec<String>> {
+    let single = args
+        .iter()
+        .any(|arg| arg.contains("%f") || arg.contains("%u"));
+    let multiple = args.iter().any(|arg| arg == "%F" || arg == "%U");
+    if single && !multiple && files.len() > 1 {
+        return files
+            .iter()
+            .map(|file| expand(args, std::slice::from_ref(file), context))
+            .collect();
+    }
+    vec![expand(args, files, context)]
+}
+
+fn expand(args: &[String], files: &[String], context: &ExecContext) -> Vec<String> {
+    let mut expanded = Vec::new();
+    let mut took_files = false;
+    for arg in args {
+        // These only stand alone and can become several arguments.
+        match arg.as_str() {
+            "%F" => {
+                expanded.extend(files.iter().map(|file| to_path(file)));
+                took_files = true;
+                continue;
+            }
+            "%U" => {
+                expanded.extend(files.iter().map(|file| to_uri(file)));
+                took_files = true;
+                continue;
+            }
+            "%i" => {
+                if let Some(icon) = context.icon {
+                    expanded.extend(["--icon".to_string(), icon.to_string()]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        let mut value = String::new();
+        let mut chars = arg.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                value.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('%') => value.push('%'),
+                Some('f') => {
+                    took_files = true;
+                    value.extend(files.first().map(|file| to_path(file)));
+                }
+                Some('u') => {
+                    took_files = true;
+                    value.extend(files.first().map(|file| to_uri(file)));
+                }
+                Some('c') => value.push_str(context.name),
+                Some('k') => value.push_str(&context.path.to_string_lossy()),
+                // Deprecated codes like `%d` and `%m` are removed.
+                _ => {}
+            }
+        }
+        // Only codes that stood for nothing, like `%f` without a file.
+        if value.is_empty() && !arg.is_empty() {
+            continue;
+        }
+        expanded.push(value);
+    }
+    // Apps that don't say what they take still get to try.
+    if !took_files {
+        expanded.extend(files.iter().cloned());
+    }
+    expanded
+}
+
+/// Undoes the escapes every string value of a desktop entry can have.
+fn unescape_string(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            // Escapes of the quoting, like `\"`, are undone by [split].
+            Some(c) => {
+                unescaped.push('\\');
+                unescaped.push(c);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// A `file://` URL as a path, other URLs stay as they are.
+fn to_path(file: &str) -> String {
+    match file.strip_prefix("file://") {
+        Some(path) => percent_decode(path),
+        None => file.to_string(),
+    }
+}
+
+/// A path as a `file://` URL.
+fn to_uri(file: &str) -> String {
+    if !file.starts_with('/') {
+        return file.to_string();
+    }
+    let mut uri = String::from("file://");
+    for byte in file.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    uri
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
@@ -1,383 +1,317 @@
-use std::cmp::Ordering;
-use std::ffi::OsStr;
-use std::fmt::{Display, Formatter};
-use std::io;
-use std::path::{Path, PathBuf};
-use std::str::FromStr;
+//! Finds icons by name like the freedesktop Icon Theme spec says: in the theme, then in the
+//! themes it inherits from, then in hicolor, and last the unthemed icons in `pixmaps`.
+//!
+//! Which directory of a theme has which size comes from its `index.theme`.
+
 use dirs::home_dir;
-use tracing::{debug, info, warn};
+use ini::{Ini, Properties};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
 use xdg::BaseDirectories;
+
+/// The theme every other one falls back to, it has the icons apps install.
+const FALLBACK_THEME: &str = "hicolor";
+/// Only these can be rendered, `xpm` can't.
+const EXTENSIONS: [&str; 2] = ["png", "svg"];
+
 pub struct IconFinder {
-    dirs: Vec<CachedDir>,
+    /// The dirs themes are in, like `~/.local/share/icons`, most important first.
+    base_dirs: Vec<PathBuf>,
+    /// The dirs with unthemed icons, like `/usr/share/pixmaps`.
+    pixmap_dirs: Vec<PathBuf>,
+    /// The theme, the themes it inherits from and hicolor, in the order they are looked in.
+    themes: Vec<IconTheme>,
+    listings: Listings,
 }
 
+/// The file names in each dir, listed the first time an icon is looked for there.
+type Listings = HashMap<PathBuf, HashSet<String>>;
+
 impl IconFinder {
-    pub fn new() -> IconFinder {
-        warn!("Creating IconFinder, this means that we are going to be looking for icons on your system");
-        warn!("This may take a while.");
+    /// Looks in `theme` first, or in the `default` theme when there is none.
+    pub fn new(theme: Option<&str>) -> IconFinder {
+        let (base_dirs, pixmap_dirs) = icon_base_dirs();
+        let themes = theme_chain(theme.unwrap_or("default"), &base_dirs);
+        info!(
+            "Looking for icons in the themes {:?}",
+            themes.iter().map(|v| v.name.as_str()).collect::<Vec<_>>()
+        );
         IconFinder {
-            dirs: icon_theme_base_paths()
-                .into_iter()
-                .map(|v| {
-                    debug!("SEARCHING IN {v:?}");
-                    CachedDir::visit(&v).unwrap()
-                })
-                .collect(),
+            base_dirs,
+            pixmap_dirs,
+            themes,
+            listings: HashMap::new(),
         }
     }
 
-    fn start_find(
-        dir: &mut CachedDir,
-        icon_name: &str,
-        out: &mut Vec<IconLocation>,
-        step: usize,
-    ) -> u32 {
-        match step {
-            0 => {
-                if let Some(subdir) = dir.join("hicolor") {
-                    return subdir.find(
-                        icon_name,
-                        out,
-                        true,
-                        IconDescriptor {
-                            theme: Some("hicolor".to_string()),
-                            ..IconDescriptor::default()
-                        },
-                    );
-                }
-            }
-            1 => {
-                if let Some(subdir) = dir.join("default") {
-                    return subdir.find(
-                        icon_name,
-                        out,
-                        true,
-                        IconDescriptor {
-                            theme: Some("default".to_string()),
-                            ..IconDescriptor::default()
-                        },
-                    );
-                }
-            }
-            2 => {
-                return dir.find(icon_name, out, false, IconDescriptor::default());
-            }
-            3 => {
-                return dir.find(icon_name, out, true, IconDescriptor::default());
-            }
-            _ => {}
-        }
-
-        0
-    }
-    pub fn find(&mut self, icon_name: &str) -> Vec<IconLocation> {
-        let mut out = Vec::new();
-        for i in 0..4 {
-            if i == 3 {
-                warn!("We could not find icon \"{icon_name}\" by simple means.");
-                warn!("Scanning the entire tree. (this may take a while)");
-            }
-            let mut found_any = false;
-            for dir in &mut self.dirs {
-                found_any |= Self::start_find(dir, icon_name, &mut out, i) > 0;
-            }
+    /// The file of the icon named `icon_name` closest to `size` at `scale`, from the first
+    /// theme that has it.
+    pub fn find(&mut self, icon_name: &str, size: u16, scale: u16) -> Option<PathBuf> {
+        // Some entries name the file, which the spec doesn't allow but works elsewhere.
+        let icon_name = EXTENSIONS
+            .iter()
+            .find_map(|extension| icon_name.strip_suffix(&format!(".{extension}")))
+            .unwrap_or(icon_name);
 
-            if found_any {
-                break;
-            }
+        let listings = &mut self.listings;
+        let themed = self
+            .themes
+            .iter()
+            .find_map(|theme| theme.find(listings, icon_name, size, scale));
+        if themed.is_some() {
+            return themed;
         }
 
-        let any_exact_matches = out.iter().any(|v| v.file_name == icon_name);
-        if any_exact_matches {
-            out.retain(|location| location.file_name == icon_name);
-        } else {
-            warn!("Did not find exact match!!");
+        let unthemed = self
+            .base_dirs
+            .iter()
+            .chain(&self.pixmap_dirs)
+            .find_map(|dir| find_file(listings, dir, icon_name));
+        if unthemed.is_none() {
+            debug!("Found no icon named {icon_name:?}");
         }
-
-        out
+        unthemed
     }
 }
 
-pub struct CachedDir {
-    path: PathBuf,
+/// An icon theme, read from its `index.theme`.
+struct IconTheme {
     name: String,
-    entries: Option<Vec<CachedDirEntry>>,
+    /// The dirs of the theme in every base dir, it can be spread over several.
+    dirs: Vec<PathBuf>,
+    subdirs: Vec<ThemeSubdir>,
+    /// The themes to look in when this one doesn't have an icon.
+    inherits: Vec<String>,
 }
 
-impl CachedDir {
-    pub fn visit(path: &Path) -> io::Result<CachedDir> {
-        assert!(path.is_dir());
-        Ok(CachedDir {
-            path: path.to_path_buf(),
-            name: to_string(path.file_name().unwrap()),
-            entries: None,
-        })
-    }
-
-    fn resolve_entries(path: PathBuf) -> io::Result<Vec<CachedDirEntry>> {
-        let dir = path.read_dir()?;
-        let mut entries = Vec::new();
-        for entry in dir {
-            let entry = entry?;
-            let Some(dir_entry) = CachedDirEntry::visit(&entry.path())? else {
-                continue;
-            };
-            entries.push(dir_entry);
-        }
-
-        Ok(entries)
-    }
-
-    pub fn entries(&mut self) -> &mut Vec<CachedDirEntry> {
-        let buf = self.path.clone();
-        self.entries
-            .get_or_insert_with(|| Self::resolve_entries(buf).unwrap())
-    }
-    pub fn files(&mut self) -> Vec<&String> {
-        self.entries()
+impl IconTheme {
+    fn load(name: &str, base_dirs: &[PathBuf]) -> Option<IconTheme> {
+        let dirs: Vec<PathBuf> = base_dirs
             .iter()
-            .flat_map(|entry| {
-                let CachedDirEntry::File(file) = entry else {
-                    return None;
-                };
-
-                Some(file)
-            })
-            .collect()
-    }
-    pub fn dirs(&mut self) -> Vec<&mut CachedDir> {
-        self.entries()
-            .iter_mut()
-            .flat_map(|entry| {
-                let CachedDirEntry::Directory(dir) = entry else {
-                    return None;
-                };
-
-                Some(&mut **dir)
-            })
-            .collect()
-    }
-
-    pub fn join(&mut self, name: &str) -> Option<&mut CachedDir> {
-        for entry in self.entries() {
-            let CachedDirEntry::Directory(dir) = entry else {
-                continue;
-            };
-
-            if to_string(dir.path.file_name().expect("File name")) != name {
-                continue;
+            .map(|base| base.join(name))
+            .filter(|dir| dir.is_dir())
+            .collect();
+        let index = dirs
+            .iter()
+            .map(|dir| dir.join("index.theme"))
+            .find(|path| path.is_file())?;
+        let info = match Ini::load_from_file_opt(
+            &index,
+            ini::ParseOption {
+                enabled_quote: false,
+                enabled_escape: false,
+            },
+        ) {
+            Ok(info) => info,
+            Err(error) => {
+                debug!("Could not read {index:?}: {error}");
+                return None;
             }
-
-            return Some(dir);
-        }
-
-        None
+        };
+        let theme = info.section(Some("Icon Theme"))?;
+        let list = |key: &str| -> Vec<String> {
+            theme
+                .get(key)
+                .unwrap_or_default()
+                .split(',')
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .collect()
+        };
+        let mut subdir_names = list("Directories");
+        subdir_names.extend(list("ScaledDirectories"));
+        let subdirs = subdir_names
+            .into_iter()
+            .filter_map(|path| {
+                let properties = info.section(Some(path.as_str()))?;
+                ThemeSubdir::parse(path, properties)
+            })
+            .collect();
+        Some(IconTheme {
+            name: name.to_string(),
+            dirs,
+            subdirs,
+            inherits: list("Inherits"),
+        })
     }
 
-    pub fn find(
-        &mut self,
+    /// The icon in the subdir closest to `size`, preferring those for `scale` and for apps.
+    fn find(
+        &self,
+        listings: &mut Listings,
         icon_name: &str,
-        out: &mut Vec<IconLocation>,
-        deep: bool,
-        desc: IconDescriptor,
-    ) -> u32 {
-        let desc = desc.apply(&self.name);
-
-        let mut found = 0;
-
-        if deep {
-            for dir in self.dirs() {
-                found += dir.find(icon_name, out, deep, desc.clone());
+        size: u16,
+        scale: u16,
+    ) -> Option<PathBuf> {
+        let mut best: Option<((u32, bool, bool), PathBuf)> = None;
+        for subdir in &self.subdirs {
+            let rank = (
+                subdir.distance(size, scale),
+                subdir.scale != scale,
+                !subdir.is_applications(),
+            );
+            if best.as_ref().is_some_and(|(best, _)| *best <= rank) {
+                continue;
             }
-        }
-
-        let path = self.path.clone();
-        for file in self.files() {
-            if file.contains(icon_name) {
-                let path = path.join(file);
-                let file_name = to_string(path.file_stem().unwrap());
-                // HACK: don't panic on None varient: my (admittly cursed) artix system shows
-                // entries like:
-                // - not-allowed
-                // - no-drop
-                // this is because it scans through ALL of .icons... but in there i have my
-                // cursors.
-                if let None = path.extension() {
-                    info!("path.extension() yields a None varient... skipping file_name: {file_name}, and path: {:?}", path);
-                    continue;
-                }
-                let extension = to_string(path.extension().unwrap());
-
-                if extension == "xpm" {
-                    continue;
-                }
-                // we only count exact matches
-                if file_name == icon_name {
-                    found += 1;
-                }
-
-                out.push(IconLocation {
-                    file_name,
-                    path,
-                    descriptor: desc.clone(),
-                });
+            let found = self
+                .dirs
+                .iter()
+                .find_map(|dir| find_file(listings, &dir.join(&subdir.path), icon_name));
+            if let Some(path) = found {
+                best = Some((rank, path));
             }
         }
-
-        found
-    }
-}
-
-pub enum CachedDirEntry {
-    File(String),
-    Directory(Box<CachedDir>),
-}
-
-impl CachedDirEntry {
-    pub fn visit(path: &Path) -> io::Result<Option<CachedDirEntry>> {
-        Ok(if path.is_dir() {
-            Some(CachedDirEntry::Directory(Box::new(CachedDir::visit(path)?)))
-        } else if path.is_file() {
-            let file_name = to_string(path.file_name().unwrap());
-            Some(CachedDirEntry::File(file_name))
-        } else {
-            None
-        })
+        best.map(|(_, path)| path)
     }
 }
 
-pub struct IconLocation {
-    pub path: PathBuf,
-    pub file_name: String,
-    pub descriptor: IconDescriptor,
-}
-
-#[derive(Clone, PartialEq, Eq)]
-pub struct IconDescriptor {
-    pub size: Option<IconSize>,
-    // @2x for example
-    pub scale: u32,
-    pub theme: Option<String>,
+/// A directory of a theme and the icons it has, like `48x48/apps`.
+struct ThemeSubdir {
+    path: String,
+    size: u16,
+    scale: u16,
+    min_size: u16,
+    max_size: u16,
+    threshold: u16,
+    kind: SubdirKind,
+    /// What its icons are for, like `Applications` or `MimeTypes`.
+    context: Option<String>,
 }
 
-#[derive(Clone, PartialEq, Eq, Ord, PartialOrd)]
-pub struct IconDescOrd {
-    size_score: u16,
-    scale: u32,
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SubdirKind {
+    /// Only for `size`.
+    Fixed,
+    /// For anything from `min_size` to `max_size`.
+    Scalable,
+    /// For sizes up to `threshold` away from `size`.
+    Threshold,
 }
 
-impl Display for IconDescriptor {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self.size {
-            None => write!(f, "unknown"),
-            Some(IconSize::Scalable) => write!(f, "scalable"),
-            Some(IconSize::Fixed(size)) => write!(f, "{size}"),
-        }?;
-
-        if self.scale != 1 {
-            write!(f, "@{}x", self.scale)?;
-        }
-
-        Ok(())
-    }
-}
-impl IconDescriptor {
-    // Higher is better
-    pub fn ord(&self, target: u16) -> IconDescOrd {
-        let size_score = match self.size {
-            None => 0,
-            Some(IconSize::Scalable) => u16::MAX - 1,
-            Some(IconSize::Fixed(size)) => match size.cmp(&target) {
-                Ordering::Equal => u16::MAX,
-                Ordering::Greater => {
-                    let distance = size - target;
-                    u16::MAX - distance
-                }
-                Ordering::Less => {
-                    let distance = target - size;
-                    (u16::MAX / 2) - distance
-                }
-            },
+impl ThemeSubdir {
+    fn parse(path: String, properties: &Properties) -> Option<ThemeSubdir> {
+        let number = |key: &str| {
+            properties
+                .get(key)
+                .and_then(|v| v.trim().parse::<u16>().ok())
+        };
+        let size = number("Size")?;
+        let kind = match properties.get("Type").unwrap_or("Threshold") {
+            "Fixed" => SubdirKind::Fixed,
+            "Scalable" => SubdirKind::Scalable,
+            _ => SubdirKind::Threshold,
         };
+        Some(ThemeSubdir {
+            path,
+            size,
+            scale: number("Scale").unwrap_or(1).max(1),
+            min_size: number("MinSize").unwrap_or(size),
+            max_size: number("MaxSize").unwrap_or(size),
+            threshold: number("Threshold").unwrap_or(2),
+            kind,
+            context: properties.get("Context").map(str::to_string),
+        })
+    }
 
-        IconDescOrd {
-            size_score,
-            scale: self.scale,
+    /// How far its icons are from `size` at `scale` in pixels, 0 when they fit.
+    fn distance(&self, size: u16, scale: u16) -> u32 {
+        let wanted = u32::from(size) * u32::from(scale);
+        let scaled = |size: u16| u32::from(size) * u32::from(self.scale);
+        let (min, max) = match self.kind {
+            SubdirKind::Fixed => (scaled(self.size), scaled(self.size)),
+            SubdirKind::Scalable => (scaled(self.min_size), scaled(self.max_size)),
+            SubdirKind::Threshold => (
+                scaled(self.size.saturating_sub(self.threshold)),
+                scaled(self.size.saturating_add(self.threshold)),
+            ),
+        };
+        if wanted < min {
+            min - wanted
+        } else {
+            wanted.saturating_sub(max)
         }
     }
-    pub fn apply(mut self, dir_name: &str) -> IconDescriptor {
-        let mut dir_name = dir_name.to_string();
-        if let Some((left_name, scale)) = dir_name.split_once("@") {
-            assert!((1..=2).contains(&scale.len()));
 
-            self.scale = u32::from_str(&scale[0..1]).unwrap();
-            dir_name = left_name.to_string();
-        }
+    fn is_applications(&self) -> bool {
+        self.context.as_deref() == Some("Applications")
+    }
+}
 
-        // Parse size
-        if dir_name == "scalable" {
-            self.size = Some(IconSize::Scalable);
+/// `name` and the themes it inherits from, breadth first, with hicolor last.
+fn theme_chain(name: &str, base_dirs: &[PathBuf]) -> Vec<IconTheme> {
+    let mut themes = Vec::new();
+    let mut seen = HashSet::new();
+    let mut queue = vec![name.to_string()];
+    while !queue.is_empty() {
+        let name = queue.remove(0);
+        if name == FALLBACK_THEME || !seen.insert(name.clone()) {
+            continue;
         }
-
-        // 32x32 format
-        if dir_name.chars().filter(|c| c == &'x').count() == 1 {
-            if let Some((left, right)) = dir_name.split_once("x") {
-                if let (Ok(left), Ok(right)) = (u16::from_str(left), u16::from_str(right)) {
-                    self.size = Some(IconSize::Fixed(left.max(right)));
-                }
+        match IconTheme::load(&name, base_dirs) {
+            Some(theme) => {
+                queue.extend(theme.inherits.iter().cloned());
+                themes.push(theme);
             }
+            None => debug!("Found no icon theme named {name:?}"),
         }
-
-        // Straight number format (32)
-        if let Ok(size) = u16::from_str(&dir_name) {
-            self.size = Some(IconSize::Fixed(size));
-        }
-
-        if self.theme.is_none() {
-            self.theme = Some(dir_name);
-        }
-
-        self
     }
+    themes.extend(IconTheme::load(FALLBACK_THEME, base_dirs));
+    themes
 }
-impl Default for IconDescriptor {
-    fn default() -> Self {
-        IconDescriptor {
-            size: None,
-            scale: 1,
-            theme: None,
-        }
-    }
+
+/// `dir/icon_name.png` or `.svg`, when it exists.
+fn find_file(listings: &mut Listings, dir: &Path, icon_name: &str) -> Option<PathBuf> {
+    let files = listings
+        .entry(dir.to_path_buf())
+        .or_insert_with(|| list_files(dir));
+    EXTENSIONS
+        .iter()
+        .map(|extension| format!("{icon_name}.{extension}"))
+        .find(|file| files.contains(file))
+        .map(|file| dir.join(file))
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
-pub enum IconSize {
-    Scalable,
-    Fixed(u16),
+fn list_files(dir: &Path) -> HashSet<String> {
+    let Ok(entries) = dir.read_dir() else {
+        return HashSet::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect()
 }
 
-fn icon_theme_base_paths() -> Vec<PathBuf> {
-    let home_icon_dir = home_dir().expect("No $HOME directory").join(".icons");
-    let mut data_dirs: Vec<_> = BaseDirectories::new()
+/// The dirs themes are in and the dirs with unthemed icons, in the order of the spec:
+/// `~/.icons`, then `icons` in the XDG data dirs, then `pixmaps`.
+fn icon_base_dirs() -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let mut data_dirs: Vec<PathBuf> = BaseDirectories::new()
         .map(|bd| {
-            let mut data_dirs: Vec<_> = bd
-                .get_data_dirs()
-                .into_iter()
-                .flat_map(|p| [p.join("icons"), p.join("pixmaps")])
-                .collect();
-            let data_home = bd.get_data_home();
-            data_dirs.push(data_home.join("icons"));
-            data_dirs.push(data_home.join("pixmaps"));
+            let mut data_dirs = vec![bd.get_data_home()];
+            data_dirs.extend(bd.get_data_dirs());
             data_dirs
         })
         .unwrap_or_default();
-    data_dirs.push(home_icon_dir);
-    for bufg in &data_dirs {
-        info!("Found {bufg:?}");
-    }
-    data_dirs.into_iter().filter(|p| p.exists()).collect()
-}
-
-fn to_string(os: &OsStr) -> String {
-    os.to_str().unwrap().to_string()
+    #[cfg(all(unix, not(target_os = "macos")))]
+    data_dirs.extend(
+        crate::platform::flatpak::host_data_dirs()
+            .into_iter()
+            .chain(crate::platform::flatpak::export_dirs()),
+    );
+    // The Flatpak exports are often in `XDG_DATA_DIRS` already.
+    let mut seen = HashSet::new();
+    data_dirs.retain(|p| seen.insert(p.clone()));
+
+    let base_dirs = home_dir()
+        .map(|home| home.join(".icons"))
+        .into_iter()
+        .chain(data_dirs.iter().map(|p| p.join("icons")))
+        .filter(|p| p.is_dir())
+        .collect();
+    let pixmap_dirs = data_dirs
+        .iter()
+        .map(|p| p.join("pixmaps"))
+        .filter(|p| p.is_dir())
+        .collect();
+    (base_dirs, pixmap_dirs)
 }
@@ -9,7 +9,10 @@ use image::{DynamicImage, RgbaImage};
 use resvg::tiny_skia;
 use resvg::tiny_skia::Pixmap;
 use resvg::usvg::{Options, Tree};
+use icns::{IconFamily, PixelFormat};
+use pelite::{FileMap, PeFile};
 use std::fs::read_to_string;
+use std::io::{BufReader, Cursor};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
@@ -21,6 +24,8 @@ pub struct LoadIconTaskRequest {
     pub app_name: String,
     pub icon: String,
     pub location: IconLocation,
+    /// The width and height to render at, in pixels.
+    pub size: u32,
 }
 pub enum LoadIconTaskResponse {
     Success(IconLocation),
@@ -40,7 +45,8 @@ pub struct IconLoader {
 }
 
 impl IconLoader {
-    pub fn new() -> IconLoader {
+    /// Finds icons in `theme`, see [IconFinder::new].
+    pub fn new(theme: Option<String>) -> IconLoader {
         let (sender_rq, receiver_rq) = bounded::<LoadIconTaskRequest>(16);
         let (sender_rs, receiver_rs) = unbounded::<LoadIconTaskResponse>();
 
@@ -48,33 +54,25 @@ impl IconLoader {
             let responder = sender_rs;
             let requester = receiver_rq;
 
-            let mut finder = IconFinder::new();
+            let mut finder = IconFinder::new(theme.as_deref());
             loop {
                 match requester.recv() {
                     Ok(request) => {
                         let source = request.icon;
                         let source_path = PathBuf::from(&source);
                         let icon_path = if !source_path.is_absolute() {
-                            let mut vec = finder.find(&source);
-                            // To make the icons order stable!
-                            vec.sort_by_cached_key(|v| v.path.clone());
-                            vec.sort_by_cached_key(|v| v.descriptor.theme.clone());
-                            vec.reverse();
-                            vec.sort_by_cached_key(|v| v.descriptor.ord(PREFERRED_ICON_SIZE));
-                            vec.reverse();
-
-                            if let Some(location) = vec.first() {
-                                location.path.clone()
-                            } else {
-                                source_path
-                            }
+                            // Themes have `@2x` dirs for HiDPI, with icons twice the size.
+                            let scale = request.size.div_ceil(PREFERRED_ICON_SIZE_U32) as u16;
+                            finder
+                                .find(&source, PREFERRED_ICON_SIZE, scale)
+                                .unwrap_or(source_path)
                         } else {
                             source_path
                         };
 
-                        let response = match Self::load_icon(&request.location, &icon_path)
-                            .wrap_err_with(|| format!("Icon at {icon_path:?}"))
-                        {
+                        let loaded = Self::load_icon(&request.location, &icon_path, request.size)
+                            .wrap_err_with(|| format!("Icon at {icon_path:?}"));
+                        let response = match loaded {
                             Ok(_) => LoadIconTaskResponse::Success(request.location),
                             Err(error) => {
                                 //error!("failed to load icon for {}", request.app_name);
@@ -148,16 +146,12 @@ impl IconLoader {
         output
     }
 
-    fn load_icon(output: &IconLocation, icon_path: &Path) -> eyre::Result<()> {
-        let image = Self::render_icon(icon_path).wrap_err("Could not render icon")?;
-        if image.width() < PREFERRED_ICON_SIZE_U32 || image.height() < PREFERRED_ICON_SIZE_U32 {
-            warn!("Icon {icon_path:?} is smaller than {PREFERRED_ICON_SIZE_U32}x{PREFERRED_ICON_SIZE_U32}", )
+    fn load_icon(output: &IconLocation, icon_path: &Path, size: u32) -> eyre::Result<()> {
+        let image = Self::render_icon(icon_path, size).wrap_err("Could not render icon")?;
+        if image.width() < size || image.height() < size {
+            warn!("Icon {icon_path:?} is smaller than {size}x{size}")
         }
-        let image = DynamicImage::from(image).resize_to_fill(
-            PREFERRED_ICON_SIZE_U32,
-            PREFERRED_ICON_SIZE_U32,
-            FilterType::Lanczos3,
-        );
+        let image = DynamicImage::from(image).resize_to_fill(size, size, FilterType::Lanczos3);
 
         image
             .save(&output.path)
@@ -165,17 +159,35 @@ impl IconLoader {
         Ok(())
     }
 
-    fn render_icon(icon: &Path) -> eyre::Result<RgbaImage> {
-        let extension = icon.extension().and_then(|v| v.to_str()).unwrap_or("");
+    /// Renders `icon`, picking the image closest to `size` when it has several.
+    fn render_icon(icon: &Path, size: u32) -> eyre::Result<RgbaImage> {
+        let (icon, resource_index) = split_resource_index(icon);
+        let icon = icon.as_path();
+        let extension = icon
+            .extension()
+            .and_then(|v| v.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let extension = extension.as_str();
         if extension == "svg" {
             let svg_data = read_to_string(icon).wrap_err("Failed to read svg")?;
-            let pixmap = Self::render_svg_icon(&svg_data).wrap_err("Failed to render svg")?;
+            let pixmap = Self::render_svg_icon(&svg_data, size).wrap_err("Failed to render svg")?;
 
             Ok(RgbaImage::from_raw(pixmap.width(), pixmap.height(), pixmap.take()).unwrap())
         } else if extension == "ico" {
             let file = std::fs::File::open(icon).wrap_err("Failed to read ico")?;
             let icon_dir = IconDir::read(file).wrap_err("Failed to read ico-dir")?;
-            let rgba = Self::render_ico_icon(icon_dir).wrap_err("Failed to render ico")?;
+            let rgba = Self::render_ico_icon(icon_dir, size).wrap_err("Failed to render ico")?;
+            Ok(rgba)
+        } else if extension == "icns" {
+            let file = std::fs::File::open(icon).wrap_err("Failed to read icns")?;
+            let family = IconFamily::read(BufReader::new(file)).wrap_err("Failed to read icns family")?;
+            let rgba = Self::render_icns_icon(&family, size).wrap_err("Failed to render icns")?;
+            Ok(rgba)
+        } else if extension == "exe" || extension == "dll" {
+            let icon_dir = Self::extract_exe_icon(icon, resource_index)
+                .wrap_err("Failed to extract icon resource")?;
+            let rgba = Self::render_ico_icon(icon_dir, size).wrap_err("Failed to render ico")?;
             Ok(rgba)
         } else {
             let image = image::open(icon).wrap_err("Could not read image.")?;
@@ -183,11 +195,45 @@ impl IconLoader {
         }
     }
 
-    fn render_ico_icon(icon_dir: IconDir) -> eyre::Result<RgbaImage> {
+    fn render_icns_icon(family: &IconFamily, size: u32) -> eyre::Result<RgbaImage> {
+        let icon_type = family
+            .available_icons()
+            .into_iter()
+            .min_by_key(|icon_type| (i64::from(size) - icon_type.pixel_width() as i64).abs())
+            .wrap_err("Icon family is empty")?;
+        let image = family
+            .get_icon_with_type(icon_type)
+            .wrap_err("Failed to decode icns")?
+            .convert_to(PixelFormat::RGBA);
+        let rgba = RgbaImage::from_raw(image.width(), image.height(), image.into_data().into_vec())
+            .wrap_err("Failed to create rgba-image")?;
+
+        Ok(rgba)
+    }
+
+    /// Extracts an icon group from the resources of a windows executable (or dll),
+    /// `index` is the position of the group like in `shell32.dll,3`.
+    fn extract_exe_icon(path: &Path, index: usize) -> eyre::Result<IconDir> {
+        let map = FileMap::open(path).wrap_err("Failed to map executable")?;
+        let pe = PeFile::from_bytes(map.as_ref()).wrap_err("Failed to parse executable")?;
+        let resources = pe.resources().wrap_err("Executable has no resources")?;
+        let (_, group) = resources
+            .icons()
+            .filter_map(|v| v.ok())
+            .nth(index)
+            .wrap_err_with(|| format!("Executable has no icon at index {index}"))?;
+
+        let mut ico = Vec::new();
+        group.write(&mut ico).wrap_err("Failed to write ico")?;
+        let icon_dir = IconDir::read(Cursor::new(ico)).wrap_err("Failed to read ico-dir")?;
+        Ok(icon_dir)
+    }
+
+    fn render_ico_icon(icon_dir: IconDir, size: u32) -> eyre::Result<RgbaImage> {
         let (mut closest_entry_i, mut closest_distance) = (0, i64::MAX);
         let entries = icon_dir.entries();
         for (i, entry) in entries.iter().enumerate() {
-            let distance = (32 - entry.width().max(entry.height()) as i64).abs();
+            let distance = (i64::from(size) - entry.width().max(entry.height()) as i64).abs();
             if distance < closest_distance {
                 closest_distance = distance;
                 closest_entry_i = i;
@@ -203,17 +249,34 @@ impl IconLoader {
         Ok(rgba)
     }
 
-    fn render_svg_icon(svg_data: &str) -> eyre::Result<Pixmap> {
+    /// Renders the svg so its longer side is `size`, instead of scaling up a small rendering.
+    fn render_svg_icon(svg_data: &str, size: u32) -> eyre::Result<Pixmap> {
         let opt = Options::default();
         let rtree = Tree::from_str(svg_data, &opt).wrap_err("Parsing svg")?;
-        let pixmap_size = rtree.size();
-        let mut pixmap = Pixmap::new(pixmap_size.width() as u32, pixmap_size.height() as u32)
+        let svg_size = rtree.size();
+        let scale = size as f32 / svg_size.width().max(svg_size.height());
+        let pixmap_size = svg_size.to_int_size().scale_by(scale);
+        let pixmap_size = pixmap_size.wrap_err("Svg has no size")?;
+        let mut pixmap = Pixmap::new(pixmap_size.width(), pixmap_size.height())
             .wrap_err_with(|| format!("Allocating pixmap {pixmap_size:?}"))?;
         resvg::render(
             &rtree,
-            tiny_skia::Transform::identity(),
+            tiny_skia::Transform::from_scale(scale, scale),
             &mut pixmap.as_mut(),
         );
         Ok(pixmap)
     }
 }
+
+/// Icon locations of executables may point at a specific resource using `path,index`.
+fn split_resource_index(icon: &Path) -> (PathBuf, usize) {
+    let string = icon.to_string_lossy();
+    if let Some((path, index)) = string.rsplit_once(',') {
+        if let Ok(index) = index.trim().parse::<i64>() {
+            // Negative indices are resource ids, which we don't resolve.
+            return (PathBuf::from(path), index.max(0) as usize);
+        }
+    }
+
+    (icon.to_path_buf(), 0)
+}
@@ -5,6 +5,7 @@ use crate::apps::icons::finder::IconFinder;
 use crate::apps::icons::loader::{IconLoader, LoadIconTaskRequest, LoadIconTaskResponse};
 use crate::apps::{App, AppId};
 use crate::config::Config;
+use crate::paths::create_private_dir;
 use crossbeam::channel::{Receiver, Sender};
 use eyre::{Context, ContextCompat};
 use ico::IconDir;
@@ -15,13 +16,17 @@ use resvg::tiny_skia::Pixmap;
 use resvg::usvg::{Options, Tree};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::fs::{create_dir_all, read_to_string, remove_file};
+use std::fs::{read_to_string, remove_file};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tracing::{debug, error, info, warn};
 
 const PREFERRED_ICON_SIZE: u16 = 32;
 const PREFERRED_ICON_SIZE_U32: u32 = PREFERRED_ICON_SIZE as u32;
 
+/// How often the ui checks for loaded icons while they are loading.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 /// The AppIconManager is responsible for finding and displaying application icons.
 pub struct AppIconManager {
     icon_image_dir: PathBuf,
@@ -31,6 +36,10 @@ pub struct AppIconManager {
     seen_icons: HashSet<AppId>,
 
     loader: Option<IconLoader>,
+    /// The export ids of the icons being loaded, which stay taken until they are done.
+    loading: HashSet<usize>,
+    /// The icons written since the last [AppIconManager::tick].
+    loaded: Vec<PathBuf>,
     extensions: HashMap<String, usize>,
     
     to_load: usize,
@@ -40,7 +49,7 @@ pub struct AppIconManager {
 impl AppIconManager {
     pub fn new(dir: &Path) -> eyre::Result<AppIconManager> {
         let cache_dir = dir.join("icons");
-        create_dir_all(&cache_dir).wrap_err("Failed to create icons dir")?;
+        create_private_dir(&cache_dir).wrap_err("Failed to create icons dir")?;
 
         let model_path = cache_dir.join("icons.json");
         let model = Config::<IconsModel>::read_file(&model_path).wrap_err("Failed to read icon")?;
@@ -51,6 +60,8 @@ impl AppIconManager {
             model,
             seen_icons: Default::default(),
             loader: None,
+            loading: Default::default(),
+            loaded: Default::default(),
             extensions: Default::default(),
             to_load: 0,
             to_load_finished: 0,
@@ -63,6 +74,28 @@ impl AppIconManager {
         path.canonicalize().ok()
     }
 
+    /// Finds icons in `theme` from now on, those found in another theme are cleared so they
+    /// load again.
+    pub fn set_theme(&mut self, theme: Option<String>) {
+        if self.model.theme != theme && !self.model.values.is_empty() {
+            info!("The icon theme changed to {theme:?}");
+            self.clear_icons();
+        }
+        self.model.theme = theme;
+    }
+
+    /// Renders icons for a display scale like 2, at twice the size. Returns whether the size
+    /// changed, the icons then need [AppIconManager::prepare_icon] again.
+    pub fn set_scale(&mut self, scale: f32) -> bool {
+        let size = (PREFERRED_ICON_SIZE as f32 * scale.clamp(1.0, 4.0)).round() as u32;
+        if size == self.model.size {
+            return false;
+        }
+        info!("Rendering icons at {size}px for the scale {scale}");
+        self.model.size = size;
+        true
+    }
+
     pub fn prepare_icon(&mut self, app: &App) {
         let Some(source) = app.icon.clone() else {
             return;
@@ -70,7 +103,7 @@ impl AppIconManager {
         self.seen_icons.insert(app.id.clone());
 
         if let Some(icon) = self.model.values.get(&app.id) {
-            if icon.source_location == source {
+            if icon.source_location == source && icon.size == self.model.size {
                 // Skip because they are the same
                 return;
             }
@@ -78,13 +111,17 @@ impl AppIconManager {
 
         self.to_load += 1;
         let icon_location = self.new_location(source.clone(), app.id.clone());
-        let loader = self.loader.get_or_insert_with(IconLoader::new);
+        let theme = &self.model.theme;
+        let loader = self
+            .loader
+            .get_or_insert_with(|| IconLoader::new(theme.clone()));
 
         loader.enqueue(LoadIconTaskRequest {
             id: app.id.clone(),
             app_name: app.name.clone(),
             icon: source,
             location: icon_location,
+            size: self.model.size,
         });
         //info!("Compiling icon {}", app.name);
         //         let source_path = PathBuf::from(&source);
@@ -145,14 +182,29 @@ impl AppIconManager {
     pub fn to_load_finished(&self) -> usize {
         self.to_load_finished
     }
-    pub fn tick(&mut self) -> bool {
-        if let Some(loader) = &mut self.loader {
-            let values = loader.tick();
-            self.handle_responses(values);
-            true
-        } else {
-            false
+    /// Takes the paths of the icons that finished loading. Export ids are reused, so the ui
+    /// has to forget what it cached for these paths. The loader is stopped once every icon is
+    /// loaded, so the ui doesn't need to poll it anymore.
+    pub fn tick(&mut self) -> Vec<PathBuf> {
+        let Some(loader) = &mut self.loader else {
+            return Vec::new();
+        };
+        let values = loader.tick();
+        self.handle_responses(values);
+
+        if self.to_load_finished >= self.to_load {
+            if let Some(loader) = self.loader.take() {
+                let responses = loader.finish();
+                self.handle_responses(responses);
+                debug!("Loaded all {} icons", self.to_load);
+            }
         }
+        std::mem::take(&mut self.loaded)
+    }
+
+    /// Whether icons are still loading, see [POLL_INTERVAL].
+    pub fn is_loading(&self) -> bool {
+        self.loader.is_some()
     }
     
     pub fn finish(&mut self) -> eyre::Result<()> {
@@ -244,7 +296,7 @@ impl AppIconManager {
             FilterType::Lanczos3,
         );
 
-        let id = self.model.find_free_id();
+        let id = self.model.find_free_id(&self.loading);
         let path = self.icon_path(id);
         image.save(&path).wrap_err("Failed to save rendered icon")?;
         Ok(id)
@@ -306,11 +358,17 @@ impl AppIconManager {
     }
 
     fn new_location(&mut self, source_location: String, app: AppId, ) -> IconLocation {
-        let id = self.model.find_free_id();
-        self.model.values.insert(app.clone(), IconEntryModel {
+        let id = self.model.find_free_id(&self.loading);
+        self.loading.insert(id);
+        let replaced = self.model.values.insert(app.clone(), IconEntryModel {
             source_location,
             export_id: Some(id),
+            size: self.model.size,
         });
+        // Like the icon at another size.
+        if let Some(export_id) = replaced.and_then(|v| v.export_id) {
+            let _ = remove_file(self.icon_path(export_id));
+        }
         let location = IconLocation {
             app_id: app,
             export_id: id,
@@ -321,32 +379,66 @@ impl AppIconManager {
     }
 
     fn free_location(&mut self, in_use: bool, mut id: IconLocation) {
-        let model = self.model.values.get_mut(&id.app_id).unwrap();
-        if !in_use {
-            model.export_id = None;
+        id.freed = true;
+        self.loading.remove(&id.export_id);
+        let model = self
+            .model
+            .values
+            .get_mut(&id.app_id)
+            .filter(|model| model.export_id == Some(id.export_id));
+        let Some(model) = model else {
+            // The icon was cleared or loaded again since, like at another size.
+            let _ = remove_file(&id.path);
+            return;
+        };
+        if in_use {
+            self.loaded.push(id.path.clone());
         } else {
-            assert_eq!(model.export_id, Some(id.export_id));
+            model.export_id = None;
         }
-        id.freed = true;
     }
 }
 
-#[derive(Serialize, Deserialize, Default)]
+#[derive(Serialize, Deserialize)]
 pub struct IconsModel {
     values: HashMap<AppId, IconEntryModel>,
+    /// The icon theme the icons were found in.
+    #[serde(default)]
+    theme: Option<String>,
+    /// The size icons are rendered at, see [AppIconManager::set_scale].
+    #[serde(default = "default_icon_size")]
+    size: u32,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct IconEntryModel {
     source_location: String,
     export_id: Option<usize>,
+    /// The size it was rendered at.
+    #[serde(default = "default_icon_size")]
+    size: u32,
+}
+
+impl Default for IconsModel {
+    fn default() -> Self {
+        IconsModel {
+            values: HashMap::new(),
+            theme: None,
+            size: default_icon_size(),
+        }
+    }
+}
+
+fn default_icon_size() -> u32 {
+    PREFERRED_ICON_SIZE_U32
 }
 
 impl IconsModel {
-    pub fn find_free_id(&self) -> usize {
+    /// The lowest export id no icon has, nor one of `taken`.
+    pub fn find_free_id(&self, taken: &HashSet<usize>) -> usize {
         let set: HashSet<usize> = self.values.values().flat_map(|v| v.export_id).collect();
         for id in 0..usize::MAX {
-            if !set.contains(&id) {
+            if !set.contains(&id) && !taken.contains(&id) {
                 return id;
             }
         }
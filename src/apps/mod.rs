@@ -1,46 +1,195 @@
+pub mod cache;
+pub mod exec;
 pub mod icons;
+pub mod running;
+pub mod watcher;
 
+use crate::paths::expand_home;
+use crate::platform;
+use crate::search::transliterate;
+use crate::settings::{AppOverride, Settings, SimpleEntry};
 use base64::Engine;
-use eyre::{Context, ContextCompat};
-use ini::{Ini, Properties};
+use eyre::ContextCompat;
+use glob::{MatchOptions, Pattern};
+use ini::Properties;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::{env, io};
+use tracing::{debug, warn};
 
 /// A sha256 hash of some of the application properties.
 #[derive(Clone, Eq, PartialEq, Hash, Ord, Default, PartialOrd, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct AppId(pub(crate) String);
 
+impl AppId {
+    /// Hashes the given properties into an id, the same properties always give the same id.
+    pub fn from_properties(properties: &[&str]) -> AppId {
+        let mut hasher = Sha256::new();
+        for property in properties {
+            hasher.update(property.as_bytes());
+        }
+        let result = hasher.finalize();
+        AppId(base64::engine::general_purpose::STANDARD.encode(result))
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct App {
     pub id: AppId,
     pub path: PathBuf,
     pub name: String,
     pub exec: String,
+    /// The `Path` key, the directory the app wants to be started in.
+    pub working_dir: Option<PathBuf>,
     pub icon: Option<String>,
     pub comment: Option<String>,
     pub generic_name: Option<String>,
     pub keywords: Option<String>,
     pub categories: Option<Vec<String>>,
     pub terminal: bool,
+    /// Whether the app can be started through `org.freedesktop.Application`.
+    pub dbus_activatable: bool,
+    /// The `WM_CLASS` of the app's windows, when it differs from the file id.
+    pub startup_wm_class: Option<String>,
+    /// Other names the app is found by, like its Chinese name and that name's pinyin.
+    #[serde(default)]
+    pub search_names: Vec<String>,
+    /// The Flatpak app id, like `org.mozilla.firefox`, of apps Flatpak exported.
+    #[serde(default)]
+    pub flatpak: Option<String>,
+    pub source: AppSource,
+}
+
+/// Where an app was found.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppSource {
+    /// A freedesktop `.desktop` file.
+    #[default]
+    DesktopEntry,
+    /// A `.desktop` file of `Type=Link`, its exec is the URL to open.
+    Link,
+    /// A shortcut in the Windows Start Menu.
+    StartMenu,
+    /// A packaged (UWP) app from `Get-StartApps`.
+    StartApps,
+    /// A macOS `.app` bundle.
+    AppBundle,
+    /// An AppImage, with the Exec line of its embedded desktop entry.
+    AppImage,
+    /// An `[[apps]]` entry of the settings.
+    Settings,
 }
 
 impl App {
+    /// Whether the Exec line takes `uri`, files need `%f`/`%F` and URLs `%u`/`%U`.
+    ///
+    /// Only desktop entries say what they take, the others get to try.
+    pub fn accepts(&self, uri: &str) -> bool {
+        match self.source {
+            AppSource::DesktopEntry | AppSource::AppImage | AppSource::Settings => {}
+            AppSource::Link => return false,
+            _ => return true,
+        }
+        let has_code = |codes: &[&str]| self.exec.split_whitespace().any(|v| codes.contains(&v));
+        let is_file = uri.starts_with('/') || uri.starts_with("file://");
+        has_code(&["%u", "%U"]) || (is_file && has_code(&["%f", "%F"]))
+    }
+
+    /// The Exec line without field codes and extra whitespace, for finding duplicates.
+    fn exec_target(&self) -> String {
+        let Ok(args) = exec::split(&self.exec) else {
+            return String::new();
+        };
+        args.into_iter()
+            .filter(|arg| !(arg.len() == 2 && arg.starts_with('%') && arg != "%%"))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// The Wine prefix of apps started through Wine or Proton, like the entries Wine creates
+    /// for the Windows programs it installs. `~/.wine` when the Exec line doesn't set one.
+    pub fn wine_prefix(&self) -> Option<String> {
+        let args = exec::split(&self.exec).ok()?;
+        // Wine's entries start it like `env WINEPREFIX="/home/me/.wine" wine ...`.
+        let mut args = args.iter().skip_while(|arg| *arg == "env");
+        let mut prefix = None;
+        let program = args.find(|arg| match arg.split_once('=') {
+            Some(("WINEPREFIX", value)) => {
+                prefix = Some(value.to_string());
+                false
+            }
+            Some(_) => false,
+            None => true,
+        })?;
+        let program = Path::new(program).file_name()?.to_str()?;
+        let is_wine = matches!(program, "wine" | "wine64" | "proton" | "umu-run")
+            || program.starts_with("wine-");
+        if !is_wine && prefix.is_none() {
+            return None;
+        }
+        Some(prefix.unwrap_or_else(|| "~/.wine".to_string()))
+    }
+
+    /// The file name without extension, like `org.gnome.Nautilus` for desktop entries.
+    ///
+    /// The settings refer to apps by this or by their name.
+    pub fn file_id(&self) -> Option<&str> {
+        self.path.file_stem().and_then(|v| v.to_str())
+    }
+
+    /// An app from an `[[apps]]` entry of the settings, it has no file.
+    pub fn from_settings(entry: &SimpleEntry) -> App {
+        let keywords: String = entry.keywords.iter().map(|v| format!("{v};")).collect();
+        App {
+            id: AppId::from_properties(&[&entry.name, &entry.exec]),
+            name: entry.name.clone(),
+            exec: entry.exec.clone(),
+            icon: entry.icon.clone(),
+            comment: entry.comment.clone(),
+            keywords: Some(keywords).filter(|v| !v.is_empty()),
+            categories: Some(entry.categories.clone()).filter(|v| !v.is_empty()),
+            terminal: entry.terminal,
+            source: AppSource::Settings,
+            ..App::default()
+        }
+    }
+
     pub fn parse(path: PathBuf, properties: &Properties) -> eyre::Result<Option<App>> {
         let no_display = properties.get("NoDisplay").unwrap_or("false") == "true";
-        if no_display {
+        // Hidden entries count as deleted, they only hide those of the same id.
+        let hidden = properties.get("Hidden").unwrap_or("false") == "true";
+        if no_display || hidden {
             return Ok(None);
         }
 
         let terminal = properties.get("Terminal").unwrap_or("false") == "true";
+        let dbus_activatable = properties.get("DBusActivatable").unwrap_or("false") == "true";
         let name = properties.get("Name").wrap_err("No Name key")?;
         let comment = properties.get("Comment");
         let icon = properties.get("Icon");
         let generic_name = properties.get("GenericName");
         let keywords = properties.get("Keywords");
-        let Some(exec) = properties.get("Exec") else {
+        let startup_wm_class = properties.get("StartupWMClass");
+        let flatpak = properties.get("X-Flatpak");
+        let working_dir = properties.get("Path").filter(|v| !v.is_empty());
+        // Names in other scripts, so 火狐 finds Firefox in any locale.
+        let search_names = properties
+            .iter()
+            .filter(|(key, value)| {
+                key.starts_with("Name[") && transliterate::is_other_script(value)
+            })
+            .map(|(_, value)| value.to_string())
+            .collect();
+        let (exec, source) = match properties.get("Type").unwrap_or("Application") {
+            "Application" => (properties.get("Exec"), AppSource::DesktopEntry),
+            "Link" => (properties.get("URL"), AppSource::Link),
+            // Directories describe menu folders, like unknown types there is nothing to launch.
+            _ => return Ok(None),
+        };
+        let Some(exec) = exec else {
             return Ok(None);
         };
         let categories = properties
@@ -48,26 +197,30 @@ impl App {
             string.split(";").map(|v| v.to_string()).collect::<Vec<String>>()
         });
 
-        // Generate id
-        let mut hasher = Sha256::new();
-        hasher.update(name.as_bytes());
-        hasher.update(comment.unwrap_or("").as_bytes());
-        hasher.update(icon.unwrap_or("").as_bytes());
-        hasher.update(generic_name.unwrap_or("").as_bytes());
-        hasher.update(keywords.unwrap_or("").as_bytes());
-        let result = hasher.finalize();
-        let id = AppId(base64::engine::general_purpose::STANDARD.encode(result));
+        let id = AppId::from_properties(&[
+            name,
+            comment.unwrap_or(""),
+            icon.unwrap_or(""),
+            generic_name.unwrap_or(""),
+            keywords.unwrap_or(""),
+        ]);
         Ok(Some(App {
             id,
             path,
             name: name.to_string(),
             exec: exec.to_string(),
+            working_dir: working_dir.map(PathBuf::from),
             icon: icon.map(|v| v.to_string()),
             comment: comment.map(|v| v.to_string()),
             generic_name: generic_name.map(|v| v.to_string()),
             keywords: keywords.map(|v| v.to_string()),
             categories,
             terminal,
+            dbus_activatable,
+            startup_wm_class: startup_wm_class.map(|v| v.to_string()),
+            search_names,
+            flatpak: flatpak.map(|v| v.to_string()),
+            source,
         }))
     }
 }
@@ -75,89 +228,228 @@ impl App {
 /// App manager is responsible for finding applications on your system.
 pub struct AppManager {
     pub applications: HashMap<AppId, App>,
+    /// The ids of duplicates that were merged, with the id of the app they were merged into.
+    pub merged: HashMap<AppId, AppId>,
+}
+
+/// What [AppManager::refresh] changed. Apps whose entry changed get a new id,
+/// so they show up in both.
+pub struct AppChanges {
+    pub added: Vec<AppId>,
+    pub removed: Vec<AppId>,
 }
 
 impl AppManager {
-    pub fn new() -> eyre::Result<Self> {
+    pub fn new(settings: &Settings) -> eyre::Result<Self> {
+        let (applications, merged) = Self::scan(settings)?;
+        Ok(Self {
+            applications,
+            merged,
+        })
+    }
+
+    /// Scans the application directories again and takes over what changed.
+    pub fn refresh(&mut self, settings: &Settings) -> eyre::Result<AppChanges> {
+        let (applications, merged) = Self::scan(settings)?;
+        let added = applications
+            .keys()
+            .filter(|id| !self.applications.contains_key(*id))
+            .cloned()
+            .collect();
+        let removed = self
+            .applications
+            .keys()
+            .filter(|id| !applications.contains_key(*id))
+            .cloned()
+            .collect();
+        self.applications = applications;
+        self.merged = merged;
+        Ok(AppChanges { added, removed })
+    }
+
+    /// The dirs [AppManager::scan] finds apps in, for [watcher::AppWatcher].
+    pub fn watch_dirs(settings: &Settings) -> Vec<PathBuf> {
+        let extra_dirs: Vec<PathBuf> = settings.app_dirs.iter().map(|v| expand_home(v)).collect();
+        let appimage_dirs = if cfg!(all(unix, not(target_os = "macos"))) {
+            settings.appimages.dirs.as_slice()
+        } else {
+            &[]
+        };
+        platform::watch_dirs(&extra_dirs)
+            .into_iter()
+            .chain(appimage_dirs.iter().map(|v| expand_home(v)))
+            .collect()
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn scan(settings: &Settings) -> eyre::Result<(HashMap<AppId, App>, HashMap<AppId, AppId>)> {
         let mut applications = HashMap::new();
-        
-        for app_dir in find_application_dirs()? {
-            let files = match get_dir_desktop_files(&app_dir) {
-                Ok(v) => v,
-                Err(e) => {
-                    println!("Could not list {}: {}", &app_dir.to_string_lossy(), e);
-                    continue;
+        let extra_dirs: Vec<PathBuf> = settings.app_dirs.iter().map(|v| expand_home(v)).collect();
+        let mut found = platform::find_applications(&extra_dirs)?;
+        #[cfg(all(unix, not(target_os = "macos")))]
+        {
+            let dirs = &settings.appimages.dirs;
+            let dirs: Vec<PathBuf> = dirs.iter().map(|v| expand_home(v)).collect();
+            found.extend(platform::appimage::find_appimages(&dirs));
+            found.extend(settings.apps.iter().map(App::from_settings));
+        }
+        let ignore = IgnoreList::new(&settings.ignore);
+        found.retain(|app| !ignore.matches(app));
+        found.retain(|app| {
+            let hidden = override_of(settings, app).is_some_and(|v| v.hidden);
+            if hidden {
+                debug!("Hiding {:?}, its override says so", app.path);
+            }
+            !hidden
+        });
+        let mut merged = if settings.merge_duplicates {
+            merge_duplicates(&mut found)
+        } else {
+            HashMap::new()
+        };
+        if settings.wine.collapse_prefixes {
+            let collapsed = collapse_wine_prefixes(&mut found);
+            for target in merged.values_mut() {
+                if let Some(kept) = collapsed.get(target) {
+                    *target = kept.clone();
                 }
-            };
-            for app_file in files {
-                let path = app_file.path();
-                let info = Ini::load_from_file_opt(
-                    &path,
-                    ini::ParseOption {
-                        enabled_quote: false,
-                        enabled_escape: false,
-                    },
-                )
-                .wrap_err("failed to parse ini")?;
-                let properties = info
-                    .section(Some("Desktop Entry"))
-                    .wrap_err("No [Desktop Entry] section")?;
-
-                if let Some(app) = App::parse(path, properties)? {
-                    applications.insert(app.id.clone(), app);
+            }
+            merged.extend(collapsed);
+        }
+        let transliterations = transliterate::for_locales(&settings.search.transliterate);
+        for mut app in found {
+            if let Some(changes) = override_of(settings, &app) {
+                // The id stays the same, so renaming keeps the usage data.
+                if let Some(name) = &changes.name {
+                    app.name = name.clone();
+                }
+                if let Some(icon) = &changes.icon {
+                    app.icon = Some(expand_home(Path::new(icon)).to_string_lossy().to_string());
+                }
+            }
+            let mut spelled: Vec<String> = Vec::new();
+            for name in std::iter::once(&app.name).chain(&app.search_names) {
+                for transliteration in &transliterations {
+                    for name in transliteration.search_names(name) {
+                        if !spelled.contains(&name) {
+                            spelled.push(name);
+                        }
+                    }
                 }
             }
+            app.search_names.extend(spelled);
+            applications.insert(app.id.clone(), app);
         }
 
-        Ok(Self { applications })
+        Ok((applications, merged))
     }
 }
 
-fn find_application_dirs() -> io::Result<Vec<PathBuf>> {
-    let data_home = match env::var_os("XDG_DATA_HOME") {
-        Some(val) => PathBuf::from(val),
-        None => {
-            let home = dirs::home_dir().ok_or(io::Error::new(
-                io::ErrorKind::Other,
-                "Couldn't get home dir",
-            ))?;
-            home.join(".local/share")
-        }
-    };
-    let extra_data_dirs = match env::var_os("XDG_DATA_DIRS") {
-        Some(val) => env::split_paths(&val).map(PathBuf::from).collect(),
-        None => {
-            vec![
-                PathBuf::from("/usr/local/share"),
-                PathBuf::from("/usr/share"),
-            ]
+/// The `[overrides]` entry of `app`, by its file id or name.
+fn override_of<'a>(settings: &'a Settings, app: &App) -> Option<&'a AppOverride> {
+    let overrides = &settings.overrides;
+    app.file_id()
+        .and_then(|id| overrides.get(id))
+        .or_else(|| overrides.get(&app.name))
+}
+
+/// The `ignore` globs of the settings, matched against the path, the file id and the name
+/// of apps.
+struct IgnoreList {
+    patterns: Vec<Pattern>,
+}
+
+impl IgnoreList {
+    fn new(globs: &[String]) -> IgnoreList {
+        let patterns = globs
+            .iter()
+            .filter_map(|glob| {
+                let glob = expand_home(Path::new(glob));
+                Pattern::new(&glob.to_string_lossy())
+                    .inspect_err(|error| warn!("Invalid ignore pattern {glob:?}: {error}"))
+                    .ok()
+            })
+            .collect();
+        IgnoreList { patterns }
+    }
+
+    fn matches(&self, app: &App) -> bool {
+        // Names are matched like they're searched, ignoring case.
+        let name_options = MatchOptions {
+            case_sensitive: false,
+            ..MatchOptions::new()
+        };
+        let matched = self.patterns.iter().find(|pattern| {
+            pattern.matches_path(&app.path)
+                || app.file_id().is_some_and(|v| pattern.matches(v))
+                || pattern.matches_with(&app.name, name_options)
+        });
+        if let Some(pattern) = matched {
+            debug!("Ignoring {:?}, it matches {}", app.path, pattern.as_str());
         }
-    };
+        matched.is_some()
+    }
+}
+
+/// Removes apps with the same Exec line as an earlier one, which wins like its dir does.
+/// It takes over the icon of a duplicate when it has none.
+///
+/// A Flatpak runs something else than its native copy, so those are matched by name.
+fn merge_duplicates(apps: &mut Vec<App>) -> HashMap<AppId, AppId> {
+    let mut merged = HashMap::new();
+    let mut kept: HashMap<String, usize> = HashMap::new();
+    // The lowercase names of the Flatpaks and of the native apps kept so far.
+    let mut kept_names: [HashMap<String, usize>; 2] = Default::default();
+    let mut i = 0;
+    while i < apps.len() {
+        let target = apps[i].exec_target();
+        let name = apps[i].name.to_lowercase();
+        let is_flatpak = apps[i].flatpak.is_some();
+        let first = kept
+            .get(&target)
+            .filter(|_| !target.is_empty())
+            .or_else(|| kept_names[usize::from(!is_flatpak)].get(&name));
+        let Some(&first) = first else {
+            kept.insert(target, i);
+            kept_names[usize::from(is_flatpak)].entry(name).or_insert(i);
+            i += 1;
+            continue;
+        };
 
-    let mut res = Vec::new();
-    res.push(data_home.join("applications"));
-    for dir in extra_data_dirs {
-        res.push(dir.join("applications"));
+        let duplicate = apps.remove(i);
+        debug!("Merging {:?} into {:?}", duplicate.path, apps[first].path);
+        if apps[first].icon.is_none() {
+            apps[first].icon = duplicate.icon;
+        }
+        if duplicate.id != apps[first].id {
+            merged.insert(duplicate.id, apps[first].id.clone());
+        }
     }
-    Ok(res)
+    merged
 }
 
-fn get_dir_desktop_files(path: &Path) -> io::Result<Vec<std::fs::DirEntry>> {
-    match path.read_dir() {
-        Ok(readdir) => Ok(readdir
-            .filter_map(|v| v.ok())
-            .filter(|e| match e.file_type() {
-                Ok(ft) => ft.is_file() | ft.is_symlink(),
-                _ => false,
-            })
-            .filter(|e| e.file_name().to_string_lossy().ends_with(".desktop"))
-            .collect::<Vec<_>>()),
-        Err(e) => {
-            if e.kind() == io::ErrorKind::NotFound {
-                Ok(Vec::new())
-            } else {
-                Err(e)
+/// Removes Wine apps with the name of one kept before, Wine creates an entry in each prefix
+/// a program is installed in.
+fn collapse_wine_prefixes(apps: &mut Vec<App>) -> HashMap<AppId, AppId> {
+    let mut merged = HashMap::new();
+    let mut kept: HashMap<String, AppId> = HashMap::new();
+    apps.retain(|app| {
+        let Some(prefix) = app.wine_prefix() else {
+            return true;
+        };
+        match kept.get(&app.name.to_lowercase()) {
+            Some(first) => {
+                debug!("Collapsing {:?} of the Wine prefix {prefix}", app.path);
+                if app.id != *first {
+                    merged.insert(app.id.clone(), first.clone());
+                }
+                false
+            }
+            None => {
+                kept.insert(app.name.to_lowercase(), app.id.clone());
+                true
             }
         }
-    }
+    });
+    merged
 }
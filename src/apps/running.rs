@@ -0,0 +1,58 @@
+use crate::apps::{App, AppSource};
+use crate::platform;
+use crossbeam::channel::{bounded, Receiver};
+use std::collections::HashSet;
+use std::thread::spawn;
+use std::time::{Duration, Instant};
+
+/// How often the window list is read again while the launcher is open.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Tracks which apps have open windows, like the dots of a dock.
+///
+/// The window list is read on a background thread, matching desktop entries by their
+/// `StartupWMClass` or else their file id.
+#[derive(Default)]
+pub struct RunningApps {
+    classes: HashSet<String>,
+    refreshed_at: Option<Instant>,
+    pending: Option<Receiver<HashSet<String>>>,
+}
+
+impl RunningApps {
+    /// Starts a refresh when the last one is older than [REFRESH_INTERVAL],
+    /// returns `true` once its result arrived.
+    pub fn tick(&mut self) -> bool {
+        if let Some(pending) = &self.pending {
+            let Ok(classes) = pending.try_recv() else {
+                return false;
+            };
+            self.pending = None;
+            self.refreshed_at = Some(Instant::now());
+            let changed = self.classes != classes;
+            self.classes = classes;
+            return changed;
+        }
+
+        if self
+            .refreshed_at
+            .is_some_and(|v| v.elapsed() < REFRESH_INTERVAL)
+        {
+            return false;
+        }
+        let (sender, receiver) = bounded(1);
+        spawn(move || {
+            let _ = sender.send(platform::window_classes());
+        });
+        self.pending = Some(receiver);
+        false
+    }
+
+    pub fn is_running(&self, app: &App) -> bool {
+        if app.source != AppSource::DesktopEntry || self.classes.is_empty() {
+            return false;
+        }
+        let class = app.startup_wm_class.as_deref().or_else(|| app.file_id());
+        class.is_some_and(|v| self.classes.contains(&v.to_lowercase()))
+    }
+}
@@ -0,0 +1,104 @@
+//! Watches the application dirs, so apps installed or removed while ignition is open show
+//! up without pressing F5.
+
+use crate::platform;
+use crossbeam::channel::{unbounded, Receiver, RecvTimeoutError};
+use eframe::egui;
+use eyre::Context;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Installing a package changes many files, the apps are scanned once it's quiet for this long.
+const QUIET_TIME: Duration = Duration::from_millis(500);
+
+pub struct AppWatcher {
+    /// Stops watching when dropped.
+    _watcher: RecommendedWatcher,
+    changed: Receiver<()>,
+    context: Arc<Mutex<Option<egui::Context>>>,
+}
+
+impl AppWatcher {
+    /// Watches `dirs` and their subdirectories, those that don't exist are left out.
+    pub fn new(dirs: &[PathBuf]) -> eyre::Result<AppWatcher> {
+        let (event_sender, events) = unbounded();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+                Ok(event) if is_app_change(&event) => {
+                    let _ = event_sender.send(());
+                }
+                Ok(_) => {}
+                Err(error) => warn!("Watching the application dirs failed: {error}"),
+            })
+            .wrap_err("Failed to create watcher")?;
+        for dir in dirs.iter().filter(|dir| dir.is_dir()) {
+            if let Err(error) = watcher.watch(dir, RecursiveMode::Recursive) {
+                warn!("Could not watch {dir:?}: {error}");
+            }
+        }
+
+        let (sender, changed) = unbounded();
+        let context: Arc<Mutex<Option<egui::Context>>> = Arc::new(Mutex::new(None));
+        let context_c = context.clone();
+        std::thread::spawn(move || {
+            while events.recv().is_ok() {
+                loop {
+                    match events.recv_timeout(QUIET_TIME) {
+                        Ok(()) => {}
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                debug!("The application dirs changed");
+                if sender.send(()).is_err() {
+                    return;
+                }
+                if let Some(context) = &*context_c.lock().unwrap() {
+                    context.request_repaint();
+                }
+            }
+        });
+
+        Ok(AppWatcher {
+            _watcher: watcher,
+            changed,
+            context,
+        })
+    }
+
+    /// Lets the watcher wake up the ui when the apps changed.
+    pub fn set_context(&self, context: egui::Context) {
+        *self.context.lock().unwrap() = Some(context);
+    }
+
+    /// Whether the apps changed since the last call.
+    pub fn changed(&self) -> bool {
+        let mut changed = false;
+        while self.changed.try_recv().is_ok() {
+            changed = true;
+        }
+        changed
+    }
+}
+
+/// Whether `event` created, changed or removed an app, reading one doesn't.
+fn is_app_change(event: &notify::Event) -> bool {
+    !matches!(event.kind, EventKind::Access(_)) && event.paths.iter().any(|v| is_app_path(v))
+}
+
+/// Whether `path` is or is in one of the [platform::APP_EXTENSIONS] files, like the
+/// `Contents` of a macOS bundle.
+fn is_app_path(path: &Path) -> bool {
+    path.components().any(|component| {
+        Path::new(component.as_os_str())
+            .extension()
+            .is_some_and(|extension| {
+                platform::APP_EXTENSIONS
+                    .iter()
+                    .any(|v| extension.eq_ignore_ascii_case(v))
+            })
+    })
+}
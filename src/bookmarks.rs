@@ -0,0 +1,148 @@
+//! Directories from the GTK bookmarks and the settings, offered to open in the file manager,
+//! a terminal or an editor.
+
+use crate::apps::AppId;
+use crate::paths::{expand_home, file_uri_path};
+use crate::platform::LaunchRequest;
+use crate::search::{starts_words, EntryAction, SearchResultEntry, SearchScore};
+use crate::settings::Settings;
+use std::env;
+use std::fs::read_to_string;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// How many bookmarks a query shows, each comes with an entry per [DirTarget].
+const MAX_MATCHES: usize = 3;
+
+#[derive(Clone, Debug)]
+pub struct Bookmark {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DirTarget {
+    FileManager,
+    Terminal,
+    Editor,
+}
+
+#[derive(Default)]
+pub struct Bookmarks {
+    bookmarks: Vec<Bookmark>,
+}
+
+impl Bookmarks {
+    pub fn load(settings: &Settings) -> Bookmarks {
+        let mut bookmarks = Vec::new();
+        if settings.bookmarks.gtk {
+            bookmarks.extend(gtk_bookmarks());
+        }
+        for dir in &settings.bookmarks.dirs {
+            let path = expand_home(dir);
+            let name = path.file_name().map_or_else(
+                || path.to_string_lossy().to_string(),
+                |v| v.to_string_lossy().to_string(),
+            );
+            bookmarks.push(Bookmark { name, path });
+        }
+        // Bookmarks of network shares and removed drives can't be opened.
+        bookmarks.retain(|bookmark| bookmark.path.is_dir());
+        Bookmarks { bookmarks }
+    }
+
+    /// The bookmarks found by their name or path, as an entry for each way to open them.
+    pub fn search(&self, query: &str, settings: &Settings) -> Vec<SearchResultEntry> {
+        let mut targets = vec![DirTarget::FileManager];
+        if settings.terminal.command().is_some() {
+            targets.push(DirTarget::Terminal);
+        }
+        if launch_request(Path::new("."), DirTarget::Editor, settings).is_some() {
+            targets.push(DirTarget::Editor);
+        }
+
+        let mut entries = Vec::new();
+        let found = self.bookmarks.iter().filter(|bookmark| {
+            let path = bookmark.path.to_string_lossy().replace(['/', '\\'], " ");
+            starts_words(&format!("{} {path}", bookmark.name), query)
+        });
+        for bookmark in found.take(MAX_MATCHES) {
+            for target in &targets {
+                entries.push(SearchResultEntry {
+                    id: AppId::from_properties(&[
+                        "bookmark",
+                        &bookmark.path.to_string_lossy(),
+                        &format!("{target:?}"),
+                    ]),
+                    score: SearchScore::default(),
+                    detail: Some(bookmark.path.display().to_string()),
+                    broken: false,
+                    action: Some(EntryAction::OpenDir(bookmark.clone(), *target)),
+                    confirm: false,
+                });
+            }
+        }
+        entries
+    }
+}
+
+/// How to open `dir` in `target`, `None` when there is no editor.
+pub fn launch_request(dir: &Path, target: DirTarget, settings: &Settings) -> Option<LaunchRequest> {
+    let dir = dir.to_path_buf();
+    match target {
+        DirTarget::FileManager => Some(LaunchRequest::Uri(dir.to_string_lossy().to_string())),
+        DirTarget::Terminal => Some(LaunchRequest::Terminal {
+            command: settings.terminal.command(),
+            dir,
+            run: Vec::new(),
+        }),
+        DirTarget::Editor => {
+            if let Some(editor) = &settings.bookmarks.editor {
+                let mut command: Vec<String> =
+                    editor.split_whitespace().map(String::from).collect();
+                command.push(dir.to_string_lossy().to_string());
+                return Some(LaunchRequest::Command { command, dir });
+            }
+            // Those are usually terminal editors.
+            let editor = env::var("VISUAL").or_else(|_| env::var("EDITOR")).ok()?;
+            let mut run: Vec<String> = editor.split_whitespace().map(String::from).collect();
+            run.push(".".to_string());
+            Some(LaunchRequest::Terminal {
+                command: Some(settings.terminal.command()?),
+                dir,
+                run,
+            })
+        }
+    }
+}
+
+/// The bookmarks of the GTK file chooser, lines of a `file://` URI and an optional name.
+fn gtk_bookmarks() -> Vec<Bookmark> {
+    let Some(config_dir) = dirs::config_dir() else {
+        return Vec::new();
+    };
+    let path = config_dir.join("gtk-3.0").join("bookmarks");
+    let text = match read_to_string(&path) {
+        Ok(text) => text,
+        Err(error) => {
+            if error.kind() != ErrorKind::NotFound {
+                warn!("Could not read the GTK bookmarks {path:?}: {error}");
+            }
+            return Vec::new();
+        }
+    };
+
+    text.lines()
+        .filter_map(|line| {
+            let (uri, name) = line.split_once(' ').unwrap_or((line, ""));
+            // Only local directories, not `sftp://` and the like.
+            let path = file_uri_path(uri.trim())?;
+            let name = match name.trim() {
+                "" => path.file_name()?.to_string_lossy().to_string(),
+                name => name.to_string(),
+            };
+            Some(Bookmark { name, path })
+        })
+        .collect()
+}
@@ -0,0 +1,229 @@
+//! The headless subcommands, ignition opens the launcher when none is given.
+
+use crate::apps::icons::AppIconManager;
+use crate::apps::{cache, AppId, AppManager, AppSource};
+use crate::ipc::{self, IpcCommand};
+use crate::paths::Paths;
+use crate::search::SearchEngine;
+use crate::settings::Settings;
+use clap::{Parser, Subcommand};
+use eyre::Context;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Opens the launcher in the terminal instead of a window, like over SSH.
+    #[arg(long)]
+    pub tui: bool,
+    /// Shows how the score of each result came together, Ctrl+D toggles it too.
+    #[arg(long)]
+    pub explain: bool,
+    /// Uses a separate config, usage history and pins, like `work` and `personal`.
+    #[arg(long, global = true, value_parser = crate::paths::parse_profile)]
+    pub profile: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Prints what you launched the most in the last 30 days.
+    Stats {
+        /// Print JSON instead of a table.
+        #[arg(long)]
+        json: bool,
+        /// How many apps to list.
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+        /// How many days of launches per day to list.
+        #[arg(long, default_value_t = 14)]
+        days: u32,
+    },
+    /// Prints every indexed app as JSON, for status bars and other tools.
+    Index {
+        /// Indent the JSON.
+        #[arg(long)]
+        pretty: bool,
+    },
+    /// Scans the applications and their icons again, an open launcher picks up the changes too.
+    Reindex,
+}
+
+impl Command {
+    pub fn run(self, paths: &Paths, settings: &Settings) -> eyre::Result<()> {
+        match self {
+            Command::Stats { json, top, days } => stats(paths, settings, json, top, days),
+            Command::Index { pretty } => index(paths, settings, pretty),
+            Command::Reindex => reindex(paths, settings),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct StatsReport {
+    launches: usize,
+    dismissals: usize,
+    conversion_rate: Option<f32>,
+    average_search_to_launch_ms: Option<u64>,
+    top: Vec<AppReport>,
+    per_day: Vec<DayReport>,
+}
+
+#[derive(Serialize)]
+struct AppReport {
+    name: String,
+    launches: u32,
+}
+
+#[derive(Serialize)]
+struct DayReport {
+    date: String,
+    launches: u32,
+}
+
+#[derive(Serialize)]
+struct IndexEntry<'a> {
+    id: &'a AppId,
+    name: &'a str,
+    exec: &'a str,
+    /// The icon name or path from the entry.
+    icon: Option<&'a str>,
+    /// The rendered icon in the cache, once ignition indexed the icons.
+    icon_path: Option<PathBuf>,
+    path: &'a Path,
+    categories: &'a [String],
+    source: AppSource,
+    /// Launches in the last 30 days.
+    uses: u32,
+}
+
+fn index(paths: &Paths, settings: &Settings, pretty: bool) -> eyre::Result<()> {
+    let apps = AppManager::new(settings).wrap_err("Failed to find applications")?;
+    let icons = AppIconManager::new(&paths.cache_dir).wrap_err("Failed to read icon cache")?;
+    let mut search = SearchEngine::new(
+        &paths.data_dir,
+        settings.sync.dir().as_deref(),
+        settings.search.frecency_half_life_days,
+    )
+        .wrap_err("Failed to initialize SearchEngine")?;
+    search.apply_merged(&apps);
+
+    let mut entries: Vec<IndexEntry> = apps
+        .applications
+        .values()
+        .map(|app| IndexEntry {
+            id: &app.id,
+            name: &app.name,
+            exec: &app.exec,
+            icon: app.icon.as_deref(),
+            icon_path: icons.read_icon(&app.id),
+            path: &app.path,
+            categories: app.categories.as_deref().unwrap_or_default(),
+            source: app.source,
+            uses: search.use_count(&app.id),
+        })
+        .collect();
+    entries.sort_by(|a, b| a.name.cmp(b.name));
+
+    let json = if pretty {
+        serde_json::to_string_pretty(&entries)?
+    } else {
+        serde_json::to_string(&entries)?
+    };
+    println!("{json}");
+    Ok(())
+}
+
+fn reindex(paths: &Paths, settings: &Settings) -> eyre::Result<()> {
+    cache::clear();
+    let apps = AppManager::new(settings).wrap_err("Failed to find applications")?;
+    let mut icons = AppIconManager::new(&paths.cache_dir).wrap_err("Failed to read icon cache")?;
+    icons.set_theme(settings.icons.theme());
+    for app in apps.applications.values() {
+        icons.prepare_icon(app);
+    }
+    icons.finish().wrap_err("Failed to load icons")?;
+    println!("Found {} applications", apps.applications.len());
+
+    if let Some(runtime_dir) = &paths.runtime_dir {
+        if ipc::send(runtime_dir, IpcCommand::Reindex).is_ok() {
+            println!("Updated the open launcher");
+        }
+    }
+    Ok(())
+}
+
+fn stats(
+    paths: &Paths,
+    settings: &Settings,
+    json: bool,
+    top: usize,
+    days: u32,
+) -> eyre::Result<()> {
+    let apps = AppManager::new(settings).wrap_err("Failed to find applications")?;
+    let mut search = SearchEngine::new(
+        &paths.data_dir,
+        settings.sync.dir().as_deref(),
+        settings.search.frecency_half_life_days,
+    )
+        .wrap_err("Failed to initialize SearchEngine")?;
+    let stats = search.stats(days)?;
+
+    let report = StatsReport {
+        launches: stats.launches,
+        dismissals: stats.dismissals,
+        conversion_rate: stats.conversion_rate(),
+        average_search_to_launch_ms: stats.average_search_to_launch.map(|v| v.as_millis() as u64),
+        top: stats
+            .most_used
+            .iter()
+            .take(top)
+            .map(|(id, launches)| AppReport {
+                // Apps that got uninstalled since are still counted.
+                name: apps
+                    .applications
+                    .get(id)
+                    .map(|app| app.name.clone())
+                    .unwrap_or_else(|| "(uninstalled)".to_string()),
+                launches: *launches,
+            })
+            .collect(),
+        per_day: stats
+            .per_day
+            .iter()
+            .map(|(date, launches)| DayReport {
+                date: date.to_string(),
+                launches: *launches,
+            })
+            .collect(),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!("Launches:        {}", report.launches);
+    println!("Closed unused:   {}", report.dismissals);
+    if let Some(rate) = report.conversion_rate {
+        println!("Conversion:      {:.0}%", rate * 100.0);
+    }
+    if let Some(ms) = report.average_search_to_launch_ms {
+        println!("Time to launch:  {:.1}s", ms as f32 / 1000.0);
+    }
+
+    println!();
+    let width = report.top.iter().map(|v| v.name.len()).max().unwrap_or(0);
+    for (i, app) in report.top.iter().enumerate() {
+        println!("{:>3}. {:<width$}  {:>5}", i + 1, app.name, app.launches);
+    }
+
+    println!();
+    for day in &report.per_day {
+        println!("{}  {:>5}", day.date, day.launches);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+//! Colors typed like `#fab387`, `rgb(250, 179, 135)` or `hsl(23, 92%, 75%)`, converted to
+//! each of those to copy.
+
+use crate::apps::AppId;
+use crate::search::{EntryAction, SearchResultEntry, SearchScore};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorFormat {
+    Hex,
+    Rgb,
+    Hsl,
+}
+
+impl ColorFormat {
+    pub const ALL: [ColorFormat; 3] = [ColorFormat::Hex, ColorFormat::Rgb, ColorFormat::Hsl];
+}
+
+/// An entry for each [ColorFormat] when the query is a color.
+pub fn search(query: &str) -> Vec<SearchResultEntry> {
+    let Some(color) = parse(query) else {
+        return Vec::new();
+    };
+    ColorFormat::ALL
+        .into_iter()
+        .map(|format| {
+            let text = format_color(color, format);
+            SearchResultEntry {
+                id: AppId::from_properties(&["color", &text]),
+                score: SearchScore::default(),
+                detail: None,
+                broken: false,
+                action: Some(EntryAction::Color { color, text }),
+                confirm: false,
+            }
+        })
+        .collect()
+}
+
+/// Reads `#rgb`, `#rrggbb`, `rgb(r, g, b)` and `hsl(h, s%, l%)`, also with spaces between
+/// the numbers instead of commas.
+pub fn parse(text: &str) -> Option<[u8; 3]> {
+    let text = text.trim().to_lowercase();
+    if let Some(hex) = text.strip_prefix('#') {
+        return parse_hex(hex);
+    }
+    let (name, args) = text.strip_suffix(')')?.split_once('(')?;
+    let args: Vec<&str> = args
+        .split([',', ' ', '/'])
+        .filter(|v| !v.is_empty())
+        .collect();
+    match (name.trim(), args.as_slice()) {
+        ("rgb" | "rgba", [r, g, b, ..]) => {
+            Some([r.parse().ok()?, g.parse().ok()?, b.parse().ok()?])
+        }
+        ("hsl" | "hsla", [h, s, l, ..]) => {
+            let hue: f32 = h.trim_end_matches("deg").parse().ok()?;
+            let saturation: f32 = s.trim_end_matches('%').parse().ok()?;
+            let lightness: f32 = l.trim_end_matches('%').parse().ok()?;
+            if !(0.0..=100.0).contains(&saturation) || !(0.0..=100.0).contains(&lightness) {
+                return None;
+            }
+            Some(hsl_to_rgb(hue, saturation / 100.0, lightness / 100.0))
+        }
+        _ => None,
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<[u8; 3]> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok();
+    match hex.len() {
+        // Each digit stands for itself twice, `#fa3` is `#ffaa33`.
+        3 => Some([digit(0)? * 17, digit(1)? * 17, digit(2)? * 17]),
+        6 => {
+            let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+            Some([byte(0)?, byte(2)?, byte(4)?])
+        }
+        _ => None,
+    }
+}
+
+pub fn format_color([r, g, b]: [u8; 3], format: ColorFormat) -> String {
+    match format {
+        ColorFormat::Hex => format!("#{r:02x}{g:02x}{b:02x}"),
+        ColorFormat::Rgb => format!("rgb({r}, {g}, {b})"),
+        ColorFormat::Hsl => {
+            let (h, s, l) = rgb_to_hsl([r, g, b]);
+            format!("hsl({:.0}, {:.0}%, {:.0}%)", h, s * 100.0, l * 100.0)
+        }
+    }
+}
+
+/// Hue in degrees, saturation and lightness from 0 to 1.
+fn rgb_to_hsl(rgb: [u8; 3]) -> (f32, f32, f32) {
+    let [r, g, b] = rgb.map(|v| v as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (max + min) / 2.0;
+    let delta = max - min;
+    if delta == 0.0 {
+        return (0.0, 0.0, lightness);
+    }
+
+    let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (hue, saturation, lightness)
+}
+
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> [u8; 3] {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    [r, g, b].map(|v| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8)
+}
@@ -1,7 +1,8 @@
 use eyre::bail;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use std::fs::{read_to_string, write};
+use crate::paths::write_private;
+use std::fs::read_to_string;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 use tracing::error;
@@ -42,7 +43,7 @@ impl<V: Serialize + DeserializeOwned + Default> Config<V> {
 impl<V: Serialize> Config<V> {
     pub fn write_file(path: &Path, value: &V) -> eyre::Result<()> {
         let string = serde_json::to_string(&value)?;
-        write(path, string)?;
+        write_private(path, string)?;
         Ok(())
     }
 }
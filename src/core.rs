@@ -0,0 +1,105 @@
+//! What the window and the terminal frontend share: loading the apps and the usage data,
+//! and launching what was picked.
+
+use crate::apps::icons::AppIconManager;
+use crate::apps::{App, AppManager};
+use crate::i18n::tr;
+use crate::paths::Paths;
+use crate::platform::{self, LaunchRequest, PlatformLauncher};
+use crate::search::{SearchEngine, DEFAULT_HALF_LIFE_DAYS};
+use crate::settings::Settings;
+use eyre::Context;
+use tracing::{info, warn};
+
+pub struct Core {
+    pub apps: AppManager,
+    pub search: SearchEngine,
+}
+
+impl Core {
+    pub fn load(paths: &Paths, settings: &Settings) -> eyre::Result<Core> {
+        let apps = AppManager::new(settings).wrap_err("Failed to initialize ShortcutManager")?;
+        let mut search = SearchEngine::new(
+            &paths.data_dir,
+            settings.sync.dir().as_deref(),
+            settings.search.frecency_half_life_days,
+        )
+            .wrap_err("Failed to initialize SearchEngine")?;
+        search.apply_settings(settings);
+        search.apply_merged(&apps);
+        search.index_apps(&apps);
+        if let Err(error) = search.track_new_apps(&apps, &settings.new_apps) {
+            warn!("Could not track new apps: {error:?}");
+        }
+        Ok(Core { apps, search })
+    }
+}
+
+/// How to launch `app` with files or URLs as `args`, with the wrappers and resource limits
+/// of the settings.
+pub fn launch_request(
+    app: &App,
+    args: Vec<String>,
+    settings: &Settings,
+) -> LaunchRequest {
+    let mut launch = LaunchRequest::from(app);
+    if let LaunchRequest::App(launch) = &mut launch {
+        launch.args = args;
+        launch.login_env = settings.launch.login_env;
+        launch.notify = settings.launch.notify;
+        if app.terminal {
+            launch.terminal_command = settings.terminal.command();
+        }
+        let categories = app.categories.as_deref().unwrap_or_default();
+        launch.apply_wrappers(categories, &settings.wrappers);
+        let resources = &settings.resources;
+        let limits = app
+            .file_id()
+            .and_then(|id| resources.get(id))
+            .or_else(|| resources.get(&app.name));
+        if let Some(limits) = limits {
+            launch.apply_resources(limits);
+        }
+    }
+    launch
+}
+
+/// Runs `request` once the frontend is closed, notifies when it fails and records how it went.
+pub fn run_launch(paths: &Paths, request: &LaunchRequest) -> eyre::Result<()> {
+    info!("Launching {}", request.name());
+
+    let launch_log = paths.launch_log();
+    platform::set_launch_log(launch_log.clone());
+    let result = request.run(&PlatformLauncher);
+    let name = request.name().to_string();
+    if let Err(error) = &result {
+        let log = tr!("launch-failed-log", path = launch_log.display().to_string());
+        let body = format!("{error:#}\n{log}");
+        platform::notify(
+            &tr!("launch-failed", name = name.clone()),
+            &body,
+            Some("dialog-error"),
+        );
+    }
+    if let LaunchRequest::App(app) = request {
+        if app.notify && result.is_ok() {
+            let icon = AppIconManager::new(&paths.cache_dir)
+                .ok()
+                .and_then(|icons| icons.read_icon(&app.id));
+            let icon = icon.as_deref().and_then(|v| v.to_str());
+            platform::notify(&tr!("launching", name = name), "", icon);
+        }
+
+        // This runs next to the frontend or after it, which keeps its engine to itself.
+        let pid = result.as_ref().ok().copied().flatten();
+        let recorded = SearchEngine::new(&paths.data_dir, None, DEFAULT_HALF_LIFE_DAYS)
+            .and_then(|mut search| search.record_launch_result(&app.id, result.is_ok(), pid));
+        if let Err(error) = recorded {
+            warn!("Could not record the launch result: {error:?}");
+        }
+    }
+    result.wrap_err("Failed to launch")?;
+
+    info!("Launched! Baii~");
+    Ok(())
+}
@@ -0,0 +1,111 @@
+//! Translations of the ui strings, the catalogs live in `assets/locales/<locale>/ignition.ftl`.
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use std::env;
+use std::sync::OnceLock;
+use tracing::{error, info};
+use unic_langid::LanguageIdentifier;
+
+const FALLBACK_LOCALE: &str = "en-US";
+
+/// Every catalog we ship, add new translations here.
+const CATALOGS: &[(&str, &str)] = &[
+    ("en-US", include_str!("../assets/locales/en-US/ignition.ftl")),
+    ("de", include_str!("../assets/locales/de/ignition.ftl")),
+];
+
+struct Translations {
+    /// The user's locale first, then the fallback.
+    bundles: Vec<FluentBundle<FluentResource>>,
+}
+
+fn translations() -> &'static Translations {
+    static TRANSLATIONS: OnceLock<Translations> = OnceLock::new();
+    TRANSLATIONS.get_or_init(|| {
+        let mut bundles = Vec::new();
+        if let Some(locale) = user_catalog() {
+            info!("Using the {locale} translations");
+            bundles.extend(load_bundle(locale));
+        }
+        bundles.extend(load_bundle(FALLBACK_LOCALE));
+        Translations { bundles }
+    })
+}
+
+/// The locale of `$LC_ALL`/`$LC_MESSAGES`/`$LANG`, like `de-DE`.
+pub fn user_locale() -> Option<String> {
+    let value = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.is_empty())?;
+    Some(value.split(['.', '@']).next()?.replace('_', "-"))
+}
+
+/// The catalog for the [user_locale], matching `de-DE` to `de` if needed.
+fn user_catalog() -> Option<&'static str> {
+    let locale = user_locale()?;
+    let language = locale.split('-').next()?;
+
+    CATALOGS
+        .iter()
+        .map(|(name, _)| *name)
+        .filter(|name| *name != FALLBACK_LOCALE)
+        .find(|name| *name == locale)
+        .or_else(|| {
+            CATALOGS
+                .iter()
+                .map(|(name, _)| *name)
+                .filter(|name| *name != FALLBACK_LOCALE)
+                .find(|name| *name == language)
+        })
+}
+
+fn load_bundle(locale: &str) -> Option<FluentBundle<FluentResource>> {
+    let (_, source) = CATALOGS.iter().find(|(name, _)| *name == locale)?;
+    let language: LanguageIdentifier = locale.parse().ok()?;
+    let resource = FluentResource::try_new(source.to_string())
+        .map_err(|(_, errors)| error!("Invalid catalog {locale}: {errors:?}"))
+        .ok()?;
+
+    let mut bundle = FluentBundle::new_concurrent(vec![language]);
+    // egui renders the unicode isolation marks as boxes.
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .map_err(|errors| error!("Invalid catalog {locale}: {errors:?}"))
+        .ok()?;
+    Some(bundle)
+}
+
+/// Looks up `id` in the catalogs, prefer the [tr] macro.
+pub fn translate(id: &str, args: Option<&FluentArgs>) -> String {
+    for bundle in &translations().bundles {
+        let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) else {
+            continue;
+        };
+
+        let mut errors = Vec::new();
+        let value = bundle.format_pattern(pattern, args, &mut errors);
+        if !errors.is_empty() {
+            error!("Failed to format {id}: {errors:?}");
+        }
+        return value.to_string();
+    }
+
+    error!("Missing translation {id}");
+    id.to_string()
+}
+
+/// Translates a message, `tr!("indexing-icons", finished = 1, total = 2)`.
+macro_rules! tr {
+    ($id:literal) => {
+        $crate::i18n::translate($id, None)
+    };
+    ($id:literal, $($key:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($key), $value);)+
+        $crate::i18n::translate($id, Some(&args))
+    }};
+}
+pub(crate) use tr;
@@ -0,0 +1,169 @@
+use crossbeam::channel::{unbounded, Receiver};
+use eframe::egui;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+const SOCKET_NAME: &str = "ignition.sock";
+
+/// Commands another ignition process can send to the running instance.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum IpcCommand {
+    /// Close the launcher, used to toggle it from a keybinding.
+    Close,
+    /// Scan the applications again, sent by `ignition reindex`.
+    Reindex,
+}
+
+impl IpcCommand {
+    fn as_str(&self) -> &'static str {
+        match self {
+            IpcCommand::Close => "close",
+            IpcCommand::Reindex => "reindex",
+        }
+    }
+
+    fn parse(line: &str) -> Option<IpcCommand> {
+        match line.trim() {
+            "close" => Some(IpcCommand::Close),
+            "reindex" => Some(IpcCommand::Reindex),
+            _ => None,
+        }
+    }
+}
+
+/// Listens on `$XDG_RUNTIME_DIR/ignition/ignition.sock` for commands.
+///
+/// The socket is removed again when the server is dropped.
+pub struct IpcServer {
+    path: PathBuf,
+    receiver: Receiver<IpcCommand>,
+    context: Arc<Mutex<Option<egui::Context>>>,
+}
+
+impl IpcServer {
+    /// Returns a receiver for the commands sent to this instance.
+    pub fn receiver(&self) -> IpcReceiver {
+        IpcReceiver {
+            receiver: self.receiver.clone(),
+            context: self.context.clone(),
+        }
+    }
+}
+
+/// The ui side of the [IpcServer].
+pub struct IpcReceiver {
+    receiver: Receiver<IpcCommand>,
+    context: Arc<Mutex<Option<egui::Context>>>,
+}
+
+impl IpcReceiver {
+    /// Lets the server wake up the ui when a command arrives.
+    pub fn set_context(&self, context: egui::Context) {
+        *self.context.lock().unwrap() = Some(context);
+    }
+
+    pub fn try_recv(&self) -> Option<IpcCommand> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        if let Err(error) = std::fs::remove_file(&self.path) {
+            tracing::warn!("Failed to remove socket {:?}: {error}", self.path);
+        }
+    }
+}
+
+#[cfg(unix)]
+impl IpcServer {
+    pub fn bind(runtime_dir: &Path) -> eyre::Result<IpcServer> {
+        use eyre::Context;
+        use std::fs::{remove_file, set_permissions, Permissions};
+        use std::io::{BufRead, BufReader};
+        use std::os::unix::fs::PermissionsExt;
+        use std::io::ErrorKind;
+        use std::os::unix::net::{UnixListener, UnixStream};
+        use tracing::{debug, warn};
+
+        crate::paths::create_private_dir(runtime_dir).wrap_err("Failed to create runtime dir")?;
+        let path = runtime_dir.join(SOCKET_NAME);
+
+        // A socket nobody listens on was left behind by a crash, but another instance may have
+        // bound it since we last checked.
+        match UnixStream::connect(&path) {
+            Ok(_) => eyre::bail!("Another instance is listening on {path:?}"),
+            Err(error) if error.kind() == ErrorKind::ConnectionRefused => {
+                warn!("Removing stale socket {path:?}");
+                remove_file(&path).wrap_err("Failed to remove stale socket")?;
+            }
+            Err(_) => {}
+        }
+
+        let listener = UnixListener::bind(&path).wrap_err("Failed to bind socket")?;
+        set_permissions(&path, Permissions::from_mode(0o600))
+            .wrap_err("Failed to restrict socket permissions")?;
+
+        let (sender, receiver) = unbounded();
+        let context: Arc<Mutex<Option<egui::Context>>> = Arc::new(Mutex::new(None));
+        let context_c = context.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+
+                for line in BufReader::new(stream).lines() {
+                    let Ok(line) = line else {
+                        break;
+                    };
+                    let Some(command) = IpcCommand::parse(&line) else {
+                        warn!("Unknown ipc command {line:?}");
+                        continue;
+                    };
+
+                    debug!("Received ipc command {command:?}");
+                    if sender.send(command).is_err() {
+                        return;
+                    }
+                    if let Some(context) = &*context_c.lock().unwrap() {
+                        context.request_repaint();
+                    }
+                }
+            }
+        });
+
+        Ok(IpcServer {
+            path,
+            receiver,
+            context,
+        })
+    }
+}
+
+#[cfg(not(unix))]
+impl IpcServer {
+    pub fn bind(_runtime_dir: &Path) -> eyre::Result<IpcServer> {
+        eyre::bail!("IPC is not supported on this platform")
+    }
+}
+
+/// Sends a command to the running instance, fails if there is none.
+#[cfg(unix)]
+pub fn send(runtime_dir: &Path, command: IpcCommand) -> io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(runtime_dir.join(SOCKET_NAME))?;
+    writeln!(stream, "{}", command.as_str())?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn send(_runtime_dir: &Path, _command: IpcCommand) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "IPC is not supported on this platform",
+    ))
+}
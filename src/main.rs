@@ -1,13 +1,38 @@
-use std::process::Command;
+use std::env;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 
-use crate::apps::icons::AppIconManager;
+use crate::apps::icons::{self, AppIconManager};
+use crate::apps::running::{self, RunningApps};
+use crate::apps::watcher::AppWatcher;
 use crate::apps::{App, AppId, AppManager};
-use crate::search::{SearchEngine, SearchQuery, SearchResult, SearchResultEntry};
+use crate::bookmarks::Bookmarks;
+use crate::cli::Cli;
+use crate::core::Core;
+use crate::i18n::tr;
+use crate::ipc::{IpcCommand, IpcReceiver, IpcServer};
+use crate::media::{MediaCommand, MediaPlayers};
+use crate::paths::{expand_home, Paths};
+use crate::platform::LaunchRequest;
+use crate::projects::Projects;
+use crate::providers::ProviderSearch;
+use crate::settings::{Layout, Renderer, Settings};
+use crate::search::stats::UsageStats;
+use crate::search::{
+    EntryAction, HistoryEntry, SearchEngine, SearchResult, SearchResultEntry,
+    SearchScore,
+};
+use crate::secrets::SecretIndex;
+use crate::toggles::ToggleStates;
+use crate::ui::bar::{BarWidget, BAR_HEIGHT};
 use crate::ui::results::{ResultsEvent, ResultsWidget};
 use crate::ui::search_bar::{SearchBarMessage, SearchBarWidget};
-use dirs::{cache_dir, data_local_dir};
+use crate::ui::settings::SettingsWidget;
+use crate::ui::stats::{StatsWidget, STATS_DAYS};
+use chrono::Local;
+use clap::Parser;
 use eframe::egui::scroll_area::ScrollBarVisibility;
 use eframe::egui::style::{Spacing, TextCursorStyle};
 use eframe::egui::text::LayoutJob;
@@ -16,12 +41,12 @@ use eframe::egui::{
     Margin, NumExt, Order, Painter, Pos2, Rect, Rounding, ScrollArea, Sense, Shadow, Stroke, Style,
     TextEdit, TextFormat, Ui, Vec2, ViewportBuilder, Visuals, X11WindowType,
 };
-use eframe::emath::easing;
+use eframe::emath::{easing, TSTransform};
 use eframe::epaint::text::TextWrapping;
 use eframe::epaint::FontFamily;
 use eframe::{egui, NativeOptions};
 use egui_extras::install_image_loaders;
-use eyre::{Context, ContextCompat};
+use eyre::{eyre, Context};
 use splinter_icon::icon;
 use tracing::level_filters::LevelFilter;
 use tracing::{debug, error, info, warn};
@@ -29,46 +54,100 @@ use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::EnvFilter;
 use ui::framework::draw_icon;
 use ui::framework::load_fonts;
-use ui::framework::Colors;
+use ui::framework::{shortcut_pressed, Colors};
+use ui::template::RowTemplate;
 
+mod answers;
 mod apps;
+mod bookmarks;
+mod cli;
+mod color;
 mod config;
+mod core;
+mod i18n;
+mod ipc;
+mod media;
+mod paths;
+mod platform;
+mod projects;
+mod providers;
 mod search;
+mod secrets;
+mod settings;
+mod toggles;
+mod tui;
 mod ui;
+mod web;
 
 #[cfg(feature = "rounded_corners")]
 const ROUNDED_CORNERS_LEVEL: f32 = 0.0;
 #[cfg(not(feature = "rounded_corners"))]
 const ROUNDED_CORNERS_LEVEL: f32 = 16.0;
 
-struct ApplicationLaunch {
-    exec: String,
-}
-
 fn main() -> eyre::Result<()> {
     let filter = EnvFilter::from_default_env().add_directive("wgpu_core=error".parse()?);
     tracing_subscriber::fmt()
         .compact()
         .with_env_filter(filter)
         .with_max_level(LevelFilter::INFO)
+        // Keeps stdout clean for the output of the subcommands.
+        .with_writer(std::io::stderr)
         .finish()
         .init();
 
     let start = Instant::now();
-    let to_launch: Arc<Mutex<Option<ApplicationLaunch>>> = Arc::new(Mutex::new(None));
+    let to_launch: Arc<Mutex<Option<LaunchRequest>>> = Arc::new(Mutex::new(None));
+    let launching: Arc<Mutex<Option<JoinHandle<eyre::Result<()>>>>> = Arc::default();
+
+    let cli = Cli::parse();
+    let paths = Paths::resolve(cli.profile.as_deref());
+    let settings = Settings::load(&paths.settings_file());
+    ui::framework::set_glyphs(settings.icons.glyphs.clone());
+    ui::framework::set_theme(&settings.theme);
+    let provider_cache = paths.cache_dir.join("providers");
+    apps::cache::init(provider_cache, settings.cache_ttl.clone());
+    if let Some(command) = cli.command {
+        return command.run(&paths, &settings);
+    }
+    if cli.tui {
+        return tui::run(&paths, &settings);
+    }
+
+    // Opening ignition while it is already open closes it instead.
+    if let Some(runtime_dir) = &paths.runtime_dir {
+        if ipc::send(runtime_dir, IpcCommand::Close).is_ok() {
+            info!("Closed the running instance");
+            return Ok(());
+        }
+    }
+    let ipc = paths.runtime_dir.as_deref().and_then(|runtime_dir| {
+        IpcServer::bind(runtime_dir)
+            .inspect_err(|error| warn!("Could not start ipc server: {error:?}"))
+            .ok()
+    });
+    let ipc_receiver = ipc.as_ref().map(|ipc| ipc.receiver());
 
-    let cache_dir = cache_dir()
-        .wrap_err("Failed to find cache dir")?
-        .join("ignition");
-    let data_local_dir = data_local_dir()
-        .wrap_err("Failed to find data local dir")?
-        .join("ignition");
+    let window_size = match settings.window.layout {
+        Layout::List => WINDOW_SIZE,
+        Layout::Bar => Vec2::new(WINDOW_SIZE.x, SEARCH_BAR_HEIGHT + BAR_HEIGHT),
+    };
+    let output = settings.window.output.as_deref();
+    // Shows up before the apps are loaded and the GPU is set up, the window replaces it.
+    let placeholder = if settings.window.placeholder {
+        let rgb = |color: Color32| [color.r(), color.g(), color.b()];
+        let (background, border) = (rgb(Colors::crust()), rgb(Colors::surface0()));
+        platform::show_placeholder(window_size, output, background, border)
+    } else {
+        None
+    };
+    if placeholder.is_some() {
+        info!("Showed a placeholder in {:?}", start.elapsed());
+    }
 
     info!("Initializing core");
-    let apps = AppManager::new().wrap_err("Failed to initialize ShortcutManager")?;
-    let mut icons = AppIconManager::new(&cache_dir).wrap_err("Failed to initialize IconManager")?;
-    let search =
-        SearchEngine::new(&data_local_dir).wrap_err("Failed to initialize SearchEngine")?;
+    let Core { apps, search } = Core::load(&paths, &settings)?;
+    let mut icons = AppIconManager::new(&paths.cache_dir).wrap_err("Failed to initialize IconManager")?;
+    icons.set_theme(settings.icons.theme());
 
     //icons.clear_icons();
     info!("Loading icons");
@@ -76,20 +155,41 @@ fn main() -> eyre::Result<()> {
         icons.prepare_icon(shortcut);
     }
 
+    let app_watcher = AppWatcher::new(&AppManager::watch_dirs(&settings))
+        .inspect_err(|error| warn!("Could not watch the application dirs: {error:?}"))
+        .ok();
+
     info!("Initialized core in {:?}", start.elapsed());
     info!("Launching ui");
     let to_launch_c = to_launch.clone();
+    let launching_c = launching.clone();
+    let paths_c = paths.clone();
+    let position = platform::window_position(window_size, output);
+    let opaque = !platform::supports_transparency();
+    let settings_path = paths.settings_file();
+    let launch_log = paths.launch_log();
+    if opaque {
+        info!("No compositor, drawing an opaque window");
+    }
     eframe::run_native(
         "Ignition",
         NativeOptions {
             viewport: ViewportBuilder {
-                transparent: Some(true),
+                position,
+                inner_size: Some(window_size),
+                transparent: Some(!opaque),
                 decorations: Some(false),
                 fullscreen: Some(false),
                 maximized: Some(false),
                 window_type: Some(X11WindowType::Utility),
+                // What window rules of Wayland compositors match, we can't place the window there.
+                app_id: Some("ignition".to_string()),
                 ..ViewportBuilder::default()
             },
+            renderer: match settings.window.renderer {
+                Renderer::Wgpu => eframe::Renderer::Wgpu,
+                Renderer::Glow => eframe::Renderer::Glow,
+            },
             ..NativeOptions::default()
         },
         Box::new(move |context| {
@@ -99,7 +199,7 @@ fn main() -> eyre::Result<()> {
                     panel_fill: Color32::TRANSPARENT,
                     window_shadow: Shadow::NONE,
                     text_cursor: TextCursorStyle {
-                        stroke: Stroke::new(1.0, Colors::OVERLAY0),
+                        stroke: Stroke::new(1.0, Colors::overlay0()),
                         blink: false,
                         ..TextCursorStyle::default()
                     },
@@ -111,41 +211,88 @@ fn main() -> eyre::Result<()> {
                 },
                 ..Style::default()
             });
-            context.egui_ctx.set_fonts(load_fonts());
+            if let Some(ipc) = &ipc_receiver {
+                ipc.set_context(context.egui_ctx.clone());
+            }
+            if let Some(watcher) = &app_watcher {
+                watcher.set_context(context.egui_ctx.clone());
+            }
+            let icon_font = settings.icons.font.as_deref().map(expand_home);
+            let row_template = settings
+                .window
+                .row_template
+                .as_deref()
+                .and_then(|template| {
+                    RowTemplate::parse(template)
+                        .inspect_err(|error| warn!("Invalid row template: {error}"))
+                        .ok()
+                });
+            let fonts = load_fonts(icon_font.as_deref(), &settings.fonts.families);
+            context.egui_ctx.set_fonts(fonts);
             install_image_loaders(&context.egui_ctx);
             let mut application = Application {
                 start: Some(start),
                 to_launch: to_launch_c,
+                placeholder,
+                launching: launching_c,
+                paths: paths_c,
+                ipc: ipc_receiver,
+                app_watcher,
                 apps,
                 last_top: AppId::default(),
                 last_top_at: Instant::now(),
                 search_query: "".to_string(),
                 search_result: SearchResult::default(),
                 app_icons: icons,
+                running: RunningApps::default(),
                 selected: Some(0),
                 search,
                 case_sensitive: false,
+                show_details: false,
+                explain: cli.explain,
+                row_template,
+                opaque,
+                stats: None,
+                settings_open: false,
+                settings_path,
+                launch_log,
+                history: None,
+                notice: None,
+                confirming: None,
+                browse_categories: false,
+                x11_grab_pending: settings.window.x11_grab,
+                secrets: SecretIndex::load(&settings.secrets),
+                toggles: ToggleStates::default(),
+                bookmarks: Bookmarks::load(&settings),
+                projects: Projects::load(&settings.projects),
+                providers: ProviderSearch::default(),
+                media: MediaPlayers::default(),
+                settings,
                 has_window_ever_received_focus: false,
                 mouse_lock_from: Instant::now(),
                 first_focused_at: Instant::now(),
+                opened_at: Instant::now(),
+                closing_since: None,
             };
             application.search("");
 
             Ok(Box::new(application))
         }),
-    )?;
+    )
+    // The glow renderer's errors can't be sent between threads, which eyre needs.
+    .map_err(|error| eyre!("Failed to run the window: {error}"))?;
 
+    // Removes the socket
+    drop(ipc);
+
+    // Started by the ui already, the window may have been closed before it finished.
+    let mut launching = launching.lock().expect("Failed to lock launch mutex.");
+    if let Some(handle) = launching.take() {
+        return handle.join().expect("The launch thread panicked");
+    }
     let quard = to_launch.lock().expect("Failed to lock launch mutex.");
     if let Some(to_launch) = &*quard {
-        info!("Launching {}", to_launch.exec);
-
-        Command::new("gio")
-            .arg("launch")
-            .arg(&to_launch.exec)
-            .spawn()
-            .expect("failed to execute process");
-
-        info!("Launched! Baii~");
+        core::run_launch(&paths, to_launch)?;
     }
     Ok(())
 }
@@ -153,11 +300,22 @@ fn main() -> eyre::Result<()> {
 pub struct Application {
     /// This is used to measure how long the application took to launch
     start: Option<Instant>,
+    /// Closed once the window drew its first frame, see [platform::show_placeholder].
+    placeholder: Option<platform::Placeholder>,
     /// This is the mutex holding what application we will launch
-    to_launch: Arc<Mutex<Option<ApplicationLaunch>>>,
+    to_launch: Arc<Mutex<Option<LaunchRequest>>>,
+    /// Requests that start a process are run while the window fades out, see
+    /// [LaunchRequest::spawns]. The window stays until they are done.
+    launching: Arc<Mutex<Option<JoinHandle<eyre::Result<()>>>>>,
+    paths: Paths,
+    /// Commands sent by other ignition processes
+    ipc: Option<IpcReceiver>,
+    /// Rescans the apps when their dirs change.
+    app_watcher: Option<AppWatcher>,
 
     apps: AppManager,
     app_icons: AppIconManager,
+    running: RunningApps,
 
     search: SearchEngine,
     search_query: String,
@@ -172,18 +330,89 @@ pub struct Application {
     selected: Option<usize>,
 
     case_sensitive: bool,
+    /// Toggled with Ctrl+I, shows the metadata of the selected app.
+    show_details: bool,
+    /// Toggled with Ctrl+D or set with `--explain`, shows how the scores came together.
+    explain: bool,
+    /// What the result rows show, from the settings.
+    row_template: Option<RowTemplate>,
+    /// Nothing blends the window with what is below (X11 without a compositor),
+    /// so it gets an opaque background and square corners.
+    opaque: bool,
+    /// Set while the usage statistics are shown, with `:stats` or Ctrl+S.
+    stats: Option<UsageStats>,
+    /// Toggled with Ctrl+, shows the settings instead of the results.
+    settings_open: bool,
+    /// Where the settings are saved from the settings view.
+    settings_path: PathBuf,
+    /// Opened from the context menu of apps that crashed.
+    launch_log: PathBuf,
+    /// Set while the launch history is shown, with `:history` or Ctrl+H.
+    /// Lines up with the entries of `search_result`.
+    history: Option<Vec<HistoryEntry>>,
+    /// Why the last action didn't work, shown until the query changes.
+    notice: Option<String>,
+    /// The entry that was pressed once and needs another press, see [SearchResultEntry::confirm].
+    confirming: Option<AppId>,
+    /// The empty query shows the apps by category instead of the home view.
+    browse_categories: bool,
+
+    settings: Settings,
+    /// The names of the passwords, searched after `secrets.prefix`.
+    secrets: SecretIndex,
+    /// Whether quick settings like dark mode are on, read once a query finds one.
+    toggles: ToggleStates,
+    /// Directories to open in the file manager, a terminal or an editor.
+    bookmarks: Bookmarks,
+    /// Recent projects of VS Code or a fork of it.
+    projects: Projects,
+    /// Results of GNOME Shell search providers, which arrive after the query.
+    providers: ProviderSearch,
+    /// The media players, read once a query is about them.
+    media: MediaPlayers,
+    /// Set until we did the X11 grab, which needs the window to exist.
+    x11_grab_pending: bool,
 
     // These are to prevent the window from getting instantly closed on launch.
     // When your mouse is not instantly on the window.
     has_window_ever_received_focus: bool,
     first_focused_at: Instant,
+
+    /// When the first frame was drawn, for the open transition.
+    opened_at: Instant,
+    /// Set once we are about to close, for the close transition.
+    closing_since: Option<Instant>,
 }
 
+const WINDOW_SIZE: Vec2 = Vec2::new(800.0, 600.0);
+const SEARCH_BAR_HEIGHT: f32 = 64.0;
 const ENTRY_HEIGHT: f32 = 32.0;
 const ENTRY_SPACING: f32 = 8.0;
 const IMAGE_SIZE: f32 = 24.0;
+/// How much smaller the window starts on open.
+const TRANSITION_SCALE: f32 = 0.04;
 impl Application {
+    /// How visible the window is, going from 0 to 1 when opening and back to 0 when closing.
+    fn transition(&self) -> f32 {
+        let transition_ms = self.settings.animation.transition_ms;
+        if transition_ms == 0 {
+            let closing = self.closing_since.is_some();
+            return if closing { 0.0 } else { 1.0 };
+        }
+
+        let duration = Duration::from_millis(transition_ms).as_secs_f32();
+        match self.closing_since {
+            Some(since) => 1.0 - (since.elapsed().as_secs_f32() / duration).clamp(0.0, 1.0),
+            None => (self.opened_at.elapsed().as_secs_f32() / duration).clamp(0.0, 1.0),
+        }
+    }
+
     pub fn search(&mut self, query: &str) {
+        // Paths are case-sensitive.
+        let terminal = EntryAction::from_query(query.trim(), &self.settings.terminal);
+        let commands = EntryAction::commands(query.trim(), &self.settings.terminal);
+        // Search engines get what was typed, not the lowercased query.
+        let typed = query.trim().to_string();
         let mut query = query.to_string();
         if !self.case_sensitive {
             query = query.to_lowercase();
@@ -192,7 +421,58 @@ impl Application {
 
         let start = Instant::now();
 
-        let results = self.search.search(query.to_string(), &self.apps);
+        self.stats = None;
+        self.history = None;
+        self.notice = None;
+        self.confirming = None;
+        match query.trim() {
+            ":stats" => self.toggle_stats(),
+            ":history" => {
+                self.toggle_history();
+                return;
+            }
+            ":autostart" => {
+                self.show_autostart();
+                return;
+            }
+            _ => {}
+        }
+
+        let secret_query = self.settings.secrets.query(&query);
+        let mut results = SearchResult::default();
+        if let Some(name) = secret_query {
+            results.entries = self.secrets.search(name);
+        } else if query.is_empty() && self.browse_categories {
+            results = self.search.categories(&self.apps);
+        } else if query.is_empty() && self.settings.home.enabled {
+            results = self.search.home(&self.apps, &self.settings.home);
+        }
+        if results.entries.is_empty() && secret_query.is_none() {
+            results = self.search.search(query.to_string(), &self.apps);
+            let top_score = results.entries.first().map_or(0.0, |v| v.score.score);
+            let mut actions = color::search(&query);
+            actions.extend(EntryAction::quick_actions(&query));
+            actions.extend(self.toggles.search(&query));
+            actions.extend(self.bookmarks.search(&query, &self.settings));
+            actions.extend(self.projects.search(&query));
+            actions.extend(self.media.search(&query));
+            let providers = &self.settings.search_providers;
+            actions.extend(self.providers.search(&query, providers));
+            // After the best app, so they don't push away what was searched for.
+            let at = results.entries.len().min(1);
+            results.entries.splice(at..at, actions);
+            let web = web::entry(&typed, top_score, &self.settings.web);
+            if let Some(entry) = web.filter(|_| terminal.is_none() && commands.is_empty()) {
+                results.entries.insert(0, entry);
+            }
+            // Instant answers are what was asked for, above everything else.
+            results.entries.splice(0..0, answers::search(&query));
+        }
+        if let Some(entry) = terminal {
+            results.entries.insert(0, entry);
+        } else {
+            results.entries.splice(0..0, commands);
+        }
         let top = results
             .entries
             .first()
@@ -209,15 +489,29 @@ impl Application {
 
     pub fn draw_search_bar(&mut self, ui: &mut Ui) {
         let mut messages = Vec::new();
+        if let Some(notice) = &self.notice {
+            messages.push(SearchBarMessage {
+                text: notice.clone(),
+                color: Colors::red(),
+            });
+        }
         if self.case_sensitive {
             messages.push(SearchBarMessage {
-                text: "Case-sensitive".to_string(),
-                color: Colors::PEACH,
+                text: tr!("case-sensitive"),
+                color: Colors::peach(),
             });
         } else if self.search_query.chars().any(|v| v.is_uppercase()) {
             messages.push(SearchBarMessage {
-                text: "CapsLock Ignored".to_string(),
-                color: Colors::YELLOW,
+                text: tr!("caps-lock-ignored"),
+                color: Colors::yellow(),
+            });
+        }
+
+        let secret_query = self.settings.secrets.query(&self.search_query);
+        if secret_query.is_some() && self.secrets.is_loading() {
+            messages.push(SearchBarMessage {
+                text: tr!("secrets-loading"),
+                color: Colors::blue(),
             });
         }
 
@@ -225,8 +519,12 @@ impl Application {
         let to_load = self.app_icons.to_load();
         if to_load != to_load_finished {
             messages.push(SearchBarMessage {
-                text:  format!("Indexing Icons {to_load_finished}/{to_load} (this may freeze)"),
-                color: Colors::BLUE,
+                text: tr!(
+                    "indexing-icons",
+                    finished = to_load_finished,
+                    total = to_load
+                ),
+                color: Colors::blue(),
             });
         }
 
@@ -258,13 +556,54 @@ impl Application {
     }
 
     pub fn draw_entries(&mut self, ui: &mut Ui) {
-        let events = ResultsWidget {
-            apps: &self.apps,
-            app_icons: &self.app_icons,
-            results: &self.search_result,
-            selected: self.selected,
+        if self.settings_open {
+            let output = SettingsWidget {
+                settings: &mut self.settings,
+            }
+            .ui(ui);
+            if output.changed {
+                self.search.apply_settings(&self.settings);
+                self.search(&self.search_query.clone());
+            }
+            if output.save {
+                self.save_settings();
+            }
+            return;
         }
-        .ui(ui);
+        if let Some(stats) = &self.stats {
+            StatsWidget {
+                apps: &self.apps,
+                stats,
+            }
+            .ui(ui);
+            return;
+        }
+
+        let events = match self.settings.window.layout {
+            Layout::List => ResultsWidget {
+                apps: &self.apps,
+                search: &self.search,
+                app_icons: &self.app_icons,
+                running: &self.running,
+                results: &self.search_result,
+                selected: self.selected,
+                show_details: self.show_details,
+                explain: self.explain,
+                confirming: self.confirming.as_ref(),
+                template: self.row_template.as_ref(),
+            }
+            .ui(ui),
+            Layout::Bar => BarWidget {
+                apps: &self.apps,
+                app_icons: &self.app_icons,
+                running: &self.running,
+                results: &self.search_result,
+                selected: self.selected,
+                show_details: self.show_details,
+                confirming: self.confirming.as_ref(),
+            }
+            .ui(ui),
+        };
         for event in events {
             match event {
                 ResultsEvent::Hovered(app_id) => {
@@ -281,19 +620,423 @@ impl Application {
                         self.selected = Some(idx);
                     }
                 }
+                ResultsEvent::SwitchTo(app) => self.switch_to(app),
+                ResultsEvent::ShowLaunchLog => {
+                    let log = self.launch_log.to_string_lossy().to_string();
+                    *self.to_launch.lock().unwrap() = Some(LaunchRequest::Uri(log));
+                }
+                ResultsEvent::ToggleTag(app, tag) => {
+                    if let Err(error) = self.search.toggle_tag(&app, &tag) {
+                        error!("Could not tag: {error:?}");
+                    }
+                    let query = self.search_query.clone();
+                    self.search(&query);
+                }
+                #[cfg(all(unix, not(target_os = "macos")))]
+                ResultsEvent::EditEntry { app, copy } => self.edit_entry(app, copy),
+                #[cfg(all(unix, not(target_os = "macos")))]
+                ResultsEvent::AddToAutostart(app) => self.add_to_autostart(app),
+                #[cfg(all(unix, not(target_os = "macos")))]
+                ResultsEvent::ManagePackage { app, uninstall } => {
+                    self.manage_package(app, uninstall)
+                }
                 ResultsEvent::Pressed(app) => {
-                    self.open(app);
+                    if self.history.is_some() {
+                        // The same app can be in the history more than once.
+                        self.open_selected();
+                    } else {
+                        self.open(app);
+                    }
+                }
+            }
+        }
+    }
+
+    fn toggle_stats(&mut self) {
+        if self.stats.take().is_some() {
+            return;
+        }
+        match self.search.stats(STATS_DAYS) {
+            Ok(stats) => self.stats = Some(stats),
+            Err(error) => error!("Could not compute the usage statistics: {error:?}"),
+        }
+    }
+
+    /// Lists the recent launches instead of the search results.
+    fn toggle_history(&mut self) {
+        if self.history.take().is_some() {
+            let query = self.search_query.clone();
+            self.search(&query);
+            return;
+        }
+        let mut history = match self.search.history() {
+            Ok(history) => history,
+            Err(error) => {
+                error!("Could not load the launch history: {error:?}");
+                return;
+            }
+        };
+        history.retain(|entry| self.apps.applications.contains_key(&entry.id));
+
+        let entries = history
+            .iter()
+            .map(|entry| {
+                let mut detail = entry.at.with_timezone(&Local).format("%x %R").to_string();
+                if !entry.args.is_empty() {
+                    detail += &format!("  {}", entry.args.join(" "));
                 }
+                SearchResultEntry {
+                    id: entry.id.clone(),
+                    score: SearchScore::default(),
+                    detail: Some(detail),
+                    broken: self.search.is_broken(&entry.id),
+                    action: None,
+                    confirm: false,
+                }
+            })
+            .collect();
+        self.search_result = SearchResult {
+            entries,
+            ..SearchResult::default()
+        };
+        self.history = Some(history);
+        self.selected = Some(0);
+    }
+
+    /// Lists the programs started at login instead of the search results.
+    fn show_autostart(&mut self) {
+        let entries = match platform::autostart_entries() {
+            Ok(entries) => entries,
+            Err(error) => {
+                error!("Could not list the autostart entries: {error:?}");
+                self.notice = Some(tr!("autostart-failed"));
+                return;
+            }
+        };
+        let entries = entries
+            .into_iter()
+            .map(|entry| {
+                let state = if entry.enabled {
+                    tr!("toggle-on")
+                } else {
+                    tr!("toggle-off")
+                };
+                SearchResultEntry {
+                    id: AppId::from_properties(&["autostart", &entry.id]),
+                    score: SearchScore::default(),
+                    detail: Some(state),
+                    broken: false,
+                    action: Some(EntryAction::Autostart(entry)),
+                    confirm: false,
+                }
+            })
+            .collect();
+        self.search_result = SearchResult {
+            entries,
+            ..SearchResult::default()
+        };
+        self.selected = Some(0);
+    }
+
+    /// Copies the desktop entry of `id` to the autostart dir.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn add_to_autostart(&mut self, id: AppId) {
+        let Some(app) = self.apps.applications.get(&id) else {
+            return;
+        };
+        match platform::add_to_autostart(&app.path) {
+            Ok(path) => info!("Added {} to autostart as {path:?}", app.name),
+            Err(error) => {
+                error!("Could not add {} to autostart: {error:?}", app.name);
+                self.notice = Some(tr!("autostart-failed"));
+            }
+        }
+    }
+
+    /// Saves the settings of the settings view and closes it.
+    fn save_settings(&mut self) {
+        if let Err(error) = self.settings.save(&self.settings_path) {
+            error!("Could not save the settings: {error:?}");
+            self.notice = Some(tr!("settings-save-failed"));
+            return;
+        }
+        self.settings_open = false;
+        // The ignored apps and merging duplicates need a rescan.
+        self.refresh_apps();
+    }
+
+    /// Opens the selected entry, in history mode with the arguments it had back then.
+    fn open_selected(&mut self) {
+        if self.stats.is_some() || self.settings_open {
+            return;
+        }
+        if let Some(history) = &self.history {
+            let entry = self.selected.and_then(|selected| history.get(selected));
+            if let Some(entry) = entry {
+                let (id, args) = (entry.id.clone(), entry.args.clone());
+                self.open_with(id, args);
             }
+            return;
+        }
+        if let Some(selected) = self.selected() {
+            self.open(selected.clone());
         }
     }
 
+    /// Launches the selected app with the files or URLs on the clipboard.
+    fn open_selected_with_clipboard(&mut self) {
+        let Some(id) = self.selected().cloned() else {
+            return;
+        };
+        let Some(app) = self.apps.applications.get(&id) else {
+            return;
+        };
+
+        let uris = match platform::clipboard_uris() {
+            Ok(uris) => uris,
+            Err(error) => {
+                warn!("Could not use the clipboard: {error:?}");
+                self.notice = Some(tr!("clipboard-invalid"));
+                return;
+            }
+        };
+        if let Some(uri) = uris.iter().find(|uri| !app.accepts(uri)) {
+            warn!("{} can't open {uri}", app.name);
+            self.notice = Some(tr!("clipboard-unsupported", app = app.name.clone()));
+            return;
+        }
+
+        self.open_with(id, uris);
+    }
+
     fn selected(&self) -> Option<&AppId> {
         self.selected
             .and_then(|v| self.search_result.entries.get(v).map(|v| &v.id))
     }
 
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn grab_x11(&self, frame: &eframe::Frame) {
+        let Some(window) = platform::x11::window_id(frame) else {
+            warn!("The X11 grab mode only works on X11");
+            return;
+        };
+        if let Err(error) = platform::x11::grab(frame, window) {
+            error!("Failed to grab the keyboard: {error:?}");
+        }
+    }
+
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    fn grab_x11(&self, _frame: &eframe::Frame) {
+        warn!("The X11 grab mode only works on X11");
+    }
+
     fn open(&mut self, id: AppId) {
+        let Some(entry) = self.search_result.entries.iter().find(|v| v.id == id) else {
+            return;
+        };
+        if entry.confirm && self.confirming.as_ref() != Some(&id) {
+            self.confirming = Some(id);
+            return;
+        }
+
+        if let Some(action) = entry.action.clone() {
+            self.run_action(action);
+            return;
+        }
+        self.open_with(id, Vec::new());
+    }
+
+    fn run_action(&mut self, action: EntryAction) {
+        let launch = match action {
+            EntryAction::OpenTerminal(dir) => LaunchRequest::Terminal {
+                command: self.settings.terminal.command(),
+                dir,
+                run: Vec::new(),
+            },
+            EntryAction::CopySecret(entry) => LaunchRequest::CopySecret {
+                entry,
+                clear_after: Duration::from_secs(self.settings.secrets.clear_after),
+            },
+            EntryAction::Capture(capture) => LaunchRequest::Capture(capture),
+            EntryAction::Toggle(toggle, on) => LaunchRequest::SetToggle { toggle, on: !on },
+            // The launcher stays open to turn more on or off.
+            EntryAction::Autostart(entry) => {
+                if let Err(error) = platform::set_autostart(&entry.id, !entry.enabled) {
+                    let name = &entry.name;
+                    error!("Could not change the autostart of {name}: {error:?}");
+                    self.notice = Some(tr!("autostart-failed"));
+                    return;
+                }
+                let selected = self.selected;
+                self.show_autostart();
+                self.selected = selected;
+                return;
+            }
+            EntryAction::WebSearch(search) => LaunchRequest::Uri(search.url),
+            EntryAction::ProviderResult(result) => LaunchRequest::ProviderResult(result),
+            EntryAction::Answer(text) | EntryAction::Color { text, .. } => {
+                LaunchRequest::CopyText(text)
+            }
+            EntryAction::Media(player, MediaCommand::Raise) => LaunchRequest::Media {
+                player,
+                command: MediaCommand::Raise,
+            },
+            // The launcher stays open to send more.
+            EntryAction::Media(player, command) => {
+                self.media.control(&player, command);
+                return;
+            }
+            EntryAction::OpenProject { project, .. } => {
+                projects::launch_request(&project, &self.settings.projects)
+            }
+            EntryAction::RunCommand { line, terminal } => {
+                let run = platform::shell_command(&line);
+                let dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("/"));
+                if terminal {
+                    LaunchRequest::Terminal {
+                        command: self.settings.terminal.command(),
+                        dir,
+                        run,
+                    }
+                } else {
+                    LaunchRequest::Command { command: run, dir }
+                }
+            }
+            EntryAction::OpenDir(bookmark, target) => {
+                match bookmarks::launch_request(&bookmark.path, target, &self.settings) {
+                    Some(launch) => launch,
+                    None => return,
+                }
+            }
+        };
+        *self.to_launch.lock().unwrap() = Some(launch);
+    }
+
+    /// Opens the desktop entry of `id` in `$VISUAL`/`$EDITOR` or the default text editor.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn edit_entry(&mut self, id: AppId, copy: bool) {
+        let Some(app) = self.apps.applications.get(&id) else {
+            return;
+        };
+        let mut path = app.path.clone();
+        if copy {
+            match platform::copy_to_user(&path) {
+                Ok(copy) => {
+                    info!("Copied {:?} to {copy:?}", app.path);
+                    path = copy;
+                }
+                Err(error) => {
+                    error!("Could not copy the desktop entry: {error:?}");
+                    return;
+                }
+            }
+        }
+
+        // Those are usually terminal editors.
+        let editor = env::var("VISUAL").or_else(|_| env::var("EDITOR")).ok();
+        let terminal = self.settings.terminal.command();
+        let launch = match (editor, terminal, path.parent()) {
+            (Some(editor), Some(terminal), Some(dir)) => {
+                let mut run: Vec<String> = editor.split_whitespace().map(String::from).collect();
+                run.push(path.to_string_lossy().to_string());
+                LaunchRequest::Terminal {
+                    command: Some(terminal),
+                    dir: dir.to_path_buf(),
+                    run,
+                }
+            }
+            _ => LaunchRequest::Uri(path.to_string_lossy().to_string()),
+        };
+        *self.to_launch.lock().unwrap() = Some(launch);
+    }
+
+    /// Picks up installed and removed apps, keeping the query and what is selected.
+    fn refresh_apps(&mut self) {
+        let changes = match self.apps.refresh(&self.settings) {
+            Ok(changes) => changes,
+            Err(error) => {
+                error!("Could not rescan the applications: {error:?}");
+                return;
+            }
+        };
+        info!(
+            "Rescanned the applications, {} added and {} removed",
+            changes.added.len(),
+            changes.removed.len()
+        );
+        for id in &changes.added {
+            if let Some(app) = self.apps.applications.get(id) {
+                self.app_icons.prepare_icon(app);
+            }
+        }
+        self.search.apply_merged(&self.apps);
+        self.search.index_apps(&self.apps);
+        if let Err(error) = self
+            .search
+            .track_new_apps(&self.apps, &self.settings.new_apps)
+        {
+            warn!("Could not track new apps: {error:?}");
+        }
+
+        let selected = self.selected().cloned();
+        self.search(&self.search_query.clone());
+        if let Some(selected) = selected {
+            let index = self
+                .search_result
+                .entries
+                .iter()
+                .position(|v| v.id == selected);
+            if index.is_some() {
+                self.selected = index;
+            }
+        }
+    }
+
+    /// Runs the package manager in a terminal, it asks before uninstalling anything.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn manage_package(&mut self, id: AppId, uninstall: bool) {
+        let Some(app) = self.apps.applications.get(&id) else {
+            return;
+        };
+        let flatpak = app.flatpak.as_deref();
+        let Some(package) = platform::packages::Package::find(&app.path, flatpak) else {
+            self.notice = Some(tr!("package-unknown", app = app.name.clone()));
+            return;
+        };
+        let Some(terminal) = self.settings.terminal.command() else {
+            self.notice = Some(tr!("package-no-terminal"));
+            return;
+        };
+        info!("{} belongs to {package:?}", app.name);
+
+        let command = if uninstall {
+            package.uninstall_command()
+        } else {
+            package.info_command()
+        };
+        let run = platform::packages::pause_after(command, &tr!("package-press-enter"));
+        *self.to_launch.lock().unwrap() = Some(LaunchRequest::Terminal {
+            command: Some(terminal),
+            dir: dirs::home_dir().unwrap_or_else(|| "/".into()),
+            run,
+        });
+    }
+
+    /// Focuses the window of `id` that is still open from its last launch, or launches it
+    /// when that is gone.
+    fn switch_to(&mut self, id: AppId) {
+        let Some(app) = self.apps.applications.get(&id) else {
+            return;
+        };
+        let mut launch = core::launch_request(app, Vec::new(), &self.settings);
+        if let LaunchRequest::App(launch) = &mut launch {
+            launch.focus_pid = self.search.running_pid(&id);
+        }
+        *self.to_launch.lock().unwrap() = Some(launch);
+    }
+
+    /// Launches `id` with files or URLs as `args`.
+    fn open_with(&mut self, id: AppId, args: Vec<String>) {
         let Some(app) = self.apps.applications.get(&id) else {
             return;
         };
@@ -313,46 +1056,142 @@ impl Application {
             return;
         }
 
-        let buf = app.path.canonicalize().unwrap();
-        let file_path = buf.to_str().unwrap();
-        let launch = ApplicationLaunch {
-            exec: file_path.to_string(),
-        };
+        let launch = core::launch_request(app, args.clone(), &self.settings);
+
+        let rank = self.search_result.entries.iter().position(|v| v.id == id);
+        if let Some(rank) = rank {
+            let query = &self.search_result.query;
+            if let Err(error) = self.search.record_choice(query, &id, rank) {
+                warn!("Could not remember the pick for {query:?}: {error:?}");
+            }
+        }
 
         let mut to_launch = self.to_launch.lock().unwrap();
         *to_launch = Some(launch);
-        self.search.record_use(id).unwrap();
+        self.search
+            .record_use(id, Some(self.opened_at.elapsed()), &args)
+            .unwrap();
     }
 }
 
 impl eframe::App for Application {
-    fn update(&mut self, ctx: &egui::Context, _: &mut eframe::Frame) {
-        if self.app_icons.tick() {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // The window shows up after the first frame, the placeholder stays until then.
+        if self.placeholder.is_some() {
+            if ctx.cumulative_pass_nr() > 0 {
+                self.placeholder = None;
+                info!("Replaced the placeholder");
+            } else {
+                ctx.request_repaint();
+            }
+        }
+        // Sharp icons on HiDPI monitors, and again after moving to one with another scale.
+        if self.app_icons.set_scale(ctx.pixels_per_point()) {
+            for app in self.apps.applications.values() {
+                self.app_icons.prepare_icon(app);
+            }
+        }
+        // Only repaint for new icons instead of continuously while they load.
+        let loaded = self.app_icons.tick();
+        if !loaded.is_empty() {
+            // egui still has the texture of an earlier icon at the path, like before a rescale.
+            for path in loaded.iter().filter_map(|path| path.canonicalize().ok()) {
+                ctx.forget_image(&format!("file://{}", path.to_str().unwrap()));
+            }
+            ctx.request_repaint();
+        }
+        if self.app_icons.is_loading() {
+            ctx.request_repaint_after(icons::POLL_INTERVAL);
+        }
+        if self.secrets.tick() && self.settings.secrets.query(&self.search_query).is_some() {
+            self.search(&self.search_query.clone());
+            ctx.request_repaint();
+        }
+        if self.projects.tick() {
+            self.search(&self.search_query.clone());
             ctx.request_repaint();
         }
+        if self.toggles.tick() {
+            self.search(&self.search_query.clone());
+            ctx.request_repaint();
+        }
+        if self.providers.tick() {
+            self.search(&self.search_query.clone());
+            ctx.request_repaint();
+        }
+        if self.media.tick() {
+            self.search(&self.search_query.clone());
+            ctx.request_repaint();
+        }
+        let loading = [
+            self.secrets.is_loading(),
+            self.projects.is_loading(),
+            self.toggles.is_loading(),
+            self.providers.is_loading(),
+            self.media.is_loading(),
+        ];
+        if loading.contains(&true) {
+            ctx.request_repaint_after(icons::POLL_INTERVAL);
+        }
+        if self.settings.window.running_indicator {
+            if self.running.tick() {
+                ctx.request_repaint();
+            }
+            ctx.request_repaint_after(running::REFRESH_INTERVAL);
+        }
 
 
         if let Some(start) = self.start.take() {
             info!("Initialized in {:?}", start.elapsed());
+            self.opened_at = Instant::now();
+        }
+
+        if self.x11_grab_pending {
+            self.x11_grab_pending = false;
+            self.grab_x11(frame);
         }
         let focused = ctx.viewport(|v| v.input.focused);
 
         let mut should_close = false;
+        let mut reindex = self.app_watcher.as_ref().is_some_and(AppWatcher::changed);
+        if let Some(ipc) = &self.ipc {
+            while let Some(command) = ipc.try_recv() {
+                match command {
+                    IpcCommand::Close => should_close = true,
+                    IpcCommand::Reindex => reindex = true,
+                }
+            }
+        }
+        if reindex {
+            self.refresh_apps();
+        }
+
+        let transition = self.transition();
+        if transition > 0.0 && transition < 1.0 {
+            ctx.request_repaint();
+        }
+        let opacity = easing::cubic_out(transition);
+
         let rect = ctx.available_rect();
         CentralPanel::default()
             .frame(Frame::none().inner_margin(Margin::symmetric(0.0, 0.0)))
             .show(ctx, |ui| {
+                ui.set_opacity(opacity);
                 ui.input(|input| {
                     let mut to_offset = 0isize;
+                    let keys = self.settings.keys.clone();
                     for event in &input.events {
                         if let Event::Key {
                             key: Key::Enter,
                             pressed: true,
+                            modifiers,
                             ..
                         } = event
                         {
-                            if let Some(selected) = self.selected() {
-                                self.open(selected.clone());
+                            if modifiers.alt {
+                                self.open_selected_with_clipboard();
+                            } else {
+                                self.open_selected();
                             }
                         };
                         if let Event::Key {
@@ -380,19 +1219,56 @@ impl eframe::App for Application {
                             to_offset -= 1;
                         };
                         if let Event::Key {
-                            key: Key::R,
+                            key: key @ (Key::ArrowLeft | Key::ArrowRight),
                             pressed: true,
                             modifiers,
                             ..
                         } = event
                         {
-                            if modifiers.ctrl {
-                                self.app_icons.clear_icons();
-                                for shortcut in self.apps.applications.values() {
-                                    self.app_icons.prepare_icon(shortcut);
+                            // The bar moves the selection, Ctrl switches the view there.
+                            let bar = self.settings.window.layout == Layout::Bar;
+                            if bar && !modifiers.ctrl {
+                                to_offset += if *key == Key::ArrowRight { 1 } else { -1 };
+                            } else if self.search_query.is_empty() {
+                                self.browse_categories = !self.browse_categories;
+                                self.search("");
+                            }
+                        }
+                        if shortcut_pressed(event, &keys.reload_icons) {
+                            self.app_icons.clear_icons();
+                            for shortcut in self.apps.applications.values() {
+                                self.app_icons.prepare_icon(shortcut);
+                            }
+                        };
+                        if shortcut_pressed(event, &keys.rescan) {
+                            apps::cache::clear();
+                            self.refresh_apps();
+                        };
+                        if shortcut_pressed(event, &keys.pin) {
+                            if let Some(selected) = self.selected() {
+                                let selected = selected.clone();
+                                match self.search.toggle_pin(&selected) {
+                                    Ok(pinned) => info!("Toggled pin, pinned: {pinned}"),
+                                    Err(error) => error!("Could not pin: {error:?}"),
                                 }
+                                self.search(&self.search_query.clone());
                             }
                         };
+                        if shortcut_pressed(event, &keys.details) {
+                            self.show_details = !self.show_details;
+                        };
+                        if shortcut_pressed(event, &keys.explain) {
+                            self.explain = !self.explain;
+                        };
+                        if shortcut_pressed(event, &keys.stats) {
+                            self.toggle_stats();
+                        };
+                        if shortcut_pressed(event, &keys.settings) {
+                            self.settings_open = !self.settings_open;
+                        };
+                        if shortcut_pressed(event, &keys.history) {
+                            self.toggle_history();
+                        };
                         if let Event::MouseWheel { delta, .. } = event {
                             if delta.y > 0.0 {
                                 to_offset -= 1;
@@ -403,12 +1279,13 @@ impl eframe::App for Application {
                         };
                     }
 
+                    let count = self.search_result.entries.len();
                     let increase = to_offset.signum() == 1;
                     for _ in 0..to_offset.abs() {
                         if increase {
                             if let Some(value) = &mut self.selected {
                                 *value = value.saturating_add(1);
-                                if *value == self.apps.applications.len() {
+                                if *value >= count {
                                     // reached end, wrap back to first (zeroth) entry
                                     *value = 0;
                                 }
@@ -420,7 +1297,7 @@ impl eframe::App for Application {
                             if let Some(value) = &mut self.selected {
                                 if *value == 0 {
                                     // reached beginning, wrap back to final entry
-                                    self.selected = Some(self.apps.applications.len());
+                                    self.selected = Some(count.saturating_sub(1));
                                 } else {
                                     *value = value.saturating_sub(1);
                                 }
@@ -429,7 +1306,7 @@ impl eframe::App for Application {
                     }
 
                     if let Some(value) = &mut self.selected {
-                        *value = (*value).clamp(0, self.search_result.entries.len() - 1);
+                        *value = (*value).min(count.saturating_sub(1));
                     }
                 });
 
@@ -437,21 +1314,58 @@ impl eframe::App for Application {
                 self.draw_entries(ui);
             });
 
-        let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("Border")));
+        let border_layer = LayerId::new(Order::Foreground, Id::new("Border"));
+        let painter = ctx.layer_painter(border_layer);
+        let rounding = if self.opaque {
+            0.0
+        } else {
+            ROUNDED_CORNERS_LEVEL
+        };
         painter.rect_stroke(
             rect,
-            Rounding::same(ROUNDED_CORNERS_LEVEL),
-            Stroke::new(2.0, Colors::SURFACE0),
+            Rounding::same(rounding),
+            Stroke::new(2.0, Colors::surface0().gamma_multiply(opacity)),
         );
 
+        // Scale in from (and out to) the center of the window, reduced motion only fades.
+        if !self.settings.animation.reduce_motion {
+            let scale = 1.0 - TRANSITION_SCALE * (1.0 - opacity);
+            let transform = TSTransform::new(rect.center().to_vec2() * (1.0 - scale), scale);
+            ctx.transform_layer_shapes(LayerId::background(), transform);
+            ctx.transform_layer_shapes(border_layer, transform);
+        }
+
         if focused && !self.has_window_ever_received_focus {
             self.has_window_ever_received_focus = true;
             self.first_focused_at = Instant::now();
         }
 
-        if self.to_launch.lock().unwrap().is_some()
-            || should_close
+        if (self.to_launch.lock().unwrap().is_some() || should_close)
+            && self.closing_since.is_none()
         {
+            self.closing_since = Some(Instant::now());
+            ctx.request_repaint();
+
+            let mut to_launch = self.to_launch.lock().unwrap();
+            if let Some(request) = to_launch.take_if(|v| v.spawns()) {
+                let paths = self.paths.clone();
+                let handle = std::thread::spawn(move || core::run_launch(&paths, &request));
+                *self.launching.lock().unwrap() = Some(handle);
+            }
+        }
+
+        let launched = self
+            .launching
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_none_or(|v| v.is_finished());
+        if self.closing_since.is_some() && !launched {
+            ctx.request_repaint_after(icons::POLL_INTERVAL);
+        }
+
+        // Only close once the window has faded out, and what we launched was started.
+        if self.closing_since.is_some() && self.transition() <= 0.0 && launched {
             let ctx = ctx.clone();
             std::thread::spawn(move || {
                 ctx.send_viewport_cmd(egui::ViewportCommand::Close);
@@ -459,13 +1373,21 @@ impl eframe::App for Application {
         }
     }
 
-    fn on_exit(&mut self) {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if self.to_launch.lock().unwrap().is_none() && self.launching.lock().unwrap().is_none() {
+            if let Err(error) = self.search.record_dismissal() {
+                warn!("Could not record closing: {error:?}");
+            }
+        }
         self.app_icons.finish().unwrap();
     }
 
     fn clear_color(&self, _visuals: &Visuals) -> [f32; 4] {
-        Colors::CRUST
-            .linear_multiply(0.75)
+        if self.opaque {
+            return Colors::crust().to_normalized_gamma_f32();
+        }
+        Colors::crust()
+            .linear_multiply(0.75 * easing::cubic_out(self.transition()))
             .to_normalized_gamma_f32()
     }
 }
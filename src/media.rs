@@ -0,0 +1,202 @@
+//! Entries to play, pause and skip tracks of the media player that's playing, and one for
+//! the current track.
+
+use crate::apps::AppId;
+use crate::i18n::tr;
+use crate::search::{starts_words, EntryAction, SearchResultEntry, SearchScore};
+use crossbeam::channel::{bounded, Receiver};
+use std::thread::{sleep, spawn};
+use std::time::{Duration, Instant};
+use tracing::{debug, error};
+
+/// How long the players are reused before a query reads them again.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+/// How long players take to update their state after a command.
+const COMMAND_DELAY: Duration = Duration::from_millis(200);
+/// Queries about media players, which read them before any entry can be found.
+const KEYWORDS: &str = "media music player song track play pause resume next previous skip";
+
+#[derive(Clone, Debug)]
+pub struct Player {
+    /// Its name on the session bus.
+    pub bus_name: String,
+    pub name: String,
+    pub playing: bool,
+    pub stopped: bool,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub can_go_next: bool,
+    pub can_go_previous: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MediaCommand {
+    PlayPause,
+    Next,
+    Previous,
+    /// Brings the player's window to the front.
+    Raise,
+}
+
+impl MediaCommand {
+    /// In the order of the entries.
+    pub const ALL: [MediaCommand; 4] = [
+        MediaCommand::Raise,
+        MediaCommand::PlayPause,
+        MediaCommand::Previous,
+        MediaCommand::Next,
+    ];
+}
+
+/// The players, read on a background thread when a query is about them.
+#[derive(Default)]
+pub struct MediaPlayers {
+    players: Vec<Player>,
+    loaded_at: Option<Instant>,
+    pending: Option<Receiver<Vec<Player>>>,
+}
+
+impl MediaPlayers {
+    /// Returns `true` once the players arrived.
+    pub fn tick(&mut self) -> bool {
+        let Some(pending) = &self.pending else {
+            return false;
+        };
+        let Ok(players) = pending.try_recv() else {
+            return false;
+        };
+        self.players = players;
+        self.loaded_at = Some(Instant::now());
+        self.pending = None;
+        true
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// The entries of the playing player found by `query`, which also finds them by the
+    /// player's name and the track.
+    pub fn search(&mut self, query: &str) -> Vec<SearchResultEntry> {
+        let found: Vec<MediaCommand> = match self.active() {
+            Some(player) => MediaCommand::ALL
+                .into_iter()
+                .filter(|command| starts_words(&keywords(player, *command), query))
+                .collect(),
+            None => Vec::new(),
+        };
+        let fresh = self
+            .loaded_at
+            .is_some_and(|at| at.elapsed() < REFRESH_INTERVAL);
+        if !fresh && (!found.is_empty() || starts_words(KEYWORDS, query)) {
+            self.load(None);
+        }
+        let Some(player) = self.active() else {
+            return Vec::new();
+        };
+
+        found
+            .into_iter()
+            .filter(|command| match command {
+                MediaCommand::Next => player.can_go_next,
+                MediaCommand::Previous => player.can_go_previous,
+                _ => true,
+            })
+            .map(|command| SearchResultEntry {
+                id: AppId::from_properties(&["media", &format!("{command:?}")]),
+                score: SearchScore::default(),
+                detail: Some(player.name.clone()),
+                broken: false,
+                action: Some(EntryAction::Media(player.clone(), command)),
+                confirm: false,
+            })
+            .collect()
+    }
+
+    /// Sends `command` to `player` on a background thread, then reads the players again.
+    pub fn control(&mut self, player: &Player, command: MediaCommand) {
+        self.load(Some((player.bus_name.clone(), command)));
+    }
+
+    /// The player that's playing, or else the first one that isn't stopped.
+    fn active(&self) -> Option<&Player> {
+        self.players
+            .iter()
+            .find(|player| player.playing)
+            .or_else(|| self.players.iter().find(|player| !player.stopped))
+            .or_else(|| self.players.first())
+    }
+
+    fn load(&mut self, command: Option<(String, MediaCommand)>) {
+        if self.pending.is_some() && command.is_none() {
+            return;
+        }
+        let (sender, receiver) = bounded(1);
+        spawn(move || {
+            if let Some((bus_name, command)) = command {
+                if let Err(error) = control(&bus_name, command) {
+                    error!("Could not send {command:?} to {bus_name}: {error:?}");
+                }
+                sleep(COMMAND_DELAY);
+            }
+            let players = players().unwrap_or_else(|error| {
+                debug!("Could not read the media players: {error:?}");
+                Vec::new()
+            });
+            let _ = sender.send(players);
+        });
+        self.pending = Some(receiver);
+    }
+}
+
+pub fn label(player: &Player, command: MediaCommand) -> String {
+    match command {
+        MediaCommand::PlayPause if player.playing => tr!("media-pause"),
+        MediaCommand::PlayPause => tr!("media-play"),
+        MediaCommand::Next => tr!("media-next"),
+        MediaCommand::Previous => tr!("media-previous"),
+        MediaCommand::Raise => match (&player.title, &player.artist) {
+            (Some(title), Some(artist)) => format!("{title} – {artist}"),
+            (Some(title), None) => title.clone(),
+            _ => player.name.clone(),
+        },
+    }
+}
+
+/// What an entry is found by, its label, the player, the track and a few words in English.
+fn keywords(player: &Player, command: MediaCommand) -> String {
+    let words = match command {
+        MediaCommand::PlayPause => "play pause resume stop",
+        MediaCommand::Next => "next skip",
+        MediaCommand::Previous => "previous back",
+        MediaCommand::Raise => "song track now playing",
+    };
+    let track = [&player.title, &player.artist].map(|v| v.as_deref().unwrap_or_default());
+    format!(
+        "{} {words} media music {} {}",
+        label(player, command),
+        player.name,
+        track.join(" ")
+    )
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn players() -> eyre::Result<Vec<Player>> {
+    crate::platform::mpris::players()
+}
+
+/// Sends `command` to the player at `bus_name`.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn control(bus_name: &str, command: MediaCommand) -> eyre::Result<()> {
+    crate::platform::mpris::control(bus_name, command)
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn players() -> eyre::Result<Vec<Player>> {
+    Ok(Vec::new())
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+pub fn control(_bus_name: &str, _command: MediaCommand) -> eyre::Result<()> {
+    eyre::bail!("Media players can only be controlled on Linux")
+}
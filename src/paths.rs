@@ -0,0 +1,182 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+use tracing::{info, warn};
+
+/// Environment variable that overrides where the icon cache lives.
+pub const CACHE_DIR_ENV: &str = "IGNITION_CACHE_DIR";
+/// Environment variable that overrides where usage data lives.
+pub const DATA_DIR_ENV: &str = "IGNITION_DATA_DIR";
+/// Environment variable that overrides where `config.toml` is read from.
+pub const CONFIG_DIR_ENV: &str = "IGNITION_CONFIG_DIR";
+
+/// The directories ignition reads and writes its own files in.
+#[derive(Clone)]
+pub struct Paths {
+    pub cache_dir: PathBuf,
+    pub data_dir: PathBuf,
+    pub config_dir: PathBuf,
+    /// Holds the IPC socket, not every platform has one.
+    pub runtime_dir: Option<PathBuf>,
+}
+
+impl Paths {
+    pub fn settings_file(&self) -> PathBuf {
+        self.config_dir.join("config.toml")
+    }
+
+    /// Where the output of launched apps goes.
+    pub fn launch_log(&self) -> PathBuf {
+        self.cache_dir.join("launch.log")
+    }
+
+    /// Resolves the directories, this never fails.
+    ///
+    /// The lookup order is the `IGNITION_*_DIR` override, the platform directory,
+    /// the XDG default relative to `$HOME` and lastly a directory in the temp dir.
+    ///
+    /// A `profile` gets its own config, usage data and socket in `profiles/<name>`,
+    /// the icon cache is shared.
+    pub fn resolve(profile: Option<&str>) -> Paths {
+        let profile_dir = |dir: PathBuf| match profile {
+            Some(profile) => dir.join("profiles").join(profile),
+            None => dir,
+        };
+        let data_dir = resolve_dir(DATA_DIR_ENV, dirs::data_local_dir, ".local/share", "data");
+        let config_dir = resolve_dir(CONFIG_DIR_ENV, dirs::config_dir, ".config", "config");
+        Paths {
+            cache_dir: resolve_dir(CACHE_DIR_ENV, dirs::cache_dir, ".cache", "cache"),
+            data_dir: profile_dir(data_dir),
+            config_dir: profile_dir(config_dir),
+            runtime_dir: dirs::runtime_dir().map(|dir| profile_dir(dir.join("ignition"))),
+        }
+    }
+}
+
+/// Checks a `--profile` name, it becomes a directory name.
+pub fn parse_profile(name: &str) -> Result<String, String> {
+    let valid = name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if name.is_empty() || !valid {
+        return Err("only letters, digits, - and _ are allowed".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// Replaces a leading `~` with the home directory, for paths from the settings.
+pub fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// The path of a `file://` URI, with the %-escapes decoded.
+pub fn file_uri_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?.as_bytes();
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut i = 0;
+    while i < path.len() {
+        let escaped = path
+            .get(i + 1..i + 3)
+            .filter(|_| path[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                bytes.push(byte);
+                i += 3;
+            }
+            None => {
+                bytes.push(path[i]);
+                i += 1;
+            }
+        }
+    }
+    // Paths are bytes on unix, they don't have to be UTF-8.
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        Some(PathBuf::from(std::ffi::OsStr::from_bytes(&bytes)))
+    }
+    #[cfg(not(unix))]
+    {
+        Some(PathBuf::from(String::from_utf8_lossy(&bytes).to_string()))
+    }
+}
+
+fn resolve_dir(
+    override_var: &str,
+    platform_dir: fn() -> Option<PathBuf>,
+    xdg_default: &str,
+    kind: &str,
+) -> PathBuf {
+    if let Some(dir) = env::var_os(override_var).filter(|v| !v.is_empty()) {
+        let dir = PathBuf::from(dir);
+        info!("Using {kind} dir {dir:?} from {override_var}");
+        return dir;
+    }
+
+    if let Some(dir) = platform_dir() {
+        return dir.join("ignition");
+    }
+
+    if let Some(home) = dirs::home_dir() {
+        return home.join(xdg_default).join("ignition");
+    }
+
+    // Containers and some service setups have neither $HOME nor XDG dirs.
+    let user = env::var("USER").unwrap_or_else(|_| "unknown".to_string());
+    let dir = env::temp_dir().join(format!("ignition-{user}")).join(kind);
+    warn!("Could not find a {kind} dir, falling back to {dir:?}");
+    dir
+}
+
+/// Creates a directory (and its parents) that only the current user can access.
+///
+/// Usage history is sensitive, so everything we store lives in directories like this.
+/// Directories that already exist keep their permissions, they may be the user's own like
+/// an `IGNITION_DATA_DIR` or a synced folder.
+pub fn create_private_dir(path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(path)
+    }
+    #[cfg(not(unix))]
+    {
+        fs::create_dir_all(path)
+    }
+}
+
+/// Writes a file that only the current user can read.
+pub fn write_private(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let mut file = open_private(path)?;
+    file.write_all(contents.as_ref())?;
+    Ok(())
+}
+
+fn open_private(path: &Path) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+        options.mode(0o600);
+        let file = options.open(path)?;
+        // `mode` only applies to newly created files.
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        Ok(file)
+    }
+    #[cfg(not(unix))]
+    {
+        options.open(path)
+    }
+}
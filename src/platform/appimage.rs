@@ -0,0 +1,231 @@
+//! AppImages in the `[appimages]` dirs, listed with the name and icon of the desktop entry
+//! inside them.
+//!
+//! A type 2 AppImage is an ELF runtime with a squashfs image appended, `unsquashfs` reads
+//! the entry from where the ELF ends. What it found is kept in a [FileCache] until the file
+//! changes.
+
+use crate::apps::cache::{self, FileCache};
+use crate::apps::{exec, App, AppId, AppSource};
+use crate::paths::create_private_dir;
+use crate::platform::flatpak;
+use crate::platform::linux::parse_entry;
+use eyre::{eyre, Context, ContextCompat};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::Read;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tracing::{debug, warn};
+
+const PROVIDER: &str = "appimages";
+
+/// The executable AppImages directly in `dirs`.
+pub fn find_appimages(dirs: &[PathBuf]) -> Vec<App> {
+    let cache_dir = cache::provider_dir(PROVIDER)
+        .unwrap_or_else(|| std::env::temp_dir().join("ignition-appimages"));
+    let mut cache = FileCache::load(PROVIDER);
+    let mut apps = Vec::new();
+    for path in dirs.iter().flat_map(|dir| list_dir(dir)) {
+        match cache.get_or_read(&path, || read_appimage(&path, &cache_dir)) {
+            Ok(app) => apps.extend(app),
+            Err(error) => {
+                warn!("Could not read the AppImage {path:?}: {error:?}");
+                // Listed by its file name, and read again next time since nothing was cached.
+                apps.push(unread_app(&path));
+            }
+        }
+    }
+    cache.save();
+
+    let icons: HashSet<PathBuf> = apps
+        .iter()
+        .filter_map(|app| app.icon.as_ref().map(PathBuf::from))
+        .collect();
+    remove_other_icons(&cache_dir.join("icons"), &icons);
+    apps
+}
+
+fn list_dir(dir: &Path) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(dir) {
+        Ok(v) => v,
+        Err(error) => {
+            debug!("Could not list {dir:?}: {error}");
+            return Vec::new();
+        }
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let is_appimage = path
+                .extension()
+                .is_some_and(|v| v.eq_ignore_ascii_case("appimage"));
+            is_appimage && is_executable(path)
+        })
+        .collect()
+}
+
+fn is_executable(path: &Path) -> bool {
+    let executable =
+        fs::metadata(path).is_ok_and(|v| v.is_file() && v.permissions().mode() & 0o111 != 0);
+    if !executable {
+        debug!("Skipping {path:?}, it isn't executable");
+    }
+    executable
+}
+
+/// Reads the desktop entry inside the AppImage at `path`, its icon goes to `cache_dir`.
+///
+/// Only not being able to run `unsquashfs` is an error. AppImages it can't read are listed
+/// by their file name, which stays cached until the file changes.
+fn read_appimage(path: &Path, cache_dir: &Path) -> eyre::Result<Option<App>> {
+    let offset = match squashfs_offset(path) {
+        Ok(offset) => offset,
+        Err(error) => return Ok(unreadable(path, error)),
+    };
+    let extract_dir = cache_dir.join("extract");
+    let _ = fs::remove_dir_all(&extract_dir);
+    // Only what's at the top, where the entry and its icon are.
+    let status = flatpak::host_command("unsquashfs")
+        .arg("-offset")
+        .arg(offset.to_string())
+        .arg("-dest")
+        .arg(&extract_dir)
+        .arg(flatpak::to_host_path(path))
+        .args(["*.desktop", "*.png", "*.svg", ".DirIcon"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .wrap_err("Failed to run unsquashfs")?;
+    if !status.success() {
+        return Ok(unreadable(path, eyre!("unsquashfs exited with {status}")));
+    }
+
+    let app = entry_app(path, &extract_dir, &cache_dir.join("icons"));
+    let _ = fs::remove_dir_all(&extract_dir);
+    Ok(app.unwrap_or_else(|error| unreadable(path, error)))
+}
+
+fn unreadable(path: &Path, error: eyre::Report) -> Option<App> {
+    warn!("Could not read the AppImage {path:?}: {error:?}");
+    Some(unread_app(path))
+}
+
+/// Where the squashfs image starts, which is after the section headers of the ELF.
+fn squashfs_offset(path: &Path) -> eyre::Result<u64> {
+    let mut header = [0u8; 64];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .wrap_err("Failed to read the ELF header")?;
+    if &header[..4] != b"\x7fELF" {
+        eyre::bail!("Not an ELF file");
+    }
+    if &header[8..11] != b"AI\x02" {
+        eyre::bail!("Not a type 2 AppImage");
+    }
+    let little_endian = header[5] == 1;
+    let number = |at: usize, len: usize| {
+        let bytes = &header[at..at + len];
+        let push = |value: u64, byte: &u8| (value << 8) | u64::from(*byte);
+        if little_endian {
+            bytes.iter().rev().fold(0, push)
+        } else {
+            bytes.iter().fold(0, push)
+        }
+    };
+    let (section_headers, header_size, headers) = match header[4] {
+        1 => (number(0x20, 4), number(0x2e, 2), number(0x30, 2)),
+        2 => (number(0x28, 8), number(0x3a, 2), number(0x3c, 2)),
+        class => eyre::bail!("Unknown ELF class {class}"),
+    };
+    Ok(section_headers + header_size * headers)
+}
+
+fn entry_app(path: &Path, extract_dir: &Path, icons_dir: &Path) -> eyre::Result<Option<App>> {
+    let entry = fs::read_dir(extract_dir)
+        .wrap_err("Nothing was extracted")?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| path.extension().is_some_and(|v| v == "desktop"))
+        .wrap_err("No desktop entry inside")?;
+    let Some(mut app) = parse_entry(&entry)? else {
+        return Ok(None);
+    };
+    if app.source != AppSource::DesktopEntry {
+        return Ok(None);
+    }
+    // The Exec line names the program inside, like `AppRun %U`.
+    app.exec = exec::with_program(&app.exec, &path.to_string_lossy());
+    app.icon = app.icon.as_deref().and_then(|icon| {
+        let name = path.file_stem()?.to_string_lossy();
+        copy_icon(extract_dir, icon, &icons_dir.join(name.as_ref()))
+    });
+    app.path = path.to_path_buf();
+    app.dbus_activatable = false;
+    app.source = AppSource::AppImage;
+    Ok(Some(app))
+}
+
+/// Copies the icon named `icon`, or the `.DirIcon`, to `target` with its extension.
+fn copy_icon(extract_dir: &Path, icon: &str, target: &Path) -> Option<String> {
+    let source = [
+        format!("{icon}.png"),
+        format!("{icon}.svg"),
+        ".DirIcon".to_string(),
+    ]
+    .into_iter()
+    .map(|name| extract_dir.join(name))
+    .find(|path| path.is_file())?;
+    let bytes = fs::read(&source).ok()?;
+    // The `.DirIcon` has no extension.
+    let extension = if bytes.starts_with(b"\x89PNG") {
+        "png"
+    } else {
+        "svg"
+    };
+    let target = target.with_extension(extension);
+    let copied = target
+        .parent()
+        .map_or(Ok(()), create_private_dir)
+        .and_then(|_| fs::write(&target, bytes));
+    if let Err(error) = copied {
+        warn!("Could not keep the icon of {source:?}: {error}");
+        return None;
+    }
+    Some(target.to_string_lossy().to_string())
+}
+
+/// Removes the icons of AppImages that are gone.
+fn remove_other_icons(icons_dir: &Path, keep: &HashSet<PathBuf>) {
+    let Ok(entries) = fs::read_dir(icons_dir) else {
+        return;
+    };
+    for path in entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        if !keep.contains(&path) {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
+
+/// An AppImage whose entry couldn't be read, named like its file.
+fn unread_app(path: &Path) -> App {
+    let name = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let exec = exec::with_program("", &path.to_string_lossy());
+    App {
+        id: AppId::from_properties(&[&name, &exec]),
+        path: path.to_path_buf(),
+        name,
+        exec,
+        source: AppSource::AppImage,
+        ..App::default()
+    }
+}
@@ -0,0 +1,157 @@
+//! The programs started at login, desktop entries in the `autostart` dirs of the XDG config
+//! dirs. Like applications, an entry of the user hides the system's with the same file name,
+//! so turning a system entry off copies it with `Hidden=true`.
+
+use crate::platform::{flatpak, AutostartEntry};
+use eyre::{Context, ContextCompat};
+use ini::Ini;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+use tracing::debug;
+
+/// The entries of all autostart dirs, sorted by name.
+pub fn autostart_entries() -> eyre::Result<Vec<AutostartEntry>> {
+    let mut entries = Vec::new();
+    let mut seen = HashSet::new();
+    for dir in autostart_dirs().wrap_err("Failed to find the autostart dirs")? {
+        let Ok(files) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for path in files
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+        {
+            let Some(id) = desktop_file_id(&path) else {
+                continue;
+            };
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+            match read_entry(&path, id) {
+                Ok(entry) => entries.push(entry),
+                Err(error) => debug!("Could not read the autostart entry {path:?}: {error:?}"),
+            }
+        }
+    }
+    entries.sort_by_key(|entry| entry.name.to_lowercase());
+    Ok(entries)
+}
+
+/// Turns the entry `id` on or off, system entries get a copy in the user's dir first.
+pub fn set_autostart(id: &str, enabled: bool) -> eyre::Result<()> {
+    let dirs = autostart_dirs().wrap_err("Failed to find the autostart dirs")?;
+    let (user_dir, _) = dirs.split_first().wrap_err("No autostart dir")?;
+    let target = user_dir.join(id);
+    let source = dirs
+        .iter()
+        .map(|dir| dir.join(id))
+        .find(|path| path.is_file())
+        .wrap_err("No autostart entry with that name")?;
+    let mut content = fs::read_to_string(&source).wrap_err("Failed to read autostart entry")?;
+    content = set_key(&content, "Hidden", if enabled { "false" } else { "true" });
+    // GNOME's own switch, it would keep the entry off.
+    if enabled && content.contains("X-GNOME-Autostart-enabled=") {
+        content = set_key(&content, "X-GNOME-Autostart-enabled", "true");
+    }
+    fs::create_dir_all(user_dir).wrap_err("Failed to create autostart dir")?;
+    fs::write(&target, content).wrap_err("Failed to write autostart entry")?;
+    Ok(())
+}
+
+/// Copies the desktop entry at `path` to the user's autostart dir, turned on.
+pub fn add_to_autostart(path: &Path) -> eyre::Result<PathBuf> {
+    let id = desktop_file_id(path).wrap_err("Not a desktop entry")?;
+    let dirs = autostart_dirs().wrap_err("Failed to find the autostart dirs")?;
+    let user_dir = dirs.first().wrap_err("No autostart dir")?;
+    let content = fs::read_to_string(path).wrap_err("Failed to read desktop entry")?;
+    let content = set_key(&content, "Hidden", "false");
+    fs::create_dir_all(user_dir).wrap_err("Failed to create autostart dir")?;
+    let target = user_dir.join(id);
+    fs::write(&target, content).wrap_err("Failed to write autostart entry")?;
+    Ok(target)
+}
+
+fn read_entry(path: &Path, id: String) -> eyre::Result<AutostartEntry> {
+    let info = Ini::load_from_file_opt(
+        path,
+        ini::ParseOption {
+            enabled_quote: false,
+            enabled_escape: false,
+        },
+    )
+    .wrap_err("failed to parse ini")?;
+    let properties = info
+        .section(Some("Desktop Entry"))
+        .wrap_err("No [Desktop Entry] section")?;
+    let hidden = properties.get("Hidden") == Some("true");
+    let gnome_disabled = properties.get("X-GNOME-Autostart-enabled") == Some("false");
+    let name = properties
+        .get("Name")
+        .map(str::to_string)
+        .unwrap_or_else(|| id.trim_end_matches(".desktop").to_string());
+    Ok(AutostartEntry {
+        id,
+        name,
+        enabled: !hidden && !gnome_disabled,
+    })
+}
+
+/// The file name of a `.desktop` file, which user entries share with those they override.
+fn desktop_file_id(path: &Path) -> Option<String> {
+    if path.extension()? != "desktop" {
+        return None;
+    }
+    Some(path.file_name()?.to_string_lossy().to_string())
+}
+
+/// `content` with `key` of the `[Desktop Entry]` group set to `value`, added when missing.
+fn set_key(content: &str, key: &str, value: &str) -> String {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let group = lines
+        .iter()
+        .position(|line| line.trim() == "[Desktop Entry]");
+    let Some(group) = group else {
+        return content.to_string();
+    };
+    let end = lines[group + 1..]
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .map_or(lines.len(), |i| group + 1 + i);
+    let existing = lines[group + 1..end].iter().position(|line| {
+        line.split_once('=')
+            .is_some_and(|(name, _)| name.trim() == key)
+    });
+    let line = format!("{key}={value}");
+    match existing {
+        Some(i) => lines[group + 1 + i] = line,
+        None => lines.insert(group + 1, line),
+    }
+    lines.join("\n") + "\n"
+}
+
+/// The XDG config dirs with `autostart` appended, the user's first. Inside a sandbox the
+/// host's.
+fn autostart_dirs() -> eyre::Result<Vec<PathBuf>> {
+    let home = dirs::home_dir().wrap_err("Couldn't get home dir")?;
+    if flatpak::is_sandboxed() {
+        // $XDG_CONFIG_HOME points into ~/.var/app, like $XDG_DATA_HOME.
+        return Ok(vec![
+            home.join(".config/autostart"),
+            PathBuf::from("/run/host/etc/xdg/autostart"),
+        ]);
+    }
+
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".config"));
+    let config_dirs = match env::var_os("XDG_CONFIG_DIRS") {
+        Some(val) => env::split_paths(&val).collect(),
+        None => vec![PathBuf::from("/etc/xdg")],
+    };
+    Ok([config_home]
+        .into_iter()
+        .chain(config_dirs)
+        .map(|dir| dir.join("autostart"))
+        .collect())
+}
@@ -0,0 +1,102 @@
+//! Support for running ignition itself inside a Flatpak sandbox, and for the apps
+//! Flatpak installed.
+//!
+//! Inside the sandbox `XDG_DATA_DIRS` only points at the runtime, so we look at the
+//! host directories instead and launch everything on the host through `flatpak-spawn`.
+
+use crate::platform::sanitize_env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// The host's `/usr` is mounted here when the app has `--filesystem=host-os`.
+const HOST_ROOT: &str = "/run/host";
+
+pub fn is_sandboxed() -> bool {
+    static SANDBOXED: OnceLock<bool> = OnceLock::new();
+    *SANDBOXED.get_or_init(|| Path::new("/.flatpak-info").exists())
+}
+
+/// The host data dirs visible from inside the sandbox, empty when we are not sandboxed.
+pub fn host_data_dirs() -> Vec<PathBuf> {
+    if !is_sandboxed() {
+        return Vec::new();
+    }
+
+    let mut dirs = vec![
+        PathBuf::from(HOST_ROOT).join("usr/local/share"),
+        PathBuf::from(HOST_ROOT).join("usr/share"),
+    ];
+    // $HOME is the real home, but $XDG_DATA_HOME points into ~/.var/app.
+    if let Some(home) = dirs::home_dir() {
+        dirs.insert(0, home.join(".local/share"));
+    }
+    dirs.extend(export_dirs());
+    dirs
+}
+
+/// The data dirs Flatpak exports the desktop entries and icons of its apps to, the user's
+/// installation first. `XDG_DATA_DIRS` only has them after a new login.
+pub fn export_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        dirs.push(home.join(".local/share/flatpak/exports/share"));
+    }
+    dirs.push(PathBuf::from("/var/lib/flatpak/exports/share"));
+    dirs
+}
+
+/// The Exec line that runs the Flatpak `app_id` with files, like Flatpak exports it.
+pub fn run_exec(app_id: &str) -> String {
+    format!("flatpak run --file-forwarding {app_id} @@u %U @@")
+}
+
+/// Creates a command that runs `program` on the host when we are sandboxed.
+///
+/// `flatpak-spawn` starts it with the host's environment, otherwise ours is sanitized.
+pub fn host_command<S: AsRef<OsStr>>(program: S) -> Command {
+    host_command_with_env(program, None, &[])
+}
+
+/// Like [host_command], but starts `program` in `dir` when there is one. `flatpak-spawn`
+/// doesn't forward our environment so it gets `env` passed.
+pub fn host_command_with_env<S: AsRef<OsStr>>(
+    program: S,
+    dir: Option<&Path>,
+    env: &[(String, String)],
+) -> Command {
+    if is_sandboxed() {
+        let mut command = Command::new("flatpak-spawn");
+        command.arg("--host");
+        if let Some(dir) = dir {
+            command.arg(format!("--directory={}", to_host_path(dir).display()));
+        }
+        for (key, value) in env {
+            command.arg(format!("--env={key}={value}"));
+        }
+        command.arg(program);
+        command
+    } else {
+        let mut command = Command::new(program);
+        sanitize_env(&mut command);
+        if let Some(dir) = dir {
+            command.current_dir(dir);
+        }
+        command.envs(env.iter().map(|(key, value)| (key, value)));
+        command
+    }
+}
+
+/// Like [host_command], but starts `program` in `dir`.
+pub fn host_command_in<S: AsRef<OsStr>>(program: S, dir: &Path) -> Command {
+    host_command_with_env(program, Some(dir), &[])
+}
+
+/// Translates a path we see in the sandbox to the path the host sees.
+pub fn to_host_path(path: &Path) -> PathBuf {
+    match path.strip_prefix(HOST_ROOT) {
+        Ok(path) => Path::new("/").join(path),
+        Err(_) => path.to_path_buf(),
+    }
+}
@@ -0,0 +1,695 @@
+use crate::apps::cache::FileCache;
+use crate::apps::exec::{self, ExecContext};
+use crate::apps::App;
+use crate::platform::{
+    flatpak, is_launcher_var, parse_uri_list, pipe_to, portal, spawn_detached, x11, AppLaunch,
+    Capture, Launcher, Monitor,
+};
+use eframe::egui::{Pos2, Vec2};
+use eyre::{Context, ContextCompat};
+use fontconfig::Fontconfig;
+use ini::Ini;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use tracing::{debug, info, warn};
+use std::{env, fs, io};
+
+pub use crate::platform::x11::Placeholder;
+
+/// Finds all applications through the desktop entries in the XDG data dirs and `extra_dirs`.
+///
+/// Like the XDG spec says, an entry hides those with the same desktop file id in later dirs,
+/// even when it is hidden itself.
+pub fn find_applications(extra_dirs: &[PathBuf]) -> eyre::Result<Vec<App>> {
+    let mut applications = Vec::new();
+    let mut seen = HashSet::new();
+    // Parsing every entry is most of the startup time, only changed ones are parsed again.
+    let mut cache = FileCache::load("desktop_entries");
+
+    let mut app_dirs = find_application_dirs()?;
+    // After the user's own entries, before the system's.
+    let user_dirs = app_dirs.len().min(1);
+    app_dirs.splice(user_dirs..user_dirs, extra_dirs.iter().cloned());
+    for app_dir in app_dirs {
+        let files = match get_dir_desktop_files(&app_dir) {
+            Ok(v) => v,
+            Err(e) => {
+                println!("Could not list {}: {}", &app_dir.to_string_lossy(), e);
+                continue;
+            }
+        };
+        for (id, path) in files {
+            if !seen.insert(id) {
+                continue;
+            }
+            match cache.get_or_read(&path, || parse_entry(&path)) {
+                Ok(Some(app)) => applications.push(app),
+                Ok(None) => {}
+                Err(error) => warn!("Could not read desktop entry {path:?}: {error:?}"),
+            }
+        }
+    }
+    cache.save();
+
+    Ok(applications)
+}
+
+/// The dirs [find_applications] reads, for watching them.
+pub fn watch_dirs(extra_dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut dirs = find_application_dirs().unwrap_or_default();
+    dirs.extend(extra_dirs.iter().cloned());
+    dirs
+}
+
+/// Desktop entries and AppImages, changes to other files don't change the apps.
+pub const APP_EXTENSIONS: &[&str] = &["desktop", "appimage"];
+
+pub(super) fn parse_entry(path: &Path) -> eyre::Result<Option<App>> {
+    let info = Ini::load_from_file_opt(
+        path,
+        ini::ParseOption {
+            enabled_quote: false,
+            enabled_escape: false,
+        },
+    )
+    .wrap_err("failed to parse ini")?;
+    let properties = info
+        .section(Some("Desktop Entry"))
+        .wrap_err("No [Desktop Entry] section")?;
+    App::parse(path.to_path_buf(), properties)
+}
+
+/// The X11 monitors, Wayland doesn't let clients place their windows.
+pub fn monitors() -> Vec<Monitor> {
+    if is_wayland() {
+        return Vec::new();
+    }
+    x11::monitors().unwrap_or_else(|error| {
+        warn!("Could not list monitors: {error:?}");
+        Vec::new()
+    })
+}
+
+pub fn cursor_position() -> Option<(i32, i32)> {
+    if is_wayland() {
+        return None;
+    }
+    x11::cursor_position().ok()
+}
+
+/// See [x11::show_placeholder], Wayland clients can't place a window of their own.
+pub fn open_placeholder(
+    position: Pos2,
+    size: Vec2,
+    border_width: u32,
+    background: u32,
+    border: u32,
+) -> eyre::Result<Option<Placeholder>> {
+    if is_wayland() {
+        return Ok(None);
+    }
+    let (x, y) = (position.x.round() as i32, position.y.round() as i32);
+    let (width, height) = (size.x.round() as u32, size.y.round() as u32);
+    x11::show_placeholder(x, y, width, height, border_width, background, border).map(Some)
+}
+
+/// Wayland compositors always blend windows, on X11 that needs a compositing manager.
+pub fn supports_transparency() -> bool {
+    if is_wayland() {
+        return true;
+    }
+    x11::is_compositing().unwrap_or_else(|error| {
+        warn!("Could not check for a compositor: {error:?}");
+        true
+    })
+}
+
+fn is_wayland() -> bool {
+    env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+/// The files or URLs on the clipboard, read with `wl-paste` or `xclip`.
+pub fn clipboard_uris() -> eyre::Result<Vec<String>> {
+    let wayland = is_wayland();
+    let program = if wayland { "wl-paste" } else { "xclip" };
+    let run = |args: &[&str]| -> eyre::Result<String> {
+        let output = flatpak::host_command(program)
+            .args(args)
+            .stderr(Stdio::null())
+            .output()
+            .wrap_err_with(|| format!("Failed to run {program}"))?;
+        if !output.status.success() {
+            eyre::bail!("{program} exited with {}", output.status);
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    };
+    let paste = |data_type: &str| {
+        if wayland {
+            run(&["--no-newline", "--type", data_type])
+        } else {
+            run(&["-selection", "clipboard", "-o", "-t", data_type])
+        }
+    };
+
+    // Copied files come as a uri-list, anything else has to be text.
+    let types = if wayland {
+        run(&["--list-types"])?
+    } else {
+        paste("TARGETS")?
+    };
+    let types: Vec<&str> = types.lines().collect();
+    let text = if types.contains(&"text/uri-list") {
+        paste("text/uri-list")?
+    } else if types
+        .iter()
+        .any(|v| v.starts_with("text/plain") || *v == "UTF8_STRING")
+    {
+        paste(if wayland { "text" } else { "UTF8_STRING" })?
+    } else {
+        eyre::bail!("The clipboard holds no text or files: {types:?}");
+    };
+    parse_uri_list(&text)
+}
+
+/// Puts `text` on the clipboard with `wl-copy` or `xclip`, empty text clears it.
+///
+/// Both keep serving the clipboard in the background after we are gone.
+pub fn set_clipboard_text(text: &str) -> eyre::Result<()> {
+    let mut command = if !is_wayland() {
+        let mut command = flatpak::host_command("xclip");
+        command.args(["-selection", "clipboard", "-in"]);
+        command
+    } else if text.is_empty() {
+        let mut command = flatpak::host_command("wl-copy");
+        command.arg("--clear");
+        command
+    } else {
+        flatpak::host_command("wl-copy")
+    };
+    pipe_to(&mut command, text)
+}
+
+/// The text on the clipboard, read with `wl-paste` or `xclip`.
+pub fn clipboard_text() -> eyre::Result<String> {
+    let mut command = if is_wayland() {
+        let mut command = flatpak::host_command("wl-paste");
+        command.arg("--no-newline");
+        command
+    } else {
+        let mut command = flatpak::host_command("xclip");
+        command.args(["-selection", "clipboard", "-o"]);
+        command
+    };
+    let output = command
+        .stderr(Stdio::null())
+        .output()
+        .wrap_err("Failed to read the clipboard")?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Takes a screenshot or picks a color and copies it, with `grim` and `slurp` when they are
+/// installed (wlroots compositors) and through the Screenshot portal otherwise.
+pub fn capture(kind: Capture) -> eyre::Result<()> {
+    let grim =
+        is_wayland() && !flatpak::is_sandboxed() && has_program("grim") && has_program("slurp");
+    match (kind, grim) {
+        (Capture::Color, true) => {
+            let geometry = slurp(&["-p"])?;
+            // At scale 1 the picked point is a single pixel, which comes last.
+            let ppm = run_output(
+                Command::new("grim").args(["-g", &geometry, "-s", "1", "-t", "ppm", "-"]),
+            )?;
+            let &[red, green, blue] = &ppm[ppm.len().saturating_sub(3)..] else {
+                eyre::bail!("grim returned no pixel");
+            };
+            set_clipboard_text(&format!("#{red:02x}{green:02x}{blue:02x}"))
+        }
+        (Capture::Color, false) => {
+            let (red, green, blue) = portal::pick_color()?;
+            let channel = |v: f64| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+            let [red, green, blue] = [red, green, blue].map(channel);
+            set_clipboard_text(&format!("#{red:02x}{green:02x}{blue:02x}"))
+        }
+        (Capture::Region, true) => {
+            let geometry = slurp(&[])?;
+            let png = run_output(Command::new("grim").args(["-g", &geometry, "-"]))?;
+            set_clipboard_png(&png)
+        }
+        (Capture::Screen, true) => {
+            let png = run_output(Command::new("grim").arg("-"))?;
+            set_clipboard_png(&png)
+        }
+        (_, false) => {
+            let path = portal::screenshot(kind == Capture::Region)?;
+            let png = fs::read(&path).wrap_err("Failed to read the screenshot")?;
+            set_clipboard_png(&png)
+        }
+    }
+}
+
+fn set_clipboard_png(png: &[u8]) -> eyre::Result<()> {
+    let mut command = if is_wayland() {
+        let mut command = flatpak::host_command("wl-copy");
+        command.args(["--type", "image/png"]);
+        command
+    } else {
+        let mut command = flatpak::host_command("xclip");
+        command.args(["-selection", "clipboard", "-t", "image/png", "-in"]);
+        command
+    };
+    pipe_to(&mut command, png)
+}
+
+/// Lets the user pick a region, or a point with `-p`, as a geometry for `grim -g`.
+fn slurp(args: &[&str]) -> eyre::Result<String> {
+    let output = run_output(Command::new("slurp").args(args))?;
+    Ok(String::from_utf8_lossy(&output).trim().to_string())
+}
+
+/// Terminal emulators, the first one installed is used when neither `[terminal] command` nor
+/// `$TERMINAL` is set. `xdg-terminal-exec` runs the one the user picked.
+const TERMINALS: &[&str] = &[
+    "xdg-terminal-exec",
+    "x-terminal-emulator",
+    "kitty",
+    "alacritty",
+    "foot",
+    "wezterm",
+    "konsole",
+    "ptyxis",
+    "gnome-terminal",
+    "kgx",
+    "xterm",
+];
+
+/// The words of `terminal` and the flag that comes before the program it should run. Most
+/// terminals take `-e`, the GNOME ones `--` and `xdg-terminal-exec` nothing.
+fn terminal_args(terminal: &str) -> (Vec<String>, Option<&'static str>) {
+    let args: Vec<String> = terminal.split_whitespace().map(str::to_string).collect();
+    let program = args
+        .first()
+        .and_then(|v| Path::new(v).file_name())
+        .and_then(|v| v.to_str())
+        .unwrap_or_default();
+    let flag = match program {
+        "xdg-terminal-exec" => None,
+        "ptyxis" | "gnome-terminal" | "kgx" => Some("--"),
+        _ => Some("-e"),
+    };
+    (args, flag)
+}
+
+pub fn default_terminal() -> Option<String> {
+    static TERMINAL: OnceLock<Option<String>> = OnceLock::new();
+    TERMINAL
+        .get_or_init(|| {
+            let terminal = TERMINALS.iter().find(|v| has_program(v))?;
+            debug!("Found the terminal {terminal}");
+            Some(terminal.to_string())
+        })
+        .clone()
+}
+
+/// The icon theme of the desktop, from `kdeglobals` on Plasma and from the settings portal
+/// elsewhere.
+pub fn icon_theme() -> Option<String> {
+    let desktop = env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+    let theme = if desktop.split(':').any(|v| v.eq_ignore_ascii_case("KDE")) {
+        kde_icon_theme().or_else(gtk_icon_theme)
+    } else {
+        gtk_icon_theme().or_else(kde_icon_theme)
+    };
+    debug!("The icon theme of the desktop is {theme:?}");
+    theme
+}
+
+fn gtk_icon_theme() -> Option<String> {
+    let theme = portal::icon_theme()
+        .inspect_err(|error| debug!("Could not read the icon theme from the portal: {error:?}"))
+        .ok()?;
+    Some(theme).filter(|v| !v.is_empty())
+}
+
+/// Plasma keeps it in `[Icons] Theme` of `kdeglobals`.
+fn kde_icon_theme() -> Option<String> {
+    // Inside a sandbox $XDG_CONFIG_HOME points into ~/.var/app.
+    let config_home = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !flatpak::is_sandboxed() => PathBuf::from(dir),
+        _ => dirs::home_dir()?.join(".config"),
+    };
+    let kdeglobals = Ini::load_from_file(config_home.join("kdeglobals")).ok()?;
+    let theme = kdeglobals.get_from(Some("Icons"), "Theme")?;
+    Some(theme.to_string())
+}
+
+fn is_shell(program: &str) -> bool {
+    let name = Path::new(program).file_name().and_then(|v| v.to_str());
+    matches!(name, Some("sh" | "bash" | "dash" | "zsh" | "fish"))
+}
+
+fn has_program(name: &str) -> bool {
+    env::var_os("PATH")
+        .is_some_and(|path| env::split_paths(&path).any(|dir| dir.join(name).is_file()))
+}
+
+/// The stdout of `command`, failing when it exits with an error like a cancelled `slurp`.
+fn run_output(command: &mut Command) -> eyre::Result<Vec<u8>> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let output = command
+        .stderr(Stdio::null())
+        .output()
+        .wrap_err_with(|| format!("Failed to run {program}"))?;
+    if !output.status.success() {
+        eyre::bail!("{program} exited with {}", output.status);
+    }
+    Ok(output.stdout)
+}
+
+/// Launches desktop entries over D-Bus activation, in a terminal, through gio or by running
+/// their Exec line ourselves.
+pub struct PlatformLauncher;
+
+impl Launcher for PlatformLauncher {
+    fn launch(&self, request: &AppLaunch) -> eyre::Result<Option<u32>> {
+        // Wayland doesn't let us activate other windows.
+        if let Some(pid) = request.focus_pid.filter(|_| !is_wayland()) {
+            match x11::focus_pid(pid) {
+                Ok(true) => return Ok(Some(pid)),
+                Ok(false) => info!("{} has no window to focus, launching it", request.name),
+                Err(error) => warn!("Could not focus {}: {error:?}", request.name),
+            }
+        }
+
+        let mut env = Vec::new();
+        if request.login_env {
+            match login_env() {
+                Ok(login_env) => env = login_env,
+                Err(error) => warn!("Could not read the login shell environment: {error:?}"),
+            }
+        }
+        env.extend(request.env.iter().cloned());
+
+        // Activate can't pass files, environment or wrappers, or open a terminal.
+        let plain = request.args.is_empty()
+            && request.wrapper.is_empty()
+            && env.is_empty()
+            && !request.terminal;
+        if request.dbus_activatable && plain {
+            match Self::activate(&request.path) {
+                Ok(()) => return Ok(None),
+                Err(error) => warn!("D-Bus activation failed, falling back to Exec: {error:?}"),
+            }
+        }
+
+        // What the Exec line is run through, outermost first. Without a terminal we know of,
+        // gio may still know one.
+        let mut outer = Vec::new();
+        let terminal = request
+            .terminal_command
+            .as_deref()
+            .filter(|_| request.terminal);
+        if let Some(terminal) = terminal {
+            let (args, flag) = terminal_args(terminal);
+            outer.extend(args);
+            outer.extend(flag.map(str::to_string));
+        }
+        outer.extend(request.wrapper.iter().cloned());
+        // gio also knows the working directory of the desktop entry, but can't wrap the Exec
+        // line. Without it we run the Exec line ourselves.
+        let gio = flatpak::is_sandboxed() || has_program("gio");
+        let is_entry = request.path.extension().is_some_and(|v| v == "desktop");
+        if gio && is_entry && outer.is_empty() {
+            let path = request
+                .path
+                .canonicalize()
+                .wrap_err("Failed to resolve desktop entry")?;
+            // The pid would be gio's.
+            return spawn_detached(
+                flatpak::host_command_with_env("gio", None, &env)
+                    .arg("launch")
+                    .arg(flatpak::to_host_path(&path))
+                    .args(&request.args),
+            )
+            .map(|_| None);
+        }
+
+        if request.terminal && terminal.is_none() {
+            eyre::bail!("No terminal found, set [terminal] command or $TERMINAL");
+        }
+
+        // An override of a Flatpak's entry may have lost its `flatpak run`.
+        let exec = match &request.flatpak {
+            Some(app_id) if !request.exec.contains("flatpak") => flatpak::run_exec(app_id),
+            _ => request.exec.clone(),
+        };
+        let args = exec::split(&exec).wrap_err("Invalid Exec line")?;
+        let context = ExecContext {
+            name: &request.name,
+            icon: request.icon.as_deref(),
+            path: &request.path,
+        };
+        let mut pid = None;
+        for command in exec::commands(&args, &request.args, &context) {
+            let command = [outer.clone(), command].concat();
+            let (program, args) = command.split_first().wrap_err("The Exec line is empty")?;
+            let dir = request.working_dir.as_deref();
+            let spawned =
+                spawn_detached(flatpak::host_command_with_env(program, dir, &env).args(args))?;
+            // The first one is the app, the others open more files.
+            pid = pid.or(spawned.map(|pid| (pid, program.clone())));
+        }
+        // Through a terminal, a wrapper, a shell or a sandbox we'd get the pid of those.
+        let own = outer.is_empty() && request.flatpak.is_none() && !flatpak::is_sandboxed();
+        Ok(pid
+            .filter(|(_, program)| own && !is_shell(program))
+            .map(|(pid, _)| pid))
+    }
+
+    fn open_uri(&self, uri: &str) -> eyre::Result<()> {
+        // The portal works from sandboxes and tells us when something went wrong.
+        match portal::open_uri(uri) {
+            Ok(()) => return Ok(()),
+            Err(error) => warn!("OpenURI portal failed, falling back to xdg-open: {error:?}"),
+        }
+
+        let status = flatpak::host_command("xdg-open")
+            .arg(uri)
+            .status()
+            .wrap_err("Failed to run xdg-open")?;
+        if !status.success() {
+            eyre::bail!("xdg-open exited with {status}");
+        }
+        Ok(())
+    }
+
+    fn open_terminal(&self, command: Option<&str>, dir: &Path, run: &[String]) -> eyre::Result<()> {
+        let terminal =
+            command.wrap_err("No terminal configured, set [terminal] command or $TERMINAL")?;
+        let (args, flag) = terminal_args(terminal);
+        let (program, args) = args.split_first().wrap_err("The terminal command is empty")?;
+        let mut command = flatpak::host_command_in(program, dir);
+        command.args(args);
+        if !run.is_empty() {
+            command.args(flag).args(run);
+        }
+        spawn_detached(&mut command).map(|_| ())
+    }
+
+    fn run_command(&self, command: &[String], dir: &Path) -> eyre::Result<()> {
+        let (program, args) = command.split_first().wrap_err("The command is empty")?;
+        spawn_detached(flatpak::host_command_in(program, dir).args(args)).map(|_| ())
+    }
+}
+
+impl PlatformLauncher {
+    /// Calls `org.freedesktop.Application.Activate` on the app named by the desktop file id.
+    fn activate(path: &Path) -> eyre::Result<()> {
+        let id = path
+            .file_stem()
+            .and_then(|v| v.to_str())
+            .wrap_err("Desktop entry has no file name")?;
+        let object_path = format!("/{}", id.replace('.', "/").replace('-', "_"));
+
+        let status = flatpak::host_command("gdbus")
+            .args(["call", "--session", "--dest", id, "--object-path"])
+            .arg(&object_path)
+            .args(["--method", "org.freedesktop.Application.Activate", "{}"])
+            .stdout(Stdio::null())
+            .status()
+            .wrap_err("Failed to run gdbus")?;
+        if !status.success() {
+            eyre::bail!("gdbus exited with {status}");
+        }
+        Ok(())
+    }
+}
+
+/// The font file and face index fontconfig picks for `family`, which can also be an alias
+/// like `sans-serif`.
+pub fn find_font(family: &str, bold: bool) -> Option<(PathBuf, u32)> {
+    let fontconfig = Fontconfig::new()?;
+    let style = if bold { "Bold" } else { "Regular" };
+    let font = fontconfig.find(family, Some(style))?;
+    debug!("Using {} for {family} {style}", font.name);
+    let index = font.index.unwrap_or(0).max(0) as u32;
+    Some((font.path, index))
+}
+
+/// The `WM_CLASS` names of all open windows, empty on Wayland.
+pub fn window_classes() -> HashSet<String> {
+    if is_wayland() {
+        return HashSet::new();
+    }
+    x11::window_classes().unwrap_or_else(|error| {
+        warn!("Could not list windows: {error:?}");
+        HashSet::new()
+    })
+}
+
+/// When the process `pid` started, in clock ticks since boot. A later process that gets the
+/// same pid has another start time.
+pub fn process_start(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The name in parentheses can contain spaces, the fields after it can't. The start time
+    // is the 22nd field, the 20th after the name.
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(19)?.parse().ok()
+}
+
+/// Shows a desktop notification, `icon` is an icon name or path.
+pub fn notify(summary: &str, body: &str, icon: Option<&str>) {
+    let status = flatpak::host_command("notify-send")
+        .arg("--app-name=ignition")
+        .arg(format!("--icon={}", icon.unwrap_or("dialog-information")))
+        .args([summary, body])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => warn!("notify-send exited with {status}"),
+        Err(error) => warn!("Could not run notify-send: {error}"),
+    }
+}
+
+/// The environment a login shell sets up, without what is specific to the shell or to us.
+fn login_env() -> eyre::Result<Vec<(String, String)>> {
+    let shell = env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+    let output = flatpak::host_command(&shell)
+        .args(["-l", "-c", "env -0"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .wrap_err_with(|| format!("Failed to run {shell}"))?;
+    if !output.status.success() {
+        eyre::bail!("{shell} exited with {}", output.status);
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter_map(|v| v.split_once('='))
+        .filter(|(key, _)| !is_launcher_var(key) && !["PWD", "OLDPWD", "SHLVL", "_"].contains(key))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect())
+}
+
+/// Where a desktop entry has to be copied to override it, `None` when it already is the user's.
+pub fn user_entry_path(path: &Path) -> Option<PathBuf> {
+    let dirs = find_application_dirs().ok()?;
+    // The user's dir always comes first.
+    let (user_dir, system_dirs) = dirs.split_first()?;
+    if path.starts_with(user_dir) {
+        return None;
+    }
+    let relative = system_dirs
+        .iter()
+        .find_map(|dir| path.strip_prefix(dir).ok())?;
+    Some(user_dir.join(relative))
+}
+
+/// Copies a system desktop entry to the user's applications, unless there already is a copy.
+pub fn copy_to_user(path: &Path) -> eyre::Result<PathBuf> {
+    let target = user_entry_path(path).wrap_err("Desktop entry is not in a system dir")?;
+    if !target.exists() {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).wrap_err("Failed to create applications dir")?;
+        }
+        fs::copy(path, &target).wrap_err("Failed to copy desktop entry")?;
+    }
+    Ok(target)
+}
+
+fn find_application_dirs() -> io::Result<Vec<PathBuf>> {
+    Ok(data_dirs()?
+        .into_iter()
+        .map(|dir| dir.join("applications"))
+        .collect())
+}
+
+/// The XDG data dirs, the user's first, and Flatpak's. Inside a sandbox the host's.
+pub fn data_dirs() -> io::Result<Vec<PathBuf>> {
+    if flatpak::is_sandboxed() {
+        return Ok(flatpak::host_data_dirs());
+    }
+
+    let data_home = match env::var_os("XDG_DATA_HOME") {
+        Some(val) => PathBuf::from(val),
+        None => {
+            let home = dirs::home_dir().ok_or(io::Error::new(
+                io::ErrorKind::Other,
+                "Couldn't get home dir",
+            ))?;
+            home.join(".local/share")
+        }
+    };
+    let extra_data_dirs = match env::var_os("XDG_DATA_DIRS") {
+        Some(val) => env::split_paths(&val).map(PathBuf::from).collect(),
+        None => {
+            vec![
+                PathBuf::from("/usr/local/share"),
+                PathBuf::from("/usr/share"),
+            ]
+        }
+    };
+
+    let mut res = vec![data_home];
+    res.extend(extra_data_dirs);
+    for dir in flatpak::export_dirs() {
+        if !res.contains(&dir) {
+            res.push(dir);
+        }
+    }
+    Ok(res)
+}
+
+/// The desktop entries in `path` and its subdirectories with their desktop file id, their
+/// path in `path` with `-` for `/`, like `kde-org.kde.dolphin.desktop`.
+fn get_dir_desktop_files(path: &Path) -> io::Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![(path.to_path_buf(), String::new())];
+    while let Some((dir, prefix)) = dirs.pop() {
+        let readdir = match dir.read_dir() {
+            Ok(v) => v,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) if dir == path => return Err(e),
+            Err(e) => {
+                warn!("Could not list {dir:?}: {e}");
+                continue;
+            }
+        };
+        for entry in readdir.filter_map(|v| v.ok()) {
+            let name = entry.file_name().to_string_lossy().to_string();
+            match entry.file_type() {
+                // Symlinked dirs aren't followed, they could loop.
+                Ok(ft) if ft.is_dir() => dirs.push((entry.path(), format!("{prefix}{name}-"))),
+                Ok(ft) if (ft.is_file() | ft.is_symlink()) && name.ends_with(".desktop") => {
+                    files.push((format!("{prefix}{name}"), entry.path()));
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(files)
+}
@@ -0,0 +1,365 @@
+use crate::apps::{App, AppId, AppSource};
+use crate::platform::{
+    parse_uri_list, pipe_to, spawn_detached, AppLaunch, AutostartEntry, Capture, Launcher, Monitor,
+    Toggle,
+};
+use eframe::egui::{Pos2, Vec2};
+use eyre::{Context, ContextCompat};
+use plist::{Dictionary, Value};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{debug, warn};
+
+/// Finds the `.app` bundles in the application folders and `extra_dirs`.
+pub fn find_applications(extra_dirs: &[PathBuf]) -> eyre::Result<Vec<App>> {
+    let languages = preferred_languages();
+    let mut applications = Vec::new();
+
+    for dir in application_dirs().iter().chain(extra_dirs) {
+        let mut bundles = Vec::new();
+        find_bundles(dir, &mut bundles, 2);
+        for bundle in bundles {
+            match parse_bundle(&bundle, &languages) {
+                Ok(app) => applications.push(app),
+                Err(error) => warn!("Could not read bundle {bundle:?}: {error:?}"),
+            }
+        }
+    }
+
+    Ok(applications)
+}
+
+/// The dirs [find_applications] reads, for watching them.
+pub fn watch_dirs(extra_dirs: &[PathBuf]) -> Vec<PathBuf> {
+    application_dirs()
+        .into_iter()
+        .chain(extra_dirs.iter().cloned())
+        .collect()
+}
+
+/// Changes inside other files than bundles don't change the apps.
+pub const APP_EXTENSIONS: &[&str] = &["app"];
+
+/// Terminal.app is opened when no terminal is set.
+pub fn default_terminal() -> Option<String> {
+    None
+}
+
+/// Monitor placement is left to the window server.
+pub fn monitors() -> Vec<Monitor> {
+    Vec::new()
+}
+
+pub fn cursor_position() -> Option<(i32, i32)> {
+    None
+}
+
+/// The files or URLs on the clipboard, as text through `pbpaste`.
+pub fn clipboard_uris() -> eyre::Result<Vec<String>> {
+    let output = Command::new("pbpaste")
+        .output()
+        .wrap_err("Failed to run pbpaste")?;
+    parse_uri_list(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Puts `text` on the clipboard with `pbcopy`, empty text clears it.
+pub fn set_clipboard_text(text: &str) -> eyre::Result<()> {
+    pipe_to(&mut Command::new("pbcopy"), text)
+}
+
+pub fn clipboard_text() -> eyre::Result<String> {
+    let output = Command::new("pbpaste")
+        .output()
+        .wrap_err("Failed to run pbpaste")?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Takes a screenshot to the clipboard with `screencapture`, macOS has no color picker to call.
+pub fn capture(kind: Capture) -> eyre::Result<()> {
+    let mut command = Command::new("screencapture");
+    match kind {
+        Capture::Region => command.args(["-c", "-i"]),
+        Capture::Screen => command.arg("-c"),
+        Capture::Color => eyre::bail!("Picking colors isn't supported on macOS"),
+    };
+    let status = command.status().wrap_err("Failed to run screencapture")?;
+    if !status.success() {
+        eyre::bail!("screencapture exited with {status}");
+    }
+    Ok(())
+}
+
+/// There are no quick settings to toggle on macOS yet.
+pub fn toggle_state(_toggle: Toggle) -> eyre::Result<bool> {
+    eyre::bail!("Quick settings aren't supported on macOS")
+}
+
+pub fn set_toggle(_toggle: Toggle, _on: bool) -> eyre::Result<()> {
+    eyre::bail!("Quick settings aren't supported on macOS")
+}
+
+/// Login items aren't managed on macOS yet.
+pub fn autostart_entries() -> eyre::Result<Vec<AutostartEntry>> {
+    eyre::bail!("Autostart isn't supported on macOS")
+}
+
+pub fn set_autostart(_id: &str, _enabled: bool) -> eyre::Result<()> {
+    eyre::bail!("Autostart isn't supported on macOS")
+}
+
+pub fn add_to_autostart(_path: &Path) -> eyre::Result<PathBuf> {
+    eyre::bail!("Autostart isn't supported on macOS")
+}
+
+/// Apps have their icons in their bundle or executable on macOS, there are no themes.
+pub fn icon_theme() -> Option<String> {
+    None
+}
+
+/// Nothing draws a window before the real one outside of X11.
+pub struct Placeholder;
+
+pub fn open_placeholder(
+    _position: Pos2,
+    _size: Vec2,
+    _border_width: u32,
+    _background: u32,
+    _border: u32,
+) -> eyre::Result<Option<Placeholder>> {
+    Ok(None)
+}
+
+pub fn supports_transparency() -> bool {
+    true
+}
+
+/// System fonts are only found through fontconfig, on Linux.
+pub fn find_font(_family: &str, _bold: bool) -> Option<(PathBuf, u32)> {
+    None
+}
+
+/// Windows aren't tracked on macOS.
+pub fn window_classes() -> HashSet<String> {
+    HashSet::new()
+}
+
+/// `open` exits right away, so there is never a pid to check.
+pub fn process_start(_pid: u32) -> Option<u64> {
+    None
+}
+
+/// Shows a notification through AppleScript, which always uses our icon.
+pub fn notify(summary: &str, body: &str, _icon: Option<&str>) {
+    let quote = |v: &str| format!("\"{}\"", v.replace('\\', "\\\\").replace('"', "\\\""));
+    let script = format!(
+        "display notification {} with title {}",
+        quote(body),
+        quote(summary)
+    );
+    if let Err(error) = Command::new("osascript").arg("-e").arg(script).status() {
+        warn!("Could not run osascript: {error}");
+    }
+}
+
+/// Launches bundles through `open`, like Finder would.
+pub struct PlatformLauncher;
+
+impl Launcher for PlatformLauncher {
+    fn launch(&self, request: &AppLaunch) -> eyre::Result<Option<u32>> {
+        if !request.wrapper.is_empty() || !request.env.is_empty() || request.login_env {
+            warn!("Launch wrappers and environments aren't supported on macOS, ignoring them");
+        }
+        spawn_detached(
+            Command::new("open")
+                .arg("-a")
+                .arg(&request.path)
+                .args(&request.args),
+        )
+    }
+
+    fn open_uri(&self, uri: &str) -> eyre::Result<()> {
+        let status = Command::new("open")
+            .arg(uri)
+            .status()
+            .wrap_err("Failed to run open")?;
+        if !status.success() {
+            eyre::bail!("open exited with {status}");
+        }
+        Ok(())
+    }
+
+    /// Terminal apps open a new window in the folder they are given.
+    fn open_terminal(&self, command: Option<&str>, dir: &Path, run: &[String]) -> eyre::Result<()> {
+        if !run.is_empty() {
+            warn!("Running a program in the terminal isn't supported on macOS, ignoring it");
+        }
+        spawn_detached(
+            Command::new("open")
+                .arg("-a")
+                .arg(command.unwrap_or("Terminal"))
+                .arg(dir),
+        )
+        .map(|_| ())
+    }
+
+    fn run_command(&self, command: &[String], dir: &Path) -> eyre::Result<()> {
+        let (program, args) = command.split_first().wrap_err("The command is empty")?;
+        spawn_detached(Command::new(program).args(args).current_dir(dir)).map(|_| ())
+    }
+}
+
+fn application_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![
+        PathBuf::from("/Applications"),
+        PathBuf::from("/System/Applications"),
+    ];
+    if let Some(home) = dirs::home_dir() {
+        dirs.push(home.join("Applications"));
+    }
+    dirs.into_iter().filter(|dir| dir.is_dir()).collect()
+}
+
+/// Bundles can be grouped in plain folders (like `/Applications/Utilities`),
+/// `depth` limits how far we look.
+fn find_bundles(dir: &Path, out: &mut Vec<PathBuf>, depth: usize) {
+    let Ok(entries) = dir.read_dir() else {
+        return;
+    };
+    for entry in entries.filter_map(|v| v.ok()) {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+
+        if path.extension().is_some_and(|v| v == "app") {
+            out.push(path);
+        } else if depth > 0 {
+            find_bundles(&path, out, depth - 1);
+        }
+    }
+}
+
+fn parse_bundle(bundle: &Path, languages: &[String]) -> eyre::Result<App> {
+    let contents = bundle.join("Contents");
+    let info = Value::from_file(contents.join("Info.plist"))
+        .wrap_err("Failed to read Info.plist")?
+        .into_dictionary()
+        .wrap_err("Info.plist is not a dictionary")?;
+    let localized = localized_strings(&contents.join("Resources"), languages);
+
+    let get = |key: &str| -> Option<String> {
+        localized
+            .get(key)
+            .cloned()
+            .or_else(|| info_string(&info, key))
+    };
+
+    let name = get("CFBundleDisplayName")
+        .or_else(|| get("CFBundleName"))
+        .or_else(|| {
+            bundle
+                .file_stem()
+                .and_then(|v| v.to_str())
+                .map(|v| v.to_string())
+        })
+        .wrap_err("Bundle has no name")?;
+    let identifier = info_string(&info, "CFBundleIdentifier").unwrap_or_default();
+    let icon = info_string(&info, "CFBundleIconFile").map(|file| {
+        let mut path = contents.join("Resources").join(file);
+        if path.extension().is_none() {
+            path.set_extension("icns");
+        }
+        path.to_string_lossy().to_string()
+    });
+    // public.app-category.developer-tools -> developer-tools
+    let categories = info_string(&info, "LSApplicationCategoryType").map(|category| {
+        vec![category
+            .rsplit('.')
+            .next()
+            .unwrap_or(&category)
+            .to_string()]
+    });
+    debug!("Found bundle {name} ({identifier})");
+
+    Ok(App {
+        id: AppId::from_properties(&[&name, &identifier]),
+        path: bundle.to_path_buf(),
+        exec: bundle.to_string_lossy().to_string(),
+        comment: get("CFBundleGetInfoString"),
+        name,
+        icon,
+        categories,
+        source: AppSource::AppBundle,
+        ..App::default()
+    })
+}
+
+fn info_string(info: &Dictionary, key: &str) -> Option<String> {
+    info.get(key)
+        .and_then(|v| v.as_string())
+        .map(|v| v.to_string())
+}
+
+/// The languages to look for in `<lang>.lproj` folders, most preferred first.
+fn preferred_languages() -> Vec<String> {
+    let mut languages = Vec::new();
+    for var in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+        let Ok(value) = env::var(var) else {
+            continue;
+        };
+        // de_DE.UTF-8 -> de_DE, de
+        let locale = value.split('.').next().unwrap_or_default().to_string();
+        if locale.is_empty() || locale == "C" || locale == "POSIX" {
+            continue;
+        }
+        if let Some((language, _)) = locale.split_once('_') {
+            languages.push(language.to_string());
+        }
+        languages.insert(0, locale);
+        break;
+    }
+    languages
+}
+
+/// Reads `InfoPlist.strings` of the first matching localization.
+fn localized_strings(resources: &Path, languages: &[String]) -> HashMap<String, String> {
+    for language in languages {
+        let path = resources
+            .join(format!("{language}.lproj"))
+            .join("InfoPlist.strings");
+        if !path.is_file() {
+            continue;
+        }
+
+        // These are either binary plists or the old "key" = "value"; text format.
+        if let Ok(Value::Dictionary(dictionary)) = Value::from_file(&path) {
+            return dictionary
+                .into_iter()
+                .filter_map(|(key, value)| Some((key, value.into_string()?)))
+                .collect();
+        }
+        if let Some(strings) = read_to_string(&path).ok().map(|v| parse_strings(&v)) {
+            return strings;
+        }
+    }
+
+    HashMap::new()
+}
+
+fn parse_strings(text: &str) -> HashMap<String, String> {
+    let mut strings = HashMap::new();
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().trim_matches('"');
+        let value = value.trim().trim_end_matches(';').trim().trim_matches('"');
+        if !key.is_empty() {
+            strings.insert(key.to_string(), value.to_string());
+        }
+    }
+    strings
+}
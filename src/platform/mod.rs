@@ -0,0 +1,521 @@
+//! Everything that differs between operating systems lives in here,
+//! each platform provides the same set of functions and a [PlatformLauncher].
+
+use crate::apps::{App, AppId, AppSource};
+use crate::media::{self, MediaCommand, Player};
+use crate::providers::{self, ProviderResult};
+use crate::secrets::{self, SecretEntry};
+use crate::settings::{LaunchWrapper, ResourceLimits};
+use eframe::egui::{Pos2, Vec2};
+use eyre::Context;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::OnceLock;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+#[cfg(all(unix, not(target_os = "macos")))]
+pub mod appimage;
+#[cfg(all(unix, not(target_os = "macos")))]
+mod autostart;
+#[cfg(all(unix, not(target_os = "macos")))]
+pub mod flatpak;
+#[cfg(all(unix, not(target_os = "macos")))]
+mod linux;
+#[cfg(all(unix, not(target_os = "macos")))]
+pub mod packages;
+#[cfg(all(unix, not(target_os = "macos")))]
+pub mod mpris;
+#[cfg(all(unix, not(target_os = "macos")))]
+mod portal;
+#[cfg(all(unix, not(target_os = "macos")))]
+pub mod search_providers;
+#[cfg(all(unix, not(target_os = "macos")))]
+pub mod secret_service;
+#[cfg(all(unix, not(target_os = "macos")))]
+mod toggles;
+#[cfg(all(unix, not(target_os = "macos")))]
+pub mod x11;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(windows)]
+mod windows;
+
+#[cfg(all(unix, not(target_os = "macos")))]
+pub use autostart::*;
+#[cfg(all(unix, not(target_os = "macos")))]
+pub use linux::*;
+#[cfg(target_os = "macos")]
+pub use macos::*;
+#[cfg(all(unix, not(target_os = "macos")))]
+pub use toggles::*;
+#[cfg(windows)]
+pub use windows::*;
+
+/// Starts applications, implemented by every platform as `PlatformLauncher`.
+pub trait Launcher {
+    /// Returns the pid of the started process, when it is still running after a moment.
+    fn launch(&self, app: &AppLaunch) -> eyre::Result<Option<u32>>;
+
+    /// Opens a file or URL in its default application.
+    fn open_uri(&self, uri: &str) -> eyre::Result<()>;
+
+    /// Opens `command`, or the platform's default terminal, in `dir`, running `run` if it isn't empty.
+    fn open_terminal(&self, command: Option<&str>, dir: &Path, run: &[String]) -> eyre::Result<()>;
+
+    /// Starts `command`, a program and its arguments, in `dir`.
+    fn run_command(&self, command: &[String], dir: &Path) -> eyre::Result<()>;
+}
+
+/// What the ui decided to launch, detached from the [App] so it outlives the ui.
+pub enum LaunchRequest {
+    App(AppLaunch),
+    /// Files and URLs, opened with their default application.
+    Uri(String),
+    /// A terminal emulator opened in a directory.
+    Terminal {
+        command: Option<String>,
+        dir: PathBuf,
+        /// A program to run in the terminal, instead of a shell.
+        run: Vec<String>,
+    },
+    /// A password copied to the clipboard, which is cleared after `clear_after`.
+    CopySecret {
+        entry: SecretEntry,
+        clear_after: Duration,
+    },
+    /// A program like an editor, started in a directory.
+    Command {
+        command: Vec<String>,
+        dir: PathBuf,
+    },
+    /// A screenshot or a color, copied to the clipboard.
+    Capture(Capture),
+    /// Turns a quick setting on or off.
+    SetToggle {
+        toggle: Toggle,
+        on: bool,
+    },
+    /// A result of a search provider, which the provider opens.
+    ProviderResult(ProviderResult),
+    /// A command for a media player, for the ones that leave the launcher.
+    Media {
+        player: Player,
+        command: MediaCommand,
+    },
+    /// Text copied to the clipboard.
+    CopyText(String),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Capture {
+    /// A screenshot of a region the user picks.
+    Region,
+    /// A screenshot of the whole screen.
+    Screen,
+    /// The color of a pixel the user picks, as `#rrggbb`.
+    Color,
+}
+
+/// A quick setting of the desktop, read with `toggle_state` and changed with `set_toggle`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Toggle {
+    NightLight,
+    DoNotDisturb,
+    DarkMode,
+    AirplaneMode,
+}
+
+impl Toggle {
+    pub const ALL: [Toggle; 4] = [
+        Toggle::NightLight,
+        Toggle::DoNotDisturb,
+        Toggle::DarkMode,
+        Toggle::AirplaneMode,
+    ];
+}
+
+/// A program started at login, listed with `autostart_entries` and turned on or off with
+/// `set_autostart`.
+#[derive(Clone, Debug)]
+pub struct AutostartEntry {
+    /// The file name of the entry.
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+}
+
+impl LaunchRequest {
+    pub fn name(&self) -> &str {
+        match self {
+            LaunchRequest::App(app) => &app.name,
+            LaunchRequest::Uri(uri) => uri,
+            LaunchRequest::Terminal { dir, .. } => dir.to_str().unwrap_or("terminal"),
+            LaunchRequest::CopySecret { entry, .. } => &entry.name,
+            LaunchRequest::Command { command, .. } => command.first().map_or("command", |v| v),
+            LaunchRequest::Capture(Capture::Color) => "color picker",
+            LaunchRequest::Capture(_) => "screenshot",
+            LaunchRequest::SetToggle { .. } => "quick setting",
+            LaunchRequest::ProviderResult(result) => &result.name,
+            LaunchRequest::Media { player, .. } => &player.name,
+            LaunchRequest::CopyText(text) => text,
+        }
+    }
+
+    /// Whether this starts a process. Those are started while the ui is still up, so a
+    /// failure is reported before it closes. The rest needs the window gone or blocks.
+    pub fn spawns(&self) -> bool {
+        matches!(
+            self,
+            LaunchRequest::App(_)
+                | LaunchRequest::Uri(_)
+                | LaunchRequest::Terminal { .. }
+                | LaunchRequest::Command { .. }
+        )
+    }
+
+    /// Returns the pid of launched apps, see [Launcher::launch].
+    pub fn run(&self, launcher: &impl Launcher) -> eyre::Result<Option<u32>> {
+        match self {
+            LaunchRequest::App(app) => launcher.launch(app),
+            LaunchRequest::Uri(uri) => launcher.open_uri(uri).map(|_| None),
+            LaunchRequest::Terminal { command, dir, run } => launcher
+                .open_terminal(command.as_deref(), dir, run)
+                .map(|_| None),
+            LaunchRequest::Command { command, dir } => {
+                launcher.run_command(command, dir).map(|_| None)
+            }
+            LaunchRequest::CopySecret { entry, clear_after } => {
+                secrets::copy(entry, *clear_after).map(|_| None)
+            }
+            LaunchRequest::Capture(kind) => {
+                // Gives the compositor a moment to take our window off the screen.
+                sleep(Duration::from_millis(200));
+                capture(*kind).map(|_| None)
+            }
+            LaunchRequest::SetToggle { toggle, on } => set_toggle(*toggle, *on).map(|_| None),
+            LaunchRequest::ProviderResult(result) => providers::activate(result).map(|_| None),
+            LaunchRequest::Media { player, command } => {
+                media::control(&player.bus_name, *command).map(|_| None)
+            }
+            LaunchRequest::CopyText(text) => set_clipboard_text(text).map(|_| None),
+        }
+    }
+}
+
+pub struct AppLaunch {
+    pub id: AppId,
+    pub name: String,
+    /// The desktop entry, shortcut or bundle the app was found at.
+    pub path: PathBuf,
+    pub exec: String,
+    /// The directory to start the app in, see [App::working_dir].
+    pub working_dir: Option<PathBuf>,
+    /// For the `%i` field code.
+    pub icon: Option<String>,
+    /// The Flatpak app id, see [App::flatpak].
+    pub flatpak: Option<String>,
+    pub terminal: bool,
+    /// Runs `terminal` apps with `-e`, see [crate::settings::TerminalSettings::command].
+    pub terminal_command: Option<String>,
+    pub dbus_activatable: bool,
+    /// Files or URLs to open with the app.
+    pub args: Vec<String>,
+    /// Commands the app is started through, like `gamemoderun`, outermost first.
+    pub wrapper: Vec<String>,
+    pub env: Vec<(String, String)>,
+    /// Start from the environment of a login shell, like a fresh session would.
+    pub login_env: bool,
+    /// Show a notification once the app was started.
+    pub notify: bool,
+    /// The process of an earlier launch, its window gets focused instead of launching again
+    /// when it has one.
+    pub focus_pid: Option<u32>,
+}
+
+impl AppLaunch {
+    /// Adds the wrappers and environment of the rules matching the app's `categories`.
+    pub fn apply_wrappers(&mut self, categories: &[String], wrappers: &[LaunchWrapper]) {
+        for wrapper in wrappers {
+            if !wrapper.categories.iter().any(|v| categories.contains(v)) {
+                continue;
+            }
+            self.wrapper.extend(wrapper.command.iter().cloned());
+            self.env.extend(wrapper.env.clone());
+        }
+    }
+
+    /// Starts the app in a scope with `limits`, around the other wrappers so they count too.
+    pub fn apply_resources(&mut self, limits: &ResourceLimits) {
+        let mut wrapper = limits.command();
+        wrapper.append(&mut self.wrapper);
+        self.wrapper = wrapper;
+    }
+}
+
+impl From<&App> for LaunchRequest {
+    fn from(app: &App) -> Self {
+        if app.source == AppSource::Link {
+            return LaunchRequest::Uri(app.exec.clone());
+        }
+        LaunchRequest::App(AppLaunch {
+            id: app.id.clone(),
+            name: app.name.clone(),
+            path: app.path.clone(),
+            exec: app.exec.clone(),
+            working_dir: app.working_dir.clone(),
+            icon: app.icon.clone(),
+            flatpak: app.flatpak.clone(),
+            terminal: app.terminal,
+            terminal_command: None,
+            dbus_activatable: app.dbus_activatable,
+            args: Vec::new(),
+            wrapper: Vec::new(),
+            env: Vec::new(),
+            login_env: false,
+            notify: false,
+            focus_pid: None,
+        })
+    }
+}
+
+/// How long a launched process gets to fail before we count the launch as a success.
+const EARLY_EXIT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Variables that configure ignition or its toolkit, launched apps shouldn't inherit them.
+const LAUNCHER_VARS: &[&str] = &[
+    "RUST_LOG",
+    "RUST_BACKTRACE",
+    "WAYLAND_SOCKET",
+    "WAYLAND_DEBUG",
+];
+const LAUNCHER_VAR_PREFIXES: &[&str] = &["WINIT_", "WGPU_", "EGL_", "__EGL_", "IGNITION_"];
+
+pub fn is_launcher_var(key: &str) -> bool {
+    LAUNCHER_VARS.contains(&key) || LAUNCHER_VAR_PREFIXES.iter().any(|v| key.starts_with(v))
+}
+
+/// Keeps `command` from inheriting our [is_launcher_var]s.
+pub fn sanitize_env(command: &mut Command) {
+    for (key, _) in env::vars_os() {
+        if key.to_str().is_some_and(is_launcher_var) {
+            command.env_remove(key);
+        }
+    }
+}
+
+/// Runs a command line in the user's shell.
+#[cfg(unix)]
+pub fn shell_command(line: &str) -> Vec<String> {
+    let shell = env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+    vec![shell, "-c".to_string(), line.to_string()]
+}
+
+/// Runs a command line in `cmd.exe`.
+#[cfg(windows)]
+pub fn shell_command(line: &str) -> Vec<String> {
+    vec!["cmd".to_string(), "/C".to_string(), line.to_string()]
+}
+
+/// Reads clipboard text as files or URLs, one per line like `text/uri-list`.
+///
+/// Fails unless every line is a URL or an existing absolute path.
+pub fn parse_uri_list(text: &str) -> eyre::Result<Vec<String>> {
+    let uris: Vec<String> = text
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.to_string())
+        .collect();
+    if uris.is_empty() {
+        eyre::bail!("The clipboard is empty");
+    }
+    for uri in &uris {
+        let is_url = uri.split_once("://").is_some_and(|(scheme, _)| {
+            !scheme.is_empty()
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+        });
+        let path = Path::new(uri);
+        if !is_url && !(path.is_absolute() && path.exists()) {
+            eyre::bail!("{uri:?} is neither a URL nor an existing file");
+        }
+    }
+    Ok(uris)
+}
+
+/// Where launched apps write their output, they outlive us and the terminal we ran in.
+static LAUNCH_LOG: OnceLock<PathBuf> = OnceLock::new();
+/// The launch log starts over above this.
+const LAUNCH_LOG_LIMIT: u64 = 1024 * 1024;
+
+pub fn set_launch_log(path: PathBuf) {
+    if fs::metadata(&path).is_ok_and(|v| v.len() > LAUNCH_LOG_LIMIT) {
+        if let Err(error) = fs::remove_file(&path) {
+            warn!("Could not clear the launch log: {error}");
+        }
+    }
+    let _ = LAUNCH_LOG.set(path);
+}
+
+/// Starts `command` detached from us: without our stdin, writing to the launch log, and on
+/// unix in its own session so it has no controlling terminal and our signals don't reach it.
+///
+/// Fails when it exits with an error right away, see [check_early_exit].
+pub fn spawn_detached(command: &mut Command) -> eyre::Result<Option<u32>> {
+    command.stdin(Stdio::null());
+    let log = LAUNCH_LOG.get().and_then(|path| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .inspect_err(|error| warn!("Could not open the launch log {path:?}: {error}"))
+            .ok()
+    });
+    let (stdout, stderr) = match log.and_then(|log| Some((log.try_clone().ok()?, log))) {
+        Some((stdout, stderr)) => (Stdio::from(stdout), Stdio::from(stderr)),
+        None => (Stdio::null(), Stdio::null()),
+    };
+    command.stdout(stdout).stderr(stderr);
+    #[cfg(unix)]
+    unsafe {
+        // Only async-signal-safe calls are allowed between fork and exec, setsid is one.
+        std::os::unix::process::CommandExt::pre_exec(command, || {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+
+    let child = command
+        .spawn()
+        .wrap_err_with(|| format!("Failed to spawn {:?}", command.get_program()))?;
+    check_early_exit(child)
+}
+
+/// Runs `command` with `input` as its stdin, for clipboard tools. Passwords go through
+/// here, so they never show up in the arguments.
+pub fn pipe_to(command: &mut Command, input: impl AsRef<[u8]>) -> eyre::Result<()> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .wrap_err_with(|| format!("Failed to run {program}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_ref())
+            .wrap_err_with(|| format!("Failed to write to {program}"))?;
+    }
+    let status = child
+        .wait()
+        .wrap_err_with(|| format!("Failed to wait for {program}"))?;
+    if !status.success() {
+        eyre::bail!("{program} exited with {status}");
+    }
+    Ok(())
+}
+
+/// Fails when `child` exits with an error right away, like `gio launch` does for a
+/// missing binary. Processes that are still running after a moment are waited for on a
+/// thread so they don't linger as zombies, once we exit init takes over. Their pid is returned.
+fn check_early_exit(mut child: Child) -> eyre::Result<Option<u32>> {
+    let start = Instant::now();
+    while start.elapsed() < EARLY_EXIT_TIMEOUT {
+        if let Some(status) = child.try_wait().wrap_err("Failed to wait for child")? {
+            if !status.success() {
+                eyre::bail!("Exited right away with {status}");
+            }
+            return Ok(None);
+        }
+        sleep(Duration::from_millis(20));
+    }
+    let pid = child.id();
+    std::thread::spawn(move || child.wait());
+    Ok(Some(pid))
+}
+
+/// A monitor, in the physical pixels of the whole desktop.
+pub struct Monitor {
+    /// The output name, like `DP-1` on X11 or `\\.\DISPLAY1` on Windows.
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    /// Pixels per point, like 2 on a HiDPI monitor.
+    pub scale: f32,
+}
+
+impl Monitor {
+    fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x
+            && y >= self.y
+            && x < self.x + self.width as i32
+            && y < self.y + self.height as i32
+    }
+
+    /// Where a window of `size` in points is centered, in points like the viewport wants it.
+    fn center(&self, size: Vec2) -> Pos2 {
+        let size = size * self.scale;
+        Pos2::new(
+            self.x as f32 + (self.width as f32 - size.x) / 2.0,
+            self.y as f32 + (self.height as f32 - size.y) / 2.0,
+        ) / self.scale
+    }
+}
+
+/// Where to open a window of `size`, centered on the pinned `output` or else on the monitor
+/// with the cursor. `None` leaves it up to the window manager (and always on Wayland).
+pub fn window_position(size: Vec2, output: Option<&str>) -> Option<Pos2> {
+    Some(window_monitor(output)?.center(size))
+}
+
+/// The pinned `output`, or else the monitor with the cursor.
+fn window_monitor(output: Option<&str>) -> Option<Monitor> {
+    let monitors = monitors();
+    let pinned = output.and_then(|output| {
+        let monitor = monitors.iter().position(|monitor| monitor.name == output);
+        if monitor.is_none() {
+            warn!("There is no output named {output:?}");
+        }
+        monitor
+    });
+    let index = pinned.or_else(|| {
+        let (x, y) = cursor_position()?;
+        monitors.iter().position(|monitor| monitor.contains(x, y))
+    })?;
+    monitors.into_iter().nth(index)
+}
+
+/// Shows a window drawn without the GPU where [window_position] puts the real one, filled
+/// with `background` inside a `border`. It is closed when dropped, once the real window
+/// drew its first frame. `None` where the window can't be placed.
+pub fn show_placeholder(
+    size: Vec2,
+    output: Option<&str>,
+    background: [u8; 3],
+    border: [u8; 3],
+) -> Option<Placeholder> {
+    let monitor = window_monitor(output)?;
+    let position = monitor.center(size) * monitor.scale;
+    let pixel = |[r, g, b]: [u8; 3]| u32::from_be_bytes([0, r, g, b]);
+    let border_width = (2.0 * monitor.scale).round() as u32;
+    open_placeholder(
+        position,
+        size * monitor.scale,
+        border_width,
+        pixel(background),
+        pixel(border),
+    )
+    .inspect_err(|error| warn!("Could not show a placeholder: {error:?}"))
+    .ok()
+    .flatten()
+}
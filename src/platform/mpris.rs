@@ -0,0 +1,89 @@
+//! Reads and controls media players over MPRIS, which Spotify, browsers and most players
+//! implement.
+
+use crate::media::{MediaCommand, Player};
+use eyre::Context;
+use std::collections::HashMap;
+use tracing::debug;
+use zbus::blocking::fdo::DBusProxy;
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{OwnedValue, Value};
+
+const PREFIX: &str = "org.mpris.MediaPlayer2.";
+const PATH: &str = "/org/mpris/MediaPlayer2";
+const ROOT: &str = "org.mpris.MediaPlayer2";
+const PLAYER: &str = "org.mpris.MediaPlayer2.Player";
+
+/// The players on the session bus, players that don't answer are left out.
+pub fn players() -> eyre::Result<Vec<Player>> {
+    let connection = Connection::session().wrap_err("Failed to connect to the session bus")?;
+    let names = DBusProxy::new(&connection)
+        .wrap_err("Failed to connect to the bus")?
+        .list_names()
+        .wrap_err("Failed to list the bus names")?;
+
+    let mut players = Vec::new();
+    for name in &names {
+        if !name.as_str().starts_with(PREFIX) {
+            continue;
+        }
+        match player(&connection, name.as_str()) {
+            Ok(player) => players.push(player),
+            Err(error) => debug!("Could not read the player {name}: {error:?}"),
+        }
+    }
+    Ok(players)
+}
+
+fn player(connection: &Connection, bus_name: &str) -> eyre::Result<Player> {
+    let root = Proxy::new(connection, bus_name, PATH, ROOT).wrap_err("Failed to reach it")?;
+    let name = root
+        .get_property::<String>("Identity")
+        .unwrap_or_else(|_| bus_name.trim_start_matches(PREFIX).to_string());
+
+    let player = Proxy::new(connection, bus_name, PATH, PLAYER).wrap_err("Failed to reach it")?;
+    let status: String = player
+        .get_property("PlaybackStatus")
+        .wrap_err("Failed to read the playback status")?;
+    let metadata: HashMap<String, OwnedValue> = player.get_property("Metadata").unwrap_or_default();
+    let title = match metadata.get("xesam:title").map(|v| &**v) {
+        Some(Value::Str(title)) if !title.is_empty() => Some(title.to_string()),
+        _ => None,
+    };
+    let artists: Vec<String> = match metadata.get("xesam:artist").map(|v| &**v) {
+        Some(Value::Array(artists)) => artists
+            .iter()
+            .filter_map(|artist| match artist {
+                Value::Str(artist) => Some(artist.to_string()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    Ok(Player {
+        bus_name: bus_name.to_string(),
+        name,
+        playing: status == "Playing",
+        stopped: status == "Stopped",
+        title,
+        artist: Some(artists.join(", ")).filter(|v| !v.is_empty()),
+        can_go_next: player.get_property("CanGoNext").unwrap_or(true),
+        can_go_previous: player.get_property("CanGoPrevious").unwrap_or(true),
+    })
+}
+
+/// Sends `command` to the player at `bus_name`.
+pub fn control(bus_name: &str, command: MediaCommand) -> eyre::Result<()> {
+    let connection = Connection::session().wrap_err("Failed to connect to the session bus")?;
+    let (interface, method) = match command {
+        MediaCommand::PlayPause => (PLAYER, "PlayPause"),
+        MediaCommand::Next => (PLAYER, "Next"),
+        MediaCommand::Previous => (PLAYER, "Previous"),
+        MediaCommand::Raise => (ROOT, "Raise"),
+    };
+    connection
+        .call_method(Some(bus_name), PATH, Some(interface), method, &())
+        .wrap_err_with(|| format!("{method} failed"))?;
+    Ok(())
+}
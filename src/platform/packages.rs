@@ -0,0 +1,117 @@
+//! Finding the package an app was installed with, to uninstall it or show its info.
+
+use crate::platform::flatpak;
+use std::path::Path;
+use tracing::debug;
+
+/// Wraps `command` to wait for Enter after it, so the terminal stays open to read its output.
+pub fn pause_after(command: Vec<String>, prompt: &str) -> Vec<String> {
+    let script = r#"prompt=$1; shift; "$@"; printf '\n%s' "$prompt"; read -r _"#;
+    let mut run = vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        script.to_string(),
+        "sh".to_string(),
+        prompt.to_string(),
+    ];
+    run.extend(command);
+    run
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum PackageManager {
+    Flatpak,
+    Pacman,
+    Dpkg,
+    Rpm,
+}
+
+#[derive(Debug)]
+pub struct Package {
+    pub manager: PackageManager,
+    pub name: String,
+}
+
+impl Package {
+    /// Finds the package that owns the desktop entry at `path`, `None` if no package
+    /// manager we know claims it. `flatpak` is the app id of Flatpak apps.
+    pub fn find(path: &Path, flatpak: Option<&str>) -> Option<Package> {
+        // Flatpak exports its entries named by the app id.
+        if flatpak.is_some() || path.components().any(|v| v.as_os_str() == "flatpak") {
+            let name = match flatpak {
+                Some(app_id) => app_id.to_string(),
+                None => path.file_stem()?.to_str()?.to_string(),
+            };
+            return Some(Package {
+                manager: PackageManager::Flatpak,
+                name,
+            });
+        }
+
+        let path = flatpak::to_host_path(path);
+        let queries: [(PackageManager, &str, &[&str]); 3] = [
+            (PackageManager::Pacman, "pacman", &["-Qqo"]),
+            (PackageManager::Dpkg, "dpkg", &["-S"]),
+            (
+                PackageManager::Rpm,
+                "rpm",
+                &["-qf", "--queryformat", "%{NAME}"],
+            ),
+        ];
+        for (manager, program, args) in queries {
+            let output = flatpak::host_command(program)
+                .args(args)
+                .arg(&path)
+                .output();
+            let Ok(output) = output.inspect_err(|error| debug!("{program}: {error}")) else {
+                continue;
+            };
+            if !output.status.success() {
+                continue;
+            }
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let name = match manager {
+                // "package1, package2: /usr/share/applications/app.desktop"
+                PackageManager::Dpkg => stdout
+                    .rsplit_once(": ")
+                    .and_then(|(names, _)| names.split(", ").next())
+                    .unwrap_or_default(),
+                _ => stdout.lines().next().unwrap_or_default(),
+            };
+            if !name.trim().is_empty() {
+                return Some(Package {
+                    manager,
+                    name: name.trim().to_string(),
+                });
+            }
+        }
+        None
+    }
+
+    /// Removes the package, the package manager asks for confirmation itself.
+    pub fn uninstall_command(&self) -> Vec<String> {
+        let command: &[&str] = match self.manager {
+            PackageManager::Flatpak => &["flatpak", "uninstall"],
+            PackageManager::Pacman => &["sudo", "pacman", "-Rs"],
+            PackageManager::Dpkg => &["sudo", "apt", "remove"],
+            PackageManager::Rpm => &["sudo", "dnf", "remove"],
+        };
+        self.command(command)
+    }
+
+    pub fn info_command(&self) -> Vec<String> {
+        let command: &[&str] = match self.manager {
+            PackageManager::Flatpak => &["flatpak", "info"],
+            PackageManager::Pacman => &["pacman", "-Qi"],
+            PackageManager::Dpkg => &["apt", "show"],
+            PackageManager::Rpm => &["rpm", "-qi"],
+        };
+        self.command(command)
+    }
+
+    fn command(&self, command: &[&str]) -> Vec<String> {
+        let mut command: Vec<String> = command.iter().map(|v| v.to_string()).collect();
+        command.push(self.name.clone());
+        command
+    }
+}
@@ -0,0 +1,178 @@
+//! Calls into the XDG desktop portals, which also work from inside sandboxes.
+
+use crate::paths::file_uri_path;
+use eyre::{bail, Context, ContextCompat};
+use std::collections::HashMap;
+use std::fs::File;
+use std::os::fd::AsFd;
+use std::path::PathBuf;
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{DynamicType, Fd, OwnedValue, Value};
+
+const DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const PATH: &str = "/org/freedesktop/portal/desktop";
+const OPEN_URI: &str = "org.freedesktop.portal.OpenURI";
+const SCREENSHOT: &str = "org.freedesktop.portal.Screenshot";
+const REQUEST: &str = "org.freedesktop.portal.Request";
+const SETTINGS: &str = "org.freedesktop.portal.Settings";
+
+/// The value of `color-scheme` for a dark style, 2 is light and 0 no preference.
+pub const COLOR_SCHEME_DARK: u32 = 1;
+
+/// Opens `uri` through the OpenURI portal, local files go through `OpenFile`
+/// since the portal doesn't accept `file://` URIs.
+pub fn open_uri(uri: &str) -> eyre::Result<()> {
+    let connection = Connection::session().wrap_err("Failed to connect to the session bus")?;
+
+    let local_path = file_uri_path(uri).unwrap_or_else(|| PathBuf::from(uri));
+    let (code, _) = if local_path.is_absolute() {
+        let file = File::open(&local_path).wrap_err("Failed to open file")?;
+        request(&connection, OPEN_URI, "OpenFile", |options| {
+            ("", Fd::from(file.as_fd()), options)
+        })?
+    } else {
+        request(&connection, OPEN_URI, "OpenURI", |options| {
+            ("", uri, options)
+        })?
+    };
+    // Closing the app chooser isn't a failure, and shouldn't fall back to xdg-open either.
+    match code {
+        0 | 1 => Ok(()),
+        _ => bail!("The portal failed to open {uri:?} with {code}"),
+    }
+}
+
+/// The preferred color scheme of the desktop, see [COLOR_SCHEME_DARK].
+pub fn color_scheme() -> eyre::Result<u32> {
+    let connection = Connection::session().wrap_err("Failed to connect to the session bus")?;
+    let reply = connection
+        .call_method(
+            Some(DESTINATION),
+            PATH,
+            Some(SETTINGS),
+            "ReadOne",
+            &("org.freedesktop.appearance", "color-scheme"),
+        )
+        .wrap_err("ReadOne failed")?;
+    let value: OwnedValue = reply
+        .body()
+        .deserialize()
+        .wrap_err("Failed to read the reply")?;
+    match &*value {
+        Value::U32(scheme) => Ok(*scheme),
+        _ => bail!("The portal returned an invalid color scheme"),
+    }
+}
+
+/// The icon theme of GTK desktops, which the portal reads from their settings over D-Bus
+/// instead of us running `gsettings`.
+pub fn icon_theme() -> eyre::Result<String> {
+    let connection = Connection::session().wrap_err("Failed to connect to the session bus")?;
+    let reply = connection
+        .call_method(
+            Some(DESTINATION),
+            PATH,
+            Some(SETTINGS),
+            "ReadOne",
+            &("org.gnome.desktop.interface", "icon-theme"),
+        )
+        .wrap_err("ReadOne failed")?;
+    let value: OwnedValue = reply
+        .body()
+        .deserialize()
+        .wrap_err("Failed to read the reply")?;
+    match &*value {
+        Value::Str(theme) => Ok(theme.to_string()),
+        _ => bail!("The portal returned an invalid icon theme"),
+    }
+}
+
+/// Takes a screenshot, `interactive` lets the user pick a region first.
+/// Returns the file the portal saved it to.
+pub fn screenshot(interactive: bool) -> eyre::Result<PathBuf> {
+    let connection = Connection::session().wrap_err("Failed to connect to the session bus")?;
+    let options = HashMap::from([("interactive", Value::from(interactive))]);
+    let results = request_results(&connection, SCREENSHOT, "Screenshot", options)?;
+    let uri = match results.get("uri").map(|v| &**v) {
+        Some(Value::Str(uri)) => uri.to_string(),
+        _ => bail!("The portal returned no screenshot"),
+    };
+    file_uri_path(&uri).wrap_err_with(|| format!("The screenshot {uri:?} is not a local file"))
+}
+
+/// Lets the user pick a color on the screen, as red, green and blue from 0 to 1.
+pub fn pick_color() -> eyre::Result<(f64, f64, f64)> {
+    let connection = Connection::session().wrap_err("Failed to connect to the session bus")?;
+    let results = request_results(&connection, SCREENSHOT, "PickColor", HashMap::new())?;
+    let Some(Value::Structure(color)) = results.get("color").map(|v| &**v) else {
+        bail!("The portal returned no color");
+    };
+    match color.fields() {
+        [Value::F64(red), Value::F64(green), Value::F64(blue)] => Ok((*red, *green, *blue)),
+        _ => bail!("The portal returned an invalid color"),
+    }
+}
+
+/// Calls a portal method that takes only options, and returns its results if it succeeded.
+fn request_results(
+    connection: &Connection,
+    interface: &str,
+    method: &str,
+    options: HashMap<&str, Value>,
+) -> eyre::Result<HashMap<String, OwnedValue>> {
+    let (code, results) = request(connection, interface, method, |mut extra| {
+        extra.extend(options);
+        ("", extra)
+    })?;
+    match code {
+        0 => Ok(results),
+        1 => bail!("Cancelled"),
+        _ => bail!("{method} failed with {code}"),
+    }
+}
+
+/// Calls a portal method that answers later with a `Response` signal, and waits for it.
+/// `body` builds the arguments around the options, which carry our request token.
+/// Returns the response code, 0 for success and 1 if the user cancelled, with the results.
+fn request<'a, B>(
+    connection: &Connection,
+    interface: &str,
+    method: &str,
+    body: impl FnOnce(HashMap<&'a str, Value<'a>>) -> B,
+) -> eyre::Result<(u32, HashMap<String, OwnedValue>)>
+where
+    B: serde::Serialize + DynamicType,
+{
+    // The request path is known up front, so we listen before the answer can arrive.
+    let token = format!("ignition{}", std::process::id());
+    let sender = connection
+        .unique_name()
+        .wrap_err("The session bus gave us no name")?
+        .trim_start_matches(':')
+        .replace('.', "_");
+    let path = format!("{PATH}/request/{sender}/{token}");
+    let request = Proxy::new(connection, DESTINATION, path.as_str(), REQUEST)
+        .wrap_err("Failed to create the request")?;
+    let mut responses = request
+        .receive_signal("Response")
+        .wrap_err("Failed to listen for the response")?;
+
+    let options = HashMap::from([("handle_token", Value::from(token))]);
+    connection
+        .call_method(
+            Some(DESTINATION),
+            PATH,
+            Some(interface),
+            method,
+            &body(options),
+        )
+        .wrap_err_with(|| format!("{method} failed"))?;
+
+    let response = responses
+        .next()
+        .wrap_err("The portal closed without answering")?;
+    response
+        .body()
+        .deserialize()
+        .wrap_err("Failed to read the response")
+}
@@ -0,0 +1,152 @@
+//! The client side of `org.gnome.Shell.SearchProvider2`, which apps like Files, Calculator
+//! and Characters implement to show their results in the GNOME Shell overview.
+
+use crate::platform::data_dirs;
+use crate::providers::{Provider, ProviderResult};
+use eyre::{Context, ContextCompat};
+use ini::Ini;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::thread;
+use tracing::{debug, warn};
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{OwnedValue, Value};
+
+const INTERFACE: &str = "org.gnome.Shell.SearchProvider2";
+
+/// The providers in `gnome-shell/search-providers` of the data dirs, a file hides those
+/// with the same name in later dirs. Providers disabled by default are left out.
+pub fn providers() -> eyre::Result<Vec<Provider>> {
+    let mut providers = Vec::new();
+    let mut seen = HashSet::new();
+    for dir in data_dirs().wrap_err("Failed to find the data dirs")? {
+        let Ok(entries) = fs::read_dir(dir.join("gnome-shell").join("search-providers")) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.extension().is_some_and(|v| v == "ini") || !seen.insert(entry.file_name()) {
+                continue;
+            }
+            match parse_provider(&path) {
+                Ok(Some(provider)) => providers.push(provider),
+                Ok(None) => {}
+                Err(error) => warn!("Could not read the search provider {path:?}: {error:?}"),
+            }
+        }
+    }
+    Ok(providers)
+}
+
+fn parse_provider(path: &Path) -> eyre::Result<Option<Provider>> {
+    let info = Ini::load_from_file(path).wrap_err("failed to parse ini")?;
+    let section = info
+        .section(Some("Shell Search Provider"))
+        .wrap_err("No [Shell Search Provider] section")?;
+    if section.get("Version") != Some("2") || section.get("DefaultDisabled") == Some("true") {
+        return Ok(None);
+    }
+    let get = |key: &str| {
+        section
+            .get(key)
+            .map(String::from)
+            .wrap_err_with(|| format!("No {key}"))
+    };
+    Ok(Some(Provider {
+        desktop_id: get("DesktopId")?,
+        bus_name: get("BusName")?,
+        object_path: get("ObjectPath")?,
+    }))
+}
+
+/// Asks all `providers` at once for the results of `terms`, at most `max_results` of each.
+/// Providers that fail are left out.
+pub fn search(
+    providers: &[Provider],
+    terms: &[String],
+    max_results: usize,
+) -> eyre::Result<Vec<ProviderResult>> {
+    let connection = Connection::session().wrap_err("Failed to connect to the session bus")?;
+    let mut results = Vec::new();
+    thread::scope(|scope| {
+        let searches: Vec<_> = providers
+            .iter()
+            .map(|provider| {
+                let connection = &connection;
+                scope.spawn(move || {
+                    let found = search_provider(connection, provider, terms, max_results);
+                    (provider, found)
+                })
+            })
+            .collect();
+        for search in searches {
+            match search.join() {
+                Ok((_, Ok(mut found))) => results.append(&mut found),
+                Ok((provider, Err(error))) => {
+                    debug!("Search provider {} failed: {error:?}", provider.desktop_id)
+                }
+                Err(_) => warn!("A search provider thread panicked"),
+            }
+        }
+    });
+    Ok(results)
+}
+
+fn search_provider(
+    connection: &Connection,
+    provider: &Provider,
+    terms: &[String],
+    max_results: usize,
+) -> eyre::Result<Vec<ProviderResult>> {
+    let proxy = Proxy::new(
+        connection,
+        provider.bus_name.as_str(),
+        provider.object_path.as_str(),
+        INTERFACE,
+    )
+    .wrap_err("Failed to reach the provider")?;
+    let mut ids: Vec<String> = proxy
+        .call("GetInitialResultSet", &(terms,))
+        .wrap_err("GetInitialResultSet failed")?;
+    ids.truncate(max_results);
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let metas: Vec<HashMap<String, OwnedValue>> = proxy
+        .call("GetResultMetas", &(&ids,))
+        .wrap_err("GetResultMetas failed")?;
+    Ok(metas
+        .into_iter()
+        .filter_map(|meta| {
+            let text = |key: &str| match meta.get(key).map(|v| &**v) {
+                Some(Value::Str(text)) if !text.is_empty() => Some(text.to_string()),
+                _ => None,
+            };
+            Some(ProviderResult {
+                provider: provider.clone(),
+                id: text("id")?,
+                name: text("name")?,
+                description: text("description"),
+                terms: terms.to_vec(),
+            })
+        })
+        .collect())
+}
+
+/// Lets the provider open its result, like Files opening a file.
+pub fn activate(result: &ProviderResult) -> eyre::Result<()> {
+    let connection = Connection::session().wrap_err("Failed to connect to the session bus")?;
+    let proxy = Proxy::new(
+        &connection,
+        result.provider.bus_name.as_str(),
+        result.provider.object_path.as_str(),
+        INTERFACE,
+    )
+    .wrap_err("Failed to reach the provider")?;
+    // No timestamp, we are closed by now.
+    proxy
+        .call::<_, _, ()>("ActivateResult", &(&result.id, &result.terms, 0u32))
+        .wrap_err("ActivateResult failed")
+}
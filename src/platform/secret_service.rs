@@ -0,0 +1,67 @@
+//! Reads the keyring of the desktop (GNOME Keyring, KWallet) over the Secret Service API.
+
+use eyre::{bail, Context};
+use std::collections::HashMap;
+use tracing::warn;
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+
+const DESTINATION: &str = "org.freedesktop.secrets";
+const PATH: &str = "/org/freedesktop/secrets";
+const SERVICE: &str = "org.freedesktop.Secret.Service";
+const ITEM: &str = "org.freedesktop.Secret.Item";
+const SESSION: &str = "org.freedesktop.Secret.Session";
+
+/// The unlocked items by their object path and label, locked collections are left out.
+pub fn items() -> eyre::Result<Vec<(String, String)>> {
+    let connection = Connection::session().wrap_err("Failed to connect to the session bus")?;
+    let service = Proxy::new(&connection, DESTINATION, PATH, SERVICE)
+        .wrap_err("Failed to reach the Secret Service")?;
+    // No attributes match every item.
+    let attributes: HashMap<&str, &str> = HashMap::new();
+    let (unlocked, _locked): (Vec<OwnedObjectPath>, Vec<OwnedObjectPath>) = service
+        .call("SearchItems", &(attributes,))
+        .wrap_err("SearchItems failed")?;
+
+    let mut items = Vec::new();
+    for path in unlocked {
+        let item = Proxy::new(&connection, DESTINATION, path.clone(), ITEM)
+            .wrap_err("Failed to reach the item")?;
+        let label: String = item
+            .get_property("Label")
+            .wrap_err("Failed to read the label")?;
+        if !label.is_empty() {
+            items.push((path.to_string(), label));
+        }
+    }
+    Ok(items)
+}
+
+/// The secret of the item at `path`.
+pub fn secret(path: &str) -> eyre::Result<String> {
+    let connection = Connection::session().wrap_err("Failed to connect to the session bus")?;
+    let service = Proxy::new(&connection, DESTINATION, PATH, SERVICE)
+        .wrap_err("Failed to reach the Secret Service")?;
+    // The secret only travels over the session bus, which is private to the user.
+    let (_output, session): (OwnedValue, OwnedObjectPath) = service
+        .call("OpenSession", &("plain", Value::from("")))
+        .wrap_err("OpenSession failed")?;
+
+    let item =
+        Proxy::new(&connection, DESTINATION, path, ITEM).wrap_err("Failed to reach the item")?;
+    let secret: eyre::Result<(OwnedObjectPath, Vec<u8>, Vec<u8>, String)> = item
+        .call("GetSecret", &(&session,))
+        .wrap_err("GetSecret failed");
+
+    let closed = Proxy::new(&connection, DESTINATION, session.clone(), SESSION)
+        .and_then(|session| session.call::<_, _, ()>("Close", &()));
+    if let Err(error) = closed {
+        warn!("Could not close the Secret Service session: {error:?}");
+    }
+
+    let (_session, _parameters, value, _content_type) = secret?;
+    match String::from_utf8(value) {
+        Ok(secret) => Ok(secret),
+        Err(_) => bail!("The secret is not text"),
+    }
+}
@@ -0,0 +1,92 @@
+//! Quick settings of GNOME and desktops sharing its schemas, through gsettings and D-Bus.
+
+use crate::platform::{flatpak, portal, Toggle};
+use eyre::Context;
+use std::env;
+use std::process::Stdio;
+use tracing::debug;
+use zbus::blocking::{Connection, Proxy};
+
+const COLOR: &str = "org.gnome.settings-daemon.plugins.color";
+const NOTIFICATIONS: &str = "org.gnome.desktop.notifications";
+const INTERFACE: &str = "org.gnome.desktop.interface";
+const RFKILL: &str = "org.gnome.SettingsDaemon.Rfkill";
+const RFKILL_PATH: &str = "/org/gnome/SettingsDaemon/Rfkill";
+
+pub fn toggle_state(toggle: Toggle) -> eyre::Result<bool> {
+    match toggle {
+        Toggle::NightLight => Ok(gsettings(&["get", COLOR, "night-light-enabled"])? == "true"),
+        Toggle::DoNotDisturb => Ok(gsettings(&["get", NOTIFICATIONS, "show-banners"])? == "false"),
+        Toggle::DarkMode if !follows_gnome_interface() => {
+            eyre::bail!("Only GNOME's dark mode can be changed")
+        }
+        Toggle::DarkMode => match portal::color_scheme() {
+            // The portal also knows the color scheme of KDE and others.
+            Ok(scheme) => Ok(scheme == portal::COLOR_SCHEME_DARK),
+            Err(error) => {
+                debug!("Could not read the color scheme from the portal: {error:?}");
+                Ok(gsettings(&["get", INTERFACE, "color-scheme"])? == "'prefer-dark'")
+            }
+        },
+        Toggle::AirplaneMode => rfkill(|rfkill| rfkill.get_property("AirplaneMode")),
+    }
+}
+
+/// Sets `toggle` and reads it back, since setting a key nobody listens to still succeeds.
+pub fn set_toggle(toggle: Toggle, on: bool) -> eyre::Result<()> {
+    write_toggle(toggle, on)?;
+    if toggle_state(toggle)? != on {
+        eyre::bail!("The desktop didn't take the change of {toggle:?}");
+    }
+    Ok(())
+}
+
+fn write_toggle(toggle: Toggle, on: bool) -> eyre::Result<()> {
+    let flag = |value: bool| if value { "true" } else { "false" };
+    match toggle {
+        Toggle::NightLight => gsettings(&["set", COLOR, "night-light-enabled", flag(on)])?,
+        Toggle::DoNotDisturb => gsettings(&["set", NOTIFICATIONS, "show-banners", flag(!on)])?,
+        Toggle::DarkMode => {
+            if !follows_gnome_interface() {
+                eyre::bail!("Only GNOME's dark mode can be changed");
+            }
+            let scheme = if on { "prefer-dark" } else { "default" };
+            gsettings(&["set", INTERFACE, "color-scheme", scheme])?
+        }
+        Toggle::AirplaneMode => {
+            return rfkill(|rfkill| Ok(rfkill.set_property("AirplaneMode", on)?));
+        }
+    };
+    Ok(())
+}
+
+/// Whether the desktop follows `color-scheme` of the GNOME interface schema. Others, like
+/// Plasma, tell the portal their own setting, which gsettings doesn't change.
+fn follows_gnome_interface() -> bool {
+    let desktop = env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+    desktop.split(':').any(|name| {
+        ["GNOME", "Unity", "Budgie", "Pantheon"]
+            .iter()
+            .any(|v| name.eq_ignore_ascii_case(v))
+    })
+}
+
+/// Runs gsettings and returns what it printed, the value for `get`.
+fn gsettings(args: &[&str]) -> eyre::Result<String> {
+    let output = flatpak::host_command("gsettings")
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .wrap_err("Failed to run gsettings")?;
+    if !output.status.success() {
+        eyre::bail!("gsettings {} exited with {}", args.join(" "), output.status);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn rfkill<T>(f: impl FnOnce(&Proxy) -> zbus::Result<T>) -> eyre::Result<T> {
+    let connection = Connection::session().wrap_err("Failed to connect to the session bus")?;
+    let proxy = Proxy::new(&connection, RFKILL, RFKILL_PATH, RFKILL)
+        .wrap_err("Failed to reach the settings daemon")?;
+    f(&proxy).wrap_err("Failed to use the airplane mode of the settings daemon")
+}
@@ -0,0 +1,456 @@
+use crate::apps::{cache, App, AppId, AppSource};
+use crate::platform::{
+    parse_uri_list, pipe_to, sanitize_env, spawn_detached, AppLaunch, AutostartEntry, Capture,
+    Launcher, Monitor, Toggle,
+};
+use eframe::egui::{Pos2, Vec2};
+use eyre::{Context, ContextCompat};
+use lnk::ShellLink;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::env;
+use std::ffi::OsStr;
+use std::os::windows::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use std::mem::{size_of, zeroed};
+use std::ptr::{null, null_mut};
+use tracing::{debug, warn};
+use windows_sys::Win32::Foundation::{BOOL, LPARAM, POINT, RECT};
+use windows_sys::Win32::Graphics::Gdi::{
+    EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFO, MONITORINFOEXW,
+};
+use windows_sys::Win32::UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+use windows_sys::Win32::UI::Shell::ShellExecuteW;
+use windows_sys::Win32::UI::WindowsAndMessaging::{GetCursorPos, SW_SHOWNORMAL};
+
+/// Get-StartApps takes a while, so its results are reused for a bit.
+const START_APPS_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Finds the Start Menu shortcuts, shortcuts in `extra_dirs` and the installed UWP/AppX packages.
+pub fn find_applications(extra_dirs: &[PathBuf]) -> eyre::Result<Vec<App>> {
+    let mut applications = Vec::new();
+
+    for dir in start_menu_dirs().iter().chain(extra_dirs) {
+        let mut shortcuts = Vec::new();
+        find_shortcuts(dir, &mut shortcuts);
+        for shortcut in shortcuts {
+            match parse_shortcut(&shortcut) {
+                Ok(Some(app)) => applications.push(app),
+                Ok(None) => {}
+                Err(error) => warn!("Could not read shortcut {shortcut:?}: {error:?}"),
+            }
+        }
+    }
+
+    match cache::cached("start_apps", START_APPS_TTL, find_packaged_apps) {
+        Ok(apps) => applications.extend(apps),
+        Err(error) => warn!("Could not list packaged apps: {error:?}"),
+    }
+
+    Ok(applications)
+}
+
+/// The dirs of the shortcuts [find_applications] reads, for watching them.
+pub fn watch_dirs(extra_dirs: &[PathBuf]) -> Vec<PathBuf> {
+    start_menu_dirs()
+        .into_iter()
+        .chain(extra_dirs.iter().cloned())
+        .collect()
+}
+
+/// Shortcuts, changes to other files don't change the apps.
+pub const APP_EXTENSIONS: &[&str] = &["lnk"];
+
+/// cmd.exe is opened when no terminal is set.
+pub fn default_terminal() -> Option<String> {
+    None
+}
+
+/// The files copied in Explorer, or the text on the clipboard.
+pub fn clipboard_uris() -> eyre::Result<Vec<String>> {
+    let output = Command::new("powershell.exe")
+        .args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "$files = Get-Clipboard -Format FileDropList; \
+             if ($files) { $files.FullName } else { Get-Clipboard }",
+        ])
+        .output()
+        .wrap_err("Failed to run powershell")?;
+    parse_uri_list(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Puts `text` on the clipboard through PowerShell, empty text clears it.
+pub fn set_clipboard_text(text: &str) -> eyre::Result<()> {
+    let mut command = Command::new("powershell.exe");
+    command.args([
+        "-NoProfile",
+        "-NonInteractive",
+        "-Command",
+        "$text = [Console]::In.ReadToEnd(); \
+         if ($text) { Set-Clipboard -Value $text } else { Set-Clipboard -Value $null }",
+    ]);
+    pipe_to(&mut command, text)
+}
+
+pub fn clipboard_text() -> eyre::Result<String> {
+    let output = Command::new("powershell.exe")
+        .args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "Get-Clipboard -Raw",
+        ])
+        .output()
+        .wrap_err("Failed to run powershell")?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    Ok(text.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Opens the Snipping Tool overlay, which copies the region itself.
+pub fn capture(kind: Capture) -> eyre::Result<()> {
+    match kind {
+        Capture::Region => shell_execute(OsStr::new("ms-screenclip:"), None),
+        _ => eyre::bail!("Only screenshots of a region are supported on Windows"),
+    }
+}
+
+/// There are no quick settings to toggle on Windows yet.
+pub fn toggle_state(_toggle: Toggle) -> eyre::Result<bool> {
+    eyre::bail!("Quick settings aren't supported on Windows")
+}
+
+pub fn set_toggle(_toggle: Toggle, _on: bool) -> eyre::Result<()> {
+    eyre::bail!("Quick settings aren't supported on Windows")
+}
+
+/// Login items aren't managed on Windows yet.
+pub fn autostart_entries() -> eyre::Result<Vec<AutostartEntry>> {
+    eyre::bail!("Autostart isn't supported on Windows")
+}
+
+pub fn set_autostart(_id: &str, _enabled: bool) -> eyre::Result<()> {
+    eyre::bail!("Autostart isn't supported on Windows")
+}
+
+pub fn add_to_autostart(_path: &Path) -> eyre::Result<PathBuf> {
+    eyre::bail!("Autostart isn't supported on Windows")
+}
+
+/// Apps have their icons in their bundle or executable on Windows, there are no themes.
+pub fn icon_theme() -> Option<String> {
+    None
+}
+
+/// DWM always composites since Windows 8.
+/// Nothing draws a window before the real one outside of X11.
+pub struct Placeholder;
+
+pub fn open_placeholder(
+    _position: Pos2,
+    _size: Vec2,
+    _border_width: u32,
+    _background: u32,
+    _border: u32,
+) -> eyre::Result<Option<Placeholder>> {
+    Ok(None)
+}
+
+pub fn supports_transparency() -> bool {
+    true
+}
+
+/// System fonts are only found through fontconfig, on Linux.
+pub fn find_font(_family: &str, _bold: bool) -> Option<(PathBuf, u32)> {
+    None
+}
+
+/// There is no `WM_CLASS` to match apps against on Windows.
+pub fn window_classes() -> HashSet<String> {
+    HashSet::new()
+}
+
+/// Apps are started through the shell, which doesn't give us their process.
+pub fn process_start(_pid: u32) -> Option<u64> {
+    None
+}
+
+/// Windows has no simple way to show a notification, it is only logged.
+pub fn notify(summary: &str, body: &str, _icon: Option<&str>) {
+    debug!("Not showing notification {summary}: {body}");
+}
+
+/// Launches shortcuts and `shell:AppsFolder` paths through `ShellExecuteW`,
+/// which resolves them the same way the Start Menu does.
+pub struct PlatformLauncher;
+
+impl Launcher for PlatformLauncher {
+    fn launch(&self, request: &AppLaunch) -> eyre::Result<Option<u32>> {
+        if !request.wrapper.is_empty() || !request.env.is_empty() || request.login_env {
+            warn!("Launch wrappers and environments aren't supported on Windows, ignoring them");
+        }
+        let parameters: Vec<String> = request.args.iter().map(|v| format!("\"{v}\"")).collect();
+        shell_execute(
+            request.path.as_os_str(),
+            Some(OsStr::new(&parameters.join(" "))),
+        )
+        .map(|_| None)
+    }
+
+    fn open_uri(&self, uri: &str) -> eyre::Result<()> {
+        shell_execute(OsStr::new(uri), None)
+    }
+
+    fn open_terminal(&self, command: Option<&str>, dir: &Path, run: &[String]) -> eyre::Result<()> {
+        if !run.is_empty() {
+            warn!("Running a program in the terminal isn't supported on Windows, ignoring it");
+        }
+        let mut command = command.unwrap_or("cmd.exe").split_whitespace();
+        let program = command.next().wrap_err("The terminal command is empty")?;
+        let mut terminal = Command::new(program);
+        sanitize_env(&mut terminal);
+        spawn_detached(terminal.args(command).current_dir(dir)).map(|_| ())
+    }
+
+    fn run_command(&self, command: &[String], dir: &Path) -> eyre::Result<()> {
+        let (program, args) = command.split_first().wrap_err("The command is empty")?;
+        let mut command = Command::new(program);
+        sanitize_env(&mut command);
+        spawn_detached(command.args(args).current_dir(dir)).map(|_| ())
+    }
+}
+
+fn shell_execute(file: &OsStr, parameters: Option<&OsStr>) -> eyre::Result<()> {
+    let file = wide(file);
+    let operation = wide(OsStr::new("open"));
+    let parameters = parameters.filter(|v| !v.is_empty()).map(wide);
+
+    // SAFETY: the strings are nul-terminated and outlive the call.
+    let result = unsafe {
+        ShellExecuteW(
+            null_mut(),
+            operation.as_ptr(),
+            file.as_ptr(),
+            parameters.as_ref().map_or(null(), |v| v.as_ptr()),
+            null(),
+            SW_SHOWNORMAL,
+        )
+    };
+    // Anything above 32 is success, the rest are legacy error codes.
+    if result as isize <= 32 {
+        eyre::bail!("ShellExecuteW failed with {}", result as isize);
+    }
+    Ok(())
+}
+
+pub fn monitors() -> Vec<Monitor> {
+    unsafe extern "system" fn callback(
+        monitor: HMONITOR,
+        _: HDC,
+        _: *mut RECT,
+        data: LPARAM,
+    ) -> BOOL {
+        // SAFETY: `data` is the `Vec` passed to `EnumDisplayMonitors` below.
+        let monitors = &mut *(data as *mut Vec<Monitor>);
+        let mut info: MONITORINFOEXW = zeroed();
+        info.monitorInfo.cbSize = size_of::<MONITORINFOEXW>() as u32;
+        if GetMonitorInfoW(monitor, &mut info as *mut MONITORINFOEXW as *mut MONITORINFO) != 0 {
+            let rect = info.monitorInfo.rcMonitor;
+            let device = &info.szDevice;
+            let length = device.iter().position(|&c| c == 0).unwrap_or(device.len());
+            // Windows keeps the horizontal and vertical DPI the same.
+            let (mut dpi, mut dpi_y) = (96, 96);
+            GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi, &mut dpi_y);
+            monitors.push(Monitor {
+                name: String::from_utf16_lossy(&device[..length]),
+                x: rect.left,
+                y: rect.top,
+                width: (rect.right - rect.left) as u32,
+                height: (rect.bottom - rect.top) as u32,
+                scale: dpi as f32 / 96.0,
+            });
+        }
+        1
+    }
+
+    let mut monitors: Vec<Monitor> = Vec::new();
+    // SAFETY: the callback only runs during this call, while `monitors` is alive.
+    unsafe {
+        EnumDisplayMonitors(
+            null_mut(),
+            null(),
+            Some(callback),
+            &mut monitors as *mut Vec<Monitor> as LPARAM,
+        );
+    }
+    monitors
+}
+
+pub fn cursor_position() -> Option<(i32, i32)> {
+    let mut point = POINT { x: 0, y: 0 };
+    // SAFETY: `point` is a valid pointer for the duration of the call.
+    if unsafe { GetCursorPos(&mut point) } == 0 {
+        return None;
+    }
+    Some((point.x, point.y))
+}
+
+fn wide(string: &OsStr) -> Vec<u16> {
+    string.encode_wide().chain(Some(0)).collect()
+}
+
+fn start_menu_dirs() -> Vec<PathBuf> {
+    let programs = Path::new("Microsoft")
+        .join("Windows")
+        .join("Start Menu")
+        .join("Programs");
+    ["APPDATA", "PROGRAMDATA"]
+        .into_iter()
+        .filter_map(env::var_os)
+        .map(|dir| PathBuf::from(dir).join(&programs))
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+fn find_shortcuts(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = dir.read_dir() else {
+        return;
+    };
+    for entry in entries.filter_map(|v| v.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            find_shortcuts(&path, out);
+        } else if path
+            .extension()
+            .is_some_and(|v| v.eq_ignore_ascii_case("lnk"))
+        {
+            out.push(path);
+        }
+    }
+}
+
+fn parse_shortcut(path: &Path) -> eyre::Result<Option<App>> {
+    let Some(name) = path.file_stem().and_then(|v| v.to_str()) else {
+        return Ok(None);
+    };
+    // Every installer puts one of these next to the app, nobody wants to launch them.
+    if name.to_lowercase().contains("uninstall") {
+        return Ok(None);
+    }
+
+    let link = ShellLink::open(path).map_err(|error| eyre::eyre!("{error:?}"))?;
+    let target = link
+        .link_info()
+        .as_ref()
+        .and_then(|info| info.local_base_path().clone());
+    // Advertised (MSI) shortcuts have no target, explorer still knows how to launch them.
+    let exec = match (&target, link.arguments()) {
+        (Some(target), Some(arguments)) => format!("\"{target}\" {arguments}"),
+        (Some(target), None) => format!("\"{target}\""),
+        (None, _) => path.to_string_lossy().to_string(),
+    };
+    debug!("Found shortcut {name} -> {exec}");
+
+    let comment = link.name().clone();
+    let icon = shortcut_icon(&link, target.as_deref());
+    Ok(Some(App {
+        id: AppId::from_properties(&[name, &exec]),
+        path: path.to_path_buf(),
+        name: name.to_string(),
+        exec,
+        icon,
+        comment,
+        source: AppSource::StartMenu,
+        ..App::default()
+    }))
+}
+
+/// The icon of a shortcut, either its explicit icon location or the icon of its target.
+///
+/// Executables get a `path,index` suffix, the icon loader extracts that resource.
+fn shortcut_icon(link: &ShellLink, target: Option<&str>) -> Option<String> {
+    let (path, index) = match link.icon_location() {
+        Some(location) => (expand_env(location), link.header().icon_index()),
+        None => (target?.to_string(), 0),
+    };
+
+    let extension = Path::new(&path)
+        .extension()
+        .and_then(|v| v.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    if extension == "exe" || extension == "dll" {
+        Some(format!("{path},{index}"))
+    } else {
+        Some(path)
+    }
+}
+
+/// Expands `%VARIABLE%`s, shortcuts often point at `%SystemRoot%\system32\shell32.dll`.
+fn expand_env(string: &str) -> String {
+    let mut out = String::new();
+    let mut parts = string.split('%');
+    if let Some(first) = parts.next() {
+        out.push_str(first);
+    }
+    let mut is_variable = true;
+    for part in parts {
+        if is_variable {
+            if let Some(value) = env::var_os(part) {
+                out.push_str(&value.to_string_lossy());
+                // The next part is plain text again.
+                is_variable = false;
+                continue;
+            }
+            out.push('%');
+        }
+        out.push_str(part);
+        is_variable = true;
+    }
+    out
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct StartApp {
+    name: String,
+    #[serde(rename = "AppID")]
+    app_id: String,
+}
+
+/// Lists UWP/AppX packages, these don't have shortcuts we could read.
+fn find_packaged_apps() -> eyre::Result<Vec<App>> {
+    let output = Command::new("powershell.exe")
+        .args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            // A single app would be an object instead of an array without `@()`.
+            "ConvertTo-Json @(Get-StartApps)",
+        ])
+        .output()
+        .wrap_err("Failed to run powershell")?;
+    let start_apps: Vec<StartApp> =
+        serde_json::from_slice(&output.stdout).wrap_err("Failed to parse Get-StartApps")?;
+
+    Ok(start_apps
+        .into_iter()
+        // Packaged apps are identified by "<PackageFamilyName>!<App>", everything else
+        // already showed up as a shortcut.
+        .filter(|app| app.app_id.contains('!'))
+        .map(|app| {
+            let path = PathBuf::from(format!("shell:AppsFolder\\{}", app.app_id));
+            App {
+                id: AppId::from_properties(&[&app.name, &app.app_id]),
+                path,
+                exec: app.app_id,
+                name: app.name,
+                source: AppSource::StartApps,
+                ..App::default()
+            }
+        })
+        .collect())
+}
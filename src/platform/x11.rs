@@ -0,0 +1,325 @@
+//! X11 specific window handling, like the "grab" mode (see
+//! [crate::settings::WindowSettings::x11_grab]) and monitor placement.
+
+use eyre::{Context, ContextCompat};
+use raw_window_handle::{HasDisplayHandle, HasWindowHandle, RawDisplayHandle, RawWindowHandle};
+use std::collections::HashSet;
+use std::thread::sleep;
+use std::time::Duration;
+use tracing::{info, warn};
+use crate::platform::Monitor;
+use x11_dl::xlib_xcb::Xlib_xcb;
+use x11rb::connection::Connection;
+use x11rb::protocol::randr::ConnectionExt as RandrConnectionExt;
+use x11rb::resource_manager;
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ChangeWindowAttributesAux, ClientMessageEvent, ConnectionExt,
+    CreateWindowAux, EventMask, GrabMode, GrabStatus, InputFocus, Window, WindowClass,
+};
+use x11rb::rust_connection::RustConnection;
+use x11rb::xcb_ffi::XCBConnection;
+use x11rb::{COPY_DEPTH_FROM_PARENT, COPY_FROM_PARENT, CURRENT_TIME};
+
+/// Another client (like the hotkey daemon that started us) may still hold the keyboard.
+const GRAB_ATTEMPTS: usize = 20;
+
+/// Turns `window` into an override-redirect window and grabs the keyboard for it.
+///
+/// The X server sends grabbed keys to the client holding the grab, so all of it goes through
+/// winit's own connection, the one reading the events of the window.
+pub fn grab(frame: &eframe::Frame, window: u32) -> eyre::Result<()> {
+    let connection = winit_connection(frame)?;
+
+    // override-redirect only applies when the window gets mapped.
+    connection
+        .unmap_window(window)
+        .wrap_err("Failed to unmap window")?;
+    connection
+        .change_window_attributes(
+            window,
+            &ChangeWindowAttributesAux::new().override_redirect(1),
+        )
+        .wrap_err("Failed to set override-redirect")?;
+    connection.map_window(window).wrap_err("Failed to map window")?;
+    connection.flush().wrap_err("Failed to flush")?;
+
+    let mut grabbed = false;
+    for _ in 0..GRAB_ATTEMPTS {
+        let reply = connection
+            .grab_keyboard(true, window, CURRENT_TIME, GrabMode::ASYNC, GrabMode::ASYNC)
+            .wrap_err("Failed to grab keyboard")?
+            .reply()
+            .wrap_err("Failed to grab keyboard")?;
+        if reply.status == GrabStatus::SUCCESS {
+            grabbed = true;
+            break;
+        }
+        sleep(Duration::from_millis(10));
+    }
+    if !grabbed {
+        warn!("Could not grab the keyboard, someone else holds it");
+    }
+
+    // The window manager no longer gives us focus.
+    connection
+        .set_input_focus(InputFocus::PARENT, window, CURRENT_TIME)
+        .wrap_err("Failed to focus window")?;
+    connection.flush().wrap_err("Failed to flush")?;
+    info!("Grabbed the keyboard for window {window}");
+    Ok(())
+}
+
+/// The connection winit opened for `frame`, wrapped without taking it over: dropping it
+/// leaves the connection open.
+fn winit_connection(frame: &eframe::Frame) -> eyre::Result<XCBConnection> {
+    let handle = frame
+        .display_handle()
+        .wrap_err("Failed to get the display")?;
+    let raw = match handle.as_raw() {
+        RawDisplayHandle::Xcb(handle) => handle
+            .connection
+            .wrap_err("No X11 connection")?
+            .as_ptr(),
+        RawDisplayHandle::Xlib(handle) => {
+            let display = handle.display.wrap_err("No X11 display")?;
+            let xlib_xcb = Xlib_xcb::open().wrap_err("Failed to load libX11-xcb")?;
+            // SAFETY: the display is winit's and open for as long as the frame.
+            unsafe { (xlib_xcb.XGetXCBConnection)(display.as_ptr().cast()) }
+        }
+        _ => eyre::bail!("Not running on X11"),
+    };
+    // SAFETY: winit's connection outlives the frame, and with `false` it isn't closed on drop.
+    unsafe { XCBConnection::from_raw_xcb_connection(raw, false) }
+        .wrap_err("Failed to use the X11 connection")
+}
+
+/// The X11 window id of the eframe window, `None` when we are not running on X11.
+pub fn window_id(frame: &eframe::Frame) -> Option<u32> {
+    match frame.window_handle().ok()?.as_raw() {
+        RawWindowHandle::Xlib(handle) => Some(handle.window as u32),
+        RawWindowHandle::Xcb(handle) => Some(handle.window.get()),
+        _ => None,
+    }
+}
+
+/// The active monitors, from RandR.
+pub fn monitors() -> eyre::Result<Vec<Monitor>> {
+    let (connection, screen) = x11rb::connect(None).wrap_err("Failed to connect to X11")?;
+    let root = connection.setup().roots[screen].root;
+    let reply = connection
+        .randr_get_monitors(root, true)
+        .wrap_err("Failed to list monitors")?
+        .reply()
+        .wrap_err("Failed to list monitors")?;
+
+    let xft_scale = resource_manager::new_from_default(&connection)
+        .ok()
+        .and_then(|db| db.get_string("Xft.dpi", "")?.trim().parse::<f32>().ok())
+        .map(|dpi| dpi / 96.0);
+
+    let mut monitors = Vec::new();
+    for monitor in reply.monitors {
+        let name = connection
+            .get_atom_name(monitor.name)
+            .wrap_err("Failed to get monitor name")?
+            .reply()
+            .wrap_err("Failed to get monitor name")?
+            .name;
+        monitors.push(Monitor {
+            name: String::from_utf8_lossy(&name).to_string(),
+            x: monitor.x as i32,
+            y: monitor.y as i32,
+            width: monitor.width as u32,
+            height: monitor.height as u32,
+            scale: xft_scale.unwrap_or_else(|| {
+                let pixels = f32::from(monitor.width) * f32::from(monitor.height);
+                let millimeters = monitor.width_in_millimeters as f32
+                    * monitor.height_in_millimeters as f32;
+                physical_scale(pixels, millimeters)
+            }),
+        });
+    }
+    Ok(monitors)
+}
+
+/// The scale winit picks for a monitor from its size when `Xft.dpi` isn't set, in twelfths.
+fn physical_scale(pixels: f32, millimeters: f32) -> f32 {
+    if millimeters <= 0.0 {
+        return 1.0;
+    }
+    let per_millimeter = (pixels / millimeters).sqrt();
+    ((per_millimeter * (12.0 * 25.4 / 96.0)).round() / 12.0).max(1.0)
+}
+
+pub fn cursor_position() -> eyre::Result<(i32, i32)> {
+    let (connection, screen) = x11rb::connect(None).wrap_err("Failed to connect to X11")?;
+    let root = connection.setup().roots[screen].root;
+    let reply = connection
+        .query_pointer(root)
+        .wrap_err("Failed to query pointer")?
+        .reply()
+        .wrap_err("Failed to query pointer")?;
+    Ok((reply.root_x as i32, reply.root_y as i32))
+}
+
+fn atom(connection: &impl Connection, name: &str) -> eyre::Result<Atom> {
+    Ok(connection
+        .intern_atom(false, name.as_bytes())
+        .wrap_err("Failed to intern atom")?
+        .reply()
+        .wrap_err("Failed to intern atom")?
+        .atom)
+}
+
+/// The windows the window manager manages, from `_NET_CLIENT_LIST`.
+fn client_windows(connection: &impl Connection, root: Window) -> eyre::Result<Vec<Window>> {
+    let reply = connection
+        .get_property(
+            false,
+            root,
+            atom(connection, "_NET_CLIENT_LIST")?,
+            AtomEnum::WINDOW,
+            0,
+            u32::MAX,
+        )
+        .wrap_err("Failed to list windows")?
+        .reply()
+        .wrap_err("Failed to list windows")?;
+    Ok(reply.value32().map(|v| v.collect()).unwrap_or_default())
+}
+
+/// The instance and class names of `WM_CLASS` of all client windows, lowercased.
+pub fn window_classes() -> eyre::Result<HashSet<String>> {
+    let (connection, screen) = x11rb::connect(None).wrap_err("Failed to connect to X11")?;
+    let root = connection.setup().roots[screen].root;
+
+    let mut classes = HashSet::new();
+    for window in client_windows(&connection, root)? {
+        let reply = connection
+            .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 256)
+            .wrap_err("Failed to get window class")?
+            .reply()
+            .wrap_err("Failed to get window class")?;
+        // Two null terminated strings, the instance and the class.
+        for name in reply.value.split(|v| *v == 0).filter(|v| !v.is_empty()) {
+            classes.insert(String::from_utf8_lossy(name).to_lowercase());
+        }
+    }
+    Ok(classes)
+}
+
+/// Whether a compositing manager runs, without one transparent windows are drawn black.
+pub fn is_compositing() -> eyre::Result<bool> {
+    let (connection, screen) = x11rb::connect(None).wrap_err("Failed to connect to X11")?;
+    let selection = atom(&connection, &format!("_NET_WM_CM_S{screen}"))?;
+    let owner = connection
+        .get_selection_owner(selection)
+        .wrap_err("Failed to get the compositor")?
+        .reply()
+        .wrap_err("Failed to get the compositor")?
+        .owner;
+    Ok(owner != x11rb::NONE)
+}
+
+/// A window the X server fills with plain colors, shown while the real window sets up its
+/// GPU context. Closed when dropped.
+pub struct Placeholder {
+    connection: RustConnection,
+    window: Window,
+}
+
+/// Shows a [Placeholder] at `x`, `y` of `width` × `height` pixels, with a `border` of
+/// `border_width` around the `background`, colors as `0xRRGGBB`. It is override-redirect,
+/// so it shows up without waiting for the window manager and never takes the focus.
+pub fn show_placeholder(
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    border_width: u32,
+    background: u32,
+    border: u32,
+) -> eyre::Result<Placeholder> {
+    let (connection, screen) = x11rb::connect(None).wrap_err("Failed to connect to X11")?;
+    let root = connection.setup().roots[screen].root;
+    let window = connection
+        .generate_id()
+        .wrap_err("Failed to create the placeholder")?;
+    // The border is drawn outside of the window, so it covers the same area as the real one.
+    let inner = |length: u32| length.saturating_sub(2 * border_width).max(1) as u16;
+    let attributes = CreateWindowAux::new()
+        .background_pixel(background)
+        .border_pixel(border)
+        .override_redirect(1);
+    connection
+        .create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            x as i16,
+            y as i16,
+            inner(width),
+            inner(height),
+            border_width as u16,
+            WindowClass::INPUT_OUTPUT,
+            COPY_FROM_PARENT,
+            &attributes,
+        )
+        .wrap_err("Failed to create the placeholder")?;
+    connection
+        .map_window(window)
+        .wrap_err("Failed to show the placeholder")?;
+    connection
+        .flush()
+        .wrap_err("Failed to show the placeholder")?;
+    Ok(Placeholder { connection, window })
+}
+
+impl Drop for Placeholder {
+    fn drop(&mut self) {
+        let _ = self.connection.destroy_window(self.window);
+        let _ = self.connection.flush();
+    }
+}
+
+/// Asks the window manager to activate the first window of `pid`, `false` if it has none.
+pub fn focus_pid(pid: u32) -> eyre::Result<bool> {
+    let (connection, screen) = x11rb::connect(None).wrap_err("Failed to connect to X11")?;
+    let root = connection.setup().roots[screen].root;
+    let wm_pid = atom(&connection, "_NET_WM_PID")?;
+
+    let mut target = None;
+    for window in client_windows(&connection, root)? {
+        let reply = connection
+            .get_property(false, window, wm_pid, AtomEnum::CARDINAL, 0, 1)
+            .wrap_err("Failed to get window pid")?
+            .reply()
+            .wrap_err("Failed to get window pid")?;
+        if reply.value32().and_then(|mut v| v.next()) == Some(pid) {
+            target = Some(window);
+            break;
+        }
+    }
+    let Some(window) = target else {
+        return Ok(false);
+    };
+
+    // Source 2 means a pager, which window managers trust to change the focus.
+    let event = ClientMessageEvent::new(
+        32,
+        window,
+        atom(&connection, "_NET_ACTIVE_WINDOW")?,
+        [2, CURRENT_TIME, 0, 0, 0],
+    );
+    connection
+        .send_event(
+            false,
+            root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            event,
+        )
+        .wrap_err("Failed to activate window")?;
+    connection.flush().wrap_err("Failed to flush")?;
+    info!("Focused window {window} of pid {pid}");
+    Ok(true)
+}
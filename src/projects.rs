@@ -0,0 +1,257 @@
+//! Recent projects of VS Code and its forks, offered to open in the editor again.
+
+use crate::apps::AppId;
+use crate::paths::file_uri_path;
+use crate::platform::LaunchRequest;
+use crate::search::{starts_words, EntryAction, SearchResultEntry, SearchScore};
+use crate::settings::ProjectSettings;
+use crossbeam::channel::{bounded, Receiver, TryRecvError};
+use serde_json::Value;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::fs::{self, read_to_string};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::thread::spawn;
+use std::time::SystemTime;
+use tracing::{info, warn};
+
+/// How many projects a query shows.
+const MAX_MATCHES: usize = 3;
+/// How many recent projects are searched.
+const MAX_PROJECTS: usize = 100;
+
+#[derive(Clone, Debug)]
+pub struct Project {
+    pub name: String,
+    /// A folder or a `.code-workspace` file.
+    pub path: PathBuf,
+}
+
+/// The recent projects, read on a background thread since there can be many paths to check.
+#[derive(Default)]
+pub struct Projects {
+    projects: Vec<Project>,
+    /// The name of the editor, like `VSCodium`.
+    editor: String,
+    pending: Option<Receiver<Projects>>,
+}
+
+impl Projects {
+    pub fn load(settings: &ProjectSettings) -> Projects {
+        if !settings.enabled {
+            return Projects::default();
+        }
+        let settings = settings.clone();
+        let (sender, receiver) = bounded(1);
+        spawn(move || {
+            let _ = sender.send(find(&settings));
+        });
+        Projects {
+            pending: Some(receiver),
+            ..Projects::default()
+        }
+    }
+
+    /// Returns `true` once the projects arrived.
+    pub fn tick(&mut self) -> bool {
+        let Some(pending) = &self.pending else {
+            return false;
+        };
+        match pending.try_recv() {
+            Ok(projects) => {
+                *self = projects;
+                true
+            }
+            Err(TryRecvError::Empty) => false,
+            Err(TryRecvError::Disconnected) => {
+                self.pending = None;
+                false
+            }
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// The projects found by their name or path, newest first.
+    pub fn search(&self, query: &str) -> Vec<SearchResultEntry> {
+        self.projects
+            .iter()
+            .filter(|project| {
+                let path = project.path.to_string_lossy().replace(['/', '\\'], " ");
+                starts_words(&format!("{} {path}", project.name), query)
+            })
+            .take(MAX_MATCHES)
+            .map(|project| SearchResultEntry {
+                id: AppId::from_properties(&["project", &project.path.to_string_lossy()]),
+                score: SearchScore::default(),
+                detail: Some(project.path.display().to_string()),
+                broken: false,
+                action: Some(EntryAction::OpenProject {
+                    project: project.clone(),
+                    editor: self.editor.clone(),
+                }),
+                confirm: false,
+            })
+            .collect()
+    }
+}
+
+/// Reads the recent projects of the editor, it has to parse its storage and check every path.
+fn find(settings: &ProjectSettings) -> Projects {
+    let program = settings
+        .command
+        .split_whitespace()
+        .next()
+        .and_then(|v| Path::new(v).file_name())
+        .map(|v| v.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let known = known_editor(&program);
+    let Some(dir_name) = settings.config_dir.as_deref().or(known.map(|v| v.0)) else {
+        warn!("Unknown editor {program}, set [projects] config_dir to find its projects");
+        return Projects::default();
+    };
+    let Some(user_dir) = dirs::config_dir().map(|v| v.join(dir_name).join("User")) else {
+        return Projects::default();
+    };
+    // Not installed.
+    if !user_dir.is_dir() {
+        return Projects::default();
+    }
+
+    // Newer versions keep the recent list in a database, the workspace storage comes
+    // first since it's always up to date.
+    let mut uris = workspace_uris(&user_dir.join("workspaceStorage"));
+    uris.extend(storage_uris(
+        &user_dir.join("globalStorage").join("storage.json"),
+    ));
+    let mut seen = HashSet::new();
+    let projects: Vec<Project> = uris
+        .iter()
+        // Leaves out remote projects like `vscode-remote://`.
+        .filter_map(|uri| file_uri_path(uri))
+        .filter(|path| path.exists() && seen.insert(path.clone()))
+        .take(MAX_PROJECTS)
+        .filter_map(|path| {
+            // Folders keep dots in their name, workspaces lose `.code-workspace`.
+            let name = if path.is_dir() {
+                path.file_name()
+            } else {
+                path.file_stem()
+            };
+            let name = name?.to_string_lossy().to_string();
+            Some(Project { name, path })
+        })
+        .collect();
+
+    let editor = settings
+        .name
+        .clone()
+        .or(known.map(|v| v.1.to_string()))
+        .unwrap_or(program);
+    info!("Found {} recent projects of {editor}", projects.len());
+    Projects {
+        projects,
+        editor,
+        pending: None,
+    }
+}
+
+/// Runs the editor with the project.
+pub fn launch_request(project: &Project, settings: &ProjectSettings) -> LaunchRequest {
+    let mut command: Vec<String> = settings
+        .command
+        .split_whitespace()
+        .map(String::from)
+        .collect();
+    command.push(project.path.to_string_lossy().to_string());
+    let dir = match project.path.parent() {
+        Some(parent) if !project.path.is_dir() => parent.to_path_buf(),
+        _ => project.path.clone(),
+    };
+    LaunchRequest::Command { command, dir }
+}
+
+/// The directory in the config directory and the name of the editors we know, by command.
+fn known_editor(program: &str) -> Option<(&'static str, &'static str)> {
+    match program {
+        "code" => Some(("Code", "Code")),
+        "code-insiders" => Some(("Code - Insiders", "Code Insiders")),
+        "code-oss" => Some(("Code - OSS", "Code - OSS")),
+        "codium" => Some(("VSCodium", "VSCodium")),
+        "cursor" => Some(("Cursor", "Cursor")),
+        _ => None,
+    }
+}
+
+/// The folders and workspaces in the workspace storage, a directory with a `workspace.json`
+/// for each, last used first.
+fn workspace_uris(dir: &Path) -> Vec<String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) => {
+            if error.kind() != ErrorKind::NotFound {
+                warn!("Could not read the workspace storage {dir:?}: {error}");
+            }
+            return Vec::new();
+        }
+    };
+
+    let mut found: Vec<(SystemTime, String)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let text = read_to_string(path.join("workspace.json")).ok()?;
+            let json: Value = serde_json::from_str(&text).ok()?;
+            let uri = json.get("folder").or_else(|| json.get("workspace"))?;
+            // The state database is written while the project is open.
+            let used = fs::metadata(path.join("state.vscdb"))
+                .or_else(|_| entry.metadata())
+                .and_then(|v| v.modified())
+                .ok()?;
+            Some((used, uri.as_str()?.to_string()))
+        })
+        .collect();
+    found.sort_by_key(|(used, _)| Reverse(*used));
+    found.into_iter().map(|(_, uri)| uri).collect()
+}
+
+/// The recently opened folders and workspaces in `storage.json`, newest first. Only older
+/// versions keep them there.
+fn storage_uris(path: &Path) -> Vec<String> {
+    let text = match read_to_string(path) {
+        Ok(text) => text,
+        Err(error) => {
+            if error.kind() != ErrorKind::NotFound {
+                warn!("Could not read {path:?}: {error}");
+            }
+            return Vec::new();
+        }
+    };
+    let json: Value = match serde_json::from_str(&text) {
+        Ok(json) => json,
+        Err(error) => {
+            warn!("Could not parse {path:?}: {error}");
+            return Vec::new();
+        }
+    };
+
+    let entries = json
+        .pointer("/openedPathsList/entries")
+        .or_else(|| json.pointer("/openedPathsList/workspaces3"))
+        .and_then(Value::as_array);
+    entries
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let uri = entry
+                .get("folderUri")
+                .or_else(|| entry.pointer("/workspace/configPath"))
+                .or_else(|| entry.get("configURIPath"))
+                .unwrap_or(entry);
+            uri.as_str().map(String::from)
+        })
+        .collect()
+}
@@ -0,0 +1,186 @@
+//! Results of GNOME Shell search providers, like files from Files or characters from
+//! Characters, asked on a background thread as the query changes.
+
+use crate::apps::AppId;
+use crate::search::{EntryAction, SearchResultEntry, SearchScore};
+use crate::settings::SearchProviderSettings;
+use crossbeam::channel::{unbounded, Receiver, Sender};
+use std::thread::spawn;
+use tracing::{info, warn};
+
+/// Shorter queries aren't sent, every provider would answer them.
+const MIN_QUERY_LEN: usize = 3;
+
+/// A search provider from a `gnome-shell/search-providers/*.ini` file.
+#[derive(Clone, Debug)]
+pub struct Provider {
+    pub desktop_id: String,
+    pub bus_name: String,
+    pub object_path: String,
+}
+
+/// A result of a provider, opened by the provider with the terms it was found by.
+#[derive(Clone, Debug)]
+pub struct ProviderResult {
+    pub provider: Provider,
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub terms: Vec<String>,
+}
+
+struct Worker {
+    queries: Sender<String>,
+    results: Receiver<(String, Vec<ProviderResult>)>,
+}
+
+#[derive(Default)]
+pub struct ProviderSearch {
+    /// Started by the first query.
+    worker: Option<Worker>,
+    /// The query last sent to the worker.
+    asked: String,
+    /// The query `results` are for.
+    answered: String,
+    results: Vec<ProviderResult>,
+}
+
+impl ProviderSearch {
+    /// Returns `true` once the results of the last query arrived.
+    pub fn tick(&mut self) -> bool {
+        let Some(worker) = &self.worker else {
+            return false;
+        };
+        let mut arrived = false;
+        while let Ok((query, results)) = worker.results.try_recv() {
+            arrived = query == self.asked;
+            self.answered = query;
+            self.results = results;
+        }
+        arrived
+    }
+
+    pub fn is_loading(&self) -> bool {
+        !self.asked.is_empty() && self.asked != self.answered
+    }
+
+    /// The results for `query`, which are asked for the first time it's seen and are empty
+    /// until they arrive.
+    pub fn search(
+        &mut self,
+        query: &str,
+        settings: &SearchProviderSettings,
+    ) -> Vec<SearchResultEntry> {
+        let query = query.trim();
+        if !settings.enabled || query.chars().count() < MIN_QUERY_LEN {
+            self.asked.clear();
+            return Vec::new();
+        }
+        if query != self.asked {
+            let worker = self.worker.get_or_insert_with(|| start(settings));
+            if worker.queries.send(query.to_string()).is_err() {
+                warn!("The search provider thread stopped");
+                return Vec::new();
+            }
+            self.asked = query.to_string();
+        }
+        if query != self.answered {
+            return Vec::new();
+        }
+
+        self.results
+            .iter()
+            .map(|result| SearchResultEntry {
+                id: AppId::from_properties(&[
+                    "search-provider",
+                    &result.provider.desktop_id,
+                    &result.id,
+                ]),
+                score: SearchScore::default(),
+                detail: result.description.clone(),
+                broken: false,
+                action: Some(EntryAction::ProviderResult(result.clone())),
+                confirm: false,
+            })
+            .collect()
+    }
+}
+
+fn start(settings: &SearchProviderSettings) -> Worker {
+    let (queries, query_receiver) = unbounded::<String>();
+    let (result_sender, results) = unbounded();
+    let disabled = settings.disabled.clone();
+    let max_results = settings.max_results;
+    spawn(move || {
+        let mut providers = match find_providers() {
+            Ok(providers) => providers,
+            Err(error) => {
+                warn!("Could not find the search providers: {error:?}");
+                Vec::new()
+            }
+        };
+        providers.retain(|provider| !disabled.contains(&provider.desktop_id));
+        info!("Found {} search providers", providers.len());
+
+        while let Ok(mut query) = query_receiver.recv() {
+            // Only the last of the queries typed while the providers were busy matters.
+            while let Ok(newer) = query_receiver.try_recv() {
+                query = newer;
+            }
+            let terms: Vec<String> = query.split_whitespace().map(String::from).collect();
+            let found = match search_all(&providers, &terms, max_results) {
+                Ok(found) => found,
+                Err(error) => {
+                    warn!("Could not ask the search providers: {error:?}");
+                    Vec::new()
+                }
+            };
+            if result_sender.send((query, found)).is_err() {
+                break;
+            }
+        }
+    });
+    Worker { queries, results }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn find_providers() -> eyre::Result<Vec<Provider>> {
+    crate::platform::search_providers::providers()
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn search_all(
+    providers: &[Provider],
+    terms: &[String],
+    max_results: usize,
+) -> eyre::Result<Vec<ProviderResult>> {
+    if providers.is_empty() {
+        return Ok(Vec::new());
+    }
+    crate::platform::search_providers::search(providers, terms, max_results)
+}
+
+/// Lets the provider open `result`.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn activate(result: &ProviderResult) -> eyre::Result<()> {
+    crate::platform::search_providers::activate(result)
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn find_providers() -> eyre::Result<Vec<Provider>> {
+    Ok(Vec::new())
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn search_all(
+    _providers: &[Provider],
+    _terms: &[String],
+    _max_results: usize,
+) -> eyre::Result<Vec<ProviderResult>> {
+    Ok(Vec::new())
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+pub fn activate(_result: &ProviderResult) -> eyre::Result<()> {
+    eyre::bail!("Search providers only exist on Linux")
+}
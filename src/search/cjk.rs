@@ -0,0 +1,67 @@
+//! Chinese and Japanese names, found by typing their pinyin or romaji.
+//!
+//! Han characters always become pinyin, so Japanese kanji only match their Chinese reading.
+
+use pinyin::ToPinyin;
+use wana_kana::ConvertJapanese;
+
+fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3040}'..='\u{30ff}')
+}
+
+/// Whether `c` is a Chinese or Japanese character.
+pub fn is_cjk(c: char) -> bool {
+    is_kana(c)
+        || matches!(c, '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}' | '\u{f900}'..='\u{faff}')
+}
+
+/// The names `name` can be found by besides itself: its romanization split into syllables,
+/// like "wei xin" for 微信 and "fairu" for ファイル, and the pinyin initials ("wx").
+///
+/// Empty for names without Chinese or Japanese characters.
+pub fn search_names(name: &str) -> Vec<String> {
+    if !name.chars().any(is_cjk) {
+        return Vec::new();
+    }
+
+    let mut words = Vec::new();
+    let mut initials = String::new();
+    let mut kana = String::new();
+    let mut other = String::new();
+    let flush = |words: &mut Vec<String>, text: &mut String, romaji: bool| {
+        if !text.is_empty() {
+            words.push(if romaji {
+                text.to_romaji()
+            } else {
+                text.clone()
+            });
+            text.clear();
+        }
+    };
+    for c in name.chars() {
+        if is_kana(c) {
+            flush(&mut words, &mut other, false);
+            kana.push(c);
+            continue;
+        }
+        flush(&mut words, &mut kana, true);
+
+        if let Some(pinyin) = c.to_pinyin() {
+            flush(&mut words, &mut other, false);
+            words.push(pinyin.plain().to_string());
+            initials.push_str(pinyin.first_letter());
+        } else if c.is_whitespace() {
+            flush(&mut words, &mut other, false);
+        } else {
+            other.push(c);
+        }
+    }
+    flush(&mut words, &mut kana, true);
+    flush(&mut words, &mut other, false);
+
+    let mut names = vec![words.join(" ")];
+    if initials.chars().count() > 1 {
+        names.push(initials);
+    }
+    names
+}
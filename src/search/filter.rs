@@ -0,0 +1,188 @@
+//! Operators like `category:Games`, `keyword:editor`, `exec:python`, `!chromium` and
+//! `"image viewer"` in a query, which only let through apps they match.
+
+use crate::apps::App;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum QueryFilter {
+    /// Apps with a category starting with this, like `gam` or `games` for `Game`.
+    Category(String),
+    /// Apps with a keyword containing this.
+    Keyword(String),
+    /// Apps whose Exec line contains this.
+    Exec(String),
+    /// Apps whose name and keywords don't contain this, from `!term` or `-term`.
+    Exclude(String),
+    /// Apps whose name or comment contain this exactly, from `"some words"`.
+    Phrase(String),
+}
+
+/// Takes the `"quoted phrases"` out of `query`, a quote that isn't closed yet goes to the end.
+pub fn take_phrases(query: &str) -> (String, Vec<QueryFilter>) {
+    let mut rest = String::new();
+    let mut phrases = Vec::new();
+    for (i, part) in query.split('"').enumerate() {
+        if i % 2 == 0 {
+            rest.push_str(part);
+            continue;
+        }
+        let phrase = part.trim().to_lowercase();
+        if !phrase.is_empty() {
+            phrases.push(QueryFilter::Phrase(phrase));
+        }
+        rest.push(' ');
+    }
+    (rest, phrases)
+}
+
+impl QueryFilter {
+    /// Reads a word of the query, `None` when it isn't an operator. Operators without a
+    /// value, like `exec:` while it's typed, are `Some(None)` so they don't get searched.
+    pub fn parse(word: &str) -> Option<Option<QueryFilter>> {
+        if let Some(term) = word.strip_prefix(['!', '-']).filter(|v| !v.is_empty()) {
+            return Some(Some(QueryFilter::Exclude(term.to_lowercase())));
+        }
+        let (operator, value) = word.split_once(':')?;
+        let filter: fn(String) -> QueryFilter = match operator.to_lowercase().as_str() {
+            "category" | "cat" => QueryFilter::Category,
+            "keyword" | "kw" => QueryFilter::Keyword,
+            "exec" => QueryFilter::Exec,
+            _ => return None,
+        };
+        let value = value.to_lowercase();
+        Some((!value.is_empty()).then(|| filter(value)))
+    }
+
+    pub fn matches(&self, app: &App) -> bool {
+        match self {
+            QueryFilter::Category(value) => {
+                // Categories are singular, like `Game`, but people write `Games`.
+                let value = value
+                    .strip_suffix('s')
+                    .filter(|v| !v.is_empty())
+                    .unwrap_or(value);
+                app.categories
+                    .iter()
+                    .flatten()
+                    .any(|category| category.to_lowercase().starts_with(value))
+            }
+            QueryFilter::Keyword(value) => app
+                .keywords
+                .iter()
+                .flat_map(|keywords| keywords.split(';'))
+                .any(|keyword| keyword.to_lowercase().contains(value)),
+            QueryFilter::Exec(value) => app.exec.to_lowercase().contains(value),
+            QueryFilter::Exclude(value) => {
+                let keywords = app.keywords.as_deref().unwrap_or_default();
+                !app.name.to_lowercase().contains(value) && !keywords.to_lowercase().contains(value)
+            }
+            QueryFilter::Phrase(value) => {
+                let comment = app.comment.as_deref().unwrap_or_default();
+                app.name.to_lowercase().contains(value) || comment.to_lowercase().contains(value)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{take_phrases, QueryFilter};
+    use crate::apps::App;
+    use crate::search::SearchQuery;
+
+    fn app() -> App {
+        App {
+            name: "Lutris".to_string(),
+            exec: "/usr/bin/python3 /usr/bin/lutris %U".to_string(),
+            keywords: Some("gaming;wine;emulator;".to_string()),
+            categories: Some(vec!["Game".to_string(), "Emulator".to_string()]),
+            ..App::default()
+        }
+    }
+
+    #[test]
+    fn parses_operators() {
+        assert_eq!(
+            QueryFilter::parse("category:Games"),
+            Some(Some(QueryFilter::Category("games".to_string())))
+        );
+        assert_eq!(
+            QueryFilter::parse("kw:Editor"),
+            Some(Some(QueryFilter::Keyword("editor".to_string())))
+        );
+        assert_eq!(
+            QueryFilter::parse("EXEC:python"),
+            Some(Some(QueryFilter::Exec("python".to_string())))
+        );
+    }
+
+    #[test]
+    fn leaves_other_words() {
+        assert_eq!(QueryFilter::parse("firefox"), None);
+        assert_eq!(QueryFilter::parse("https://example.com"), None);
+        assert_eq!(QueryFilter::parse("exec:"), Some(None));
+        assert_eq!(QueryFilter::parse("-"), None);
+    }
+
+    #[test]
+    fn matches_apps() {
+        let app = app();
+        assert!(QueryFilter::Category("game".to_string()).matches(&app));
+        assert!(QueryFilter::Category("games".to_string()).matches(&app));
+        assert!(QueryFilter::Category("emu".to_string()).matches(&app));
+        assert!(!QueryFilter::Category("office".to_string()).matches(&app));
+        assert!(QueryFilter::Keyword("emul".to_string()).matches(&app));
+        assert!(!QueryFilter::Keyword("editor".to_string()).matches(&app));
+        assert!(QueryFilter::Exec("python".to_string()).matches(&app));
+        assert!(!QueryFilter::Exec("java".to_string()).matches(&app));
+    }
+
+    #[test]
+    fn excludes_terms() {
+        assert_eq!(
+            QueryFilter::parse("!Chromium"),
+            Some(Some(QueryFilter::Exclude("chromium".to_string())))
+        );
+        assert_eq!(
+            QueryFilter::parse("-wine"),
+            Some(Some(QueryFilter::Exclude("wine".to_string())))
+        );
+        let app = app();
+        assert!(!QueryFilter::Exclude("lutris".to_string()).matches(&app));
+        assert!(!QueryFilter::Exclude("wine".to_string()).matches(&app));
+        assert!(QueryFilter::Exclude("steam".to_string()).matches(&app));
+    }
+
+    #[test]
+    fn takes_phrases() {
+        let (rest, phrases) = take_phrases(r#"open "Image  Viewer" now"#);
+        assert_eq!(rest, "open   now");
+        assert_eq!(
+            phrases,
+            vec![QueryFilter::Phrase("image  viewer".to_string())]
+        );
+
+        let (rest, phrases) = take_phrases(r#"gtk "game lau"#);
+        assert_eq!(rest, "gtk  ");
+        assert_eq!(phrases, vec![QueryFilter::Phrase("game lau".to_string())]);
+
+        let mut app = app();
+        app.comment = Some("Open source gaming platform".to_string());
+        assert!(QueryFilter::Phrase("source gaming".to_string()).matches(&app));
+        assert!(!QueryFilter::Phrase("gaming source".to_string()).matches(&app));
+    }
+
+    #[test]
+    fn takes_operators_out_of_the_text() {
+        let query = SearchQuery::from("wine category:game  #fun exec: !steam".to_string(), &[]);
+        assert_eq!(query.text, "wine");
+        assert_eq!(query.tags, vec!["fun".to_string()]);
+        assert_eq!(
+            query.filters,
+            vec![
+                QueryFilter::Category("game".to_string()),
+                QueryFilter::Exclude("steam".to_string())
+            ]
+        );
+    }
+}
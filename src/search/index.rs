@@ -0,0 +1,59 @@
+//! What scoring needs of each app, prepared once when the apps are loaded instead of on
+//! every keystroke.
+
+use crate::apps::App;
+use crate::search::matcher::Folded;
+use crate::search::words;
+
+#[derive(Default)]
+pub struct AppIndex {
+    pub name: Folded,
+    /// See [App::search_names].
+    pub search_names: Vec<Folded>,
+    pub comment: Folded,
+    pub generic_name: Folded,
+    /// Split at `;`, with the empty part after the last one like the desktop entry has it.
+    pub keywords: Vec<Folded>,
+    /// The lowercase first letters of the words of the name.
+    pub initials: String,
+    /// The char index of each of the `initials` in the name.
+    pub initial_positions: Vec<usize>,
+    /// Whether Wine or Proton runs the app, see [App::wine_prefix].
+    pub wine: bool,
+}
+
+impl AppIndex {
+    pub fn new(app: &App) -> AppIndex {
+        let words = words::split(&app.name);
+        AppIndex {
+            name: Folded::new(&app.name),
+            search_names: app.search_names.iter().map(|v| Folded::new(v)).collect(),
+            comment: Folded::new(app.comment.as_deref().unwrap_or_default()),
+            generic_name: Folded::new(app.generic_name.as_deref().unwrap_or_default()),
+            keywords: app
+                .keywords
+                .as_deref()
+                .unwrap_or_default()
+                .split(';')
+                .map(Folded::new)
+                .collect(),
+            initials: words
+                .iter()
+                .filter_map(|word| word.text.chars().next())
+                .flat_map(char::to_lowercase)
+                .collect(),
+            initial_positions: words.iter().map(|word| word.start).collect(),
+            wine: app.wine_prefix().is_some(),
+        }
+    }
+
+    /// Where each word of a query has to match something, see
+    /// [crate::search::SearchEngine::search].
+    pub fn fields(&self) -> impl Iterator<Item = &Folded> {
+        [&self.name, &self.generic_name, &self.comment]
+            .into_iter()
+            .chain(&self.keywords)
+            .chain(&self.search_names)
+            .filter(|v| !v.is_empty())
+    }
+}
@@ -0,0 +1,175 @@
+//! The fuzzy matching backends, picked with `[search] matcher` in the settings.
+
+use crate::settings::MatcherKind;
+use fuzzy_matcher::skim::{SkimMatcherV2, SkimScoreConfig};
+use nucleo_matcher::pattern::{Atom, AtomKind, CaseMatching, Normalization};
+use nucleo_matcher::Utf32Str;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::ops::Range;
+use unicode_normalization::char::{decompose_compatible, is_combining_mark};
+
+/// Scores how well `query` fuzzily matches `string`.
+pub trait Matcher {
+    /// The score and, with `with_pos`, the char indices of `string` that matched.
+    fn fuzzy(&self, string: &str, query: &str, with_pos: bool) -> Option<(i64, Vec<usize>)>;
+}
+
+pub fn build(kind: MatcherKind) -> FoldingMatcher {
+    let matcher: Box<dyn Matcher> = match kind {
+        MatcherKind::Skim => Box::new(SkimMatcher::default()),
+        MatcherKind::Nucleo => Box::new(NucleoMatcher::default()),
+    };
+    FoldingMatcher(matcher)
+}
+
+/// Matches without accents, so "telegram" finds "Télégram" and "muzik" finds "Müzik". Case
+/// is left to the matchers, which only match it when the query has uppercase letters. The
+/// indices are those of the original string.
+pub struct FoldingMatcher(Box<dyn Matcher>);
+
+impl FoldingMatcher {
+    /// Like [Matcher::fuzzy], for a string that was folded beforehand.
+    pub fn fuzzy_folded(
+        &self,
+        string: &Folded,
+        query: &str,
+        with_pos: bool,
+    ) -> Option<(i64, Vec<usize>)> {
+        self.fuzzy_in(string, 0..usize::MAX, query, with_pos)
+    }
+
+    /// Matches only the `chars` of `string`, like one of its words. The indices are still
+    /// those of the whole string.
+    pub fn fuzzy_in(
+        &self,
+        string: &Folded,
+        chars: Range<usize>,
+        query: &str,
+        with_pos: bool,
+    ) -> Option<(i64, Vec<usize>)> {
+        let (text, first) = string.slice(chars);
+        // Plain ASCII doesn't change, which is most of what gets typed.
+        let query = if query.is_ascii() {
+            Cow::Borrowed(query)
+        } else {
+            Cow::Owned(fold(query).0)
+        };
+        let (score, indices) = self.0.fuzzy(text, &query, with_pos)?;
+        let mut indices: Vec<usize> = indices
+            .into_iter()
+            .map(|i| string.chars[first + i].1)
+            .collect();
+        // Chars like `ﬁ` fold into more than one.
+        indices.dedup();
+        Some((score, indices))
+    }
+}
+
+impl Matcher for FoldingMatcher {
+    fn fuzzy(&self, string: &str, query: &str, with_pos: bool) -> Option<(i64, Vec<usize>)> {
+        self.fuzzy_folded(&Folded::new(string), query, with_pos)
+    }
+}
+
+/// A string and its folded form, folded once when the apps are indexed instead of for every
+/// query, see [FoldingMatcher].
+#[derive(Default)]
+pub struct Folded {
+    pub original: String,
+    text: String,
+    /// The byte offset in `text` of each of its chars, and the char of `original` it came
+    /// from.
+    chars: Vec<(usize, usize)>,
+}
+
+impl Folded {
+    pub fn new(original: &str) -> Folded {
+        let (text, chars) = fold(original);
+        Folded {
+            original: original.to_string(),
+            text,
+            chars,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.original.is_empty()
+    }
+
+    /// The folded text of the `chars` of the original, and the index of its first char.
+    fn slice(&self, chars: Range<usize>) -> (&str, usize) {
+        let first = self
+            .chars
+            .partition_point(|(_, origin)| *origin < chars.start);
+        let last = self
+            .chars
+            .partition_point(|(_, origin)| *origin < chars.end);
+        let offset = |index: usize| self.chars.get(index).map_or(self.text.len(), |v| v.0);
+        (&self.text[offset(first)..offset(last)], first)
+    }
+}
+
+/// `text` NFKD-decomposed without combining marks, with the byte offset of each of its chars
+/// and the index of the char of `text` it came from.
+fn fold(text: &str) -> (String, Vec<(usize, usize)>) {
+    let mut folded = String::with_capacity(text.len());
+    let mut chars = Vec::with_capacity(text.len());
+    for (index, c) in text.chars().enumerate() {
+        decompose_compatible(c, |c| {
+            if is_combining_mark(c) {
+                return;
+            }
+            chars.push((folded.len(), index));
+            folded.push(c);
+        });
+    }
+    (folded, chars)
+}
+
+pub struct SkimMatcher(SkimMatcherV2);
+
+impl Default for SkimMatcher {
+    fn default() -> Self {
+        SkimMatcher(SkimMatcherV2::default().score_config(SkimScoreConfig {
+            ..SkimScoreConfig::default()
+        }))
+    }
+}
+
+impl Matcher for SkimMatcher {
+    fn fuzzy(&self, string: &str, query: &str, with_pos: bool) -> Option<(i64, Vec<usize>)> {
+        self.0.fuzzy(string, query, with_pos)
+    }
+}
+
+/// The matcher of helix and nucleo, faster than skim and scoring more like fzf.
+#[derive(Default)]
+pub struct NucleoMatcher {
+    /// nucleo reuses its buffers between matches, so it needs to be mutable.
+    matcher: RefCell<nucleo_matcher::Matcher>,
+}
+
+impl Matcher for NucleoMatcher {
+    fn fuzzy(&self, string: &str, query: &str, with_pos: bool) -> Option<(i64, Vec<usize>)> {
+        let atom = Atom::new(
+            query,
+            CaseMatching::Smart,
+            Normalization::Smart,
+            AtomKind::Fuzzy,
+            false,
+        );
+        let mut buffer = Vec::new();
+        let haystack = Utf32Str::new(string, &mut buffer);
+        let mut matcher = self.matcher.borrow_mut();
+
+        if !with_pos {
+            let score = atom.score(haystack, &mut matcher)?;
+            return Some((score as i64, Vec::new()));
+        }
+        let mut indices = Vec::new();
+        let score = atom.indices(haystack, &mut matcher, &mut indices)?;
+        let indices = indices.into_iter().map(|v| v as usize).collect();
+        Some((score as i64, indices))
+    }
+}
@@ -1,98 +1,988 @@
+pub mod cjk;
+pub mod filter;
+pub mod index;
+pub mod matcher;
+pub mod phonetic;
+pub mod stats;
+pub mod sync;
+pub mod transliterate;
+pub mod words;
+
 use crate::apps::{App, AppId, AppManager};
+use crate::bookmarks::{Bookmark, DirTarget};
 use crate::config::Config;
-use chrono::{DateTime, Local, TimeDelta, Utc};
+use crate::i18n;
+use crate::media::{MediaCommand, Player};
+use crate::paths::{create_private_dir, expand_home};
+use crate::platform::{self, AutostartEntry, Capture, Toggle};
+use crate::projects::Project;
+use crate::providers::ProviderResult;
+use crate::search::filter::QueryFilter;
+use crate::search::index::AppIndex;
+use crate::search::matcher::{Folded, FoldingMatcher, Matcher};
+use crate::search::stats::UsageStats;
+use crate::search::words::Word;
+use crate::secrets::SecretEntry;
+use crate::settings::{
+    HomeSettings, MatcherKind, NewAppsSettings, RewriteRule, Settings, TerminalSettings,
+};
+use crate::web::WebSearch;
+use chrono::{DateTime, Datelike, Local, TimeDelta, Timelike, Utc};
 use eframe::egui::TextBuffer;
 use eyre::Context;
-use fuzzy_matcher::skim::{SkimMatcherV2, SkimScoreConfig};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, HashMap};
-use std::fs::create_dir_all;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::ops::Sub;
-use std::path::Path;
-use tracing::info;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{info, warn};
 
 #[derive(Default)]
 pub struct SearchResult {
     pub query: String,
     pub entries: Vec<SearchResultEntry>,
+    /// Headers between the entries, only used by the empty query view.
+    pub sections: Vec<ResultSection>,
+    /// The `#tag` words of the query.
+    pub query_tags: Vec<String>,
+}
+
+pub struct ResultSection {
+    pub kind: SectionKind,
+    /// The index of the first entry in this section.
+    pub start: usize,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum SectionKind {
+    Pinned,
+    Recent,
+    MostUsed,
+    /// One of the [MAIN_CATEGORIES], `None` for apps in none of them.
+    Category(Option<&'static str>),
 }
 
+/// The main categories of the freedesktop menu spec, in the order the category view shows them.
+/// `Audio` and `Video` apps are also `AudioVideo` ones.
+pub const MAIN_CATEGORIES: &[&str] = &[
+    "AudioVideo",
+    "Development",
+    "Education",
+    "Game",
+    "Graphics",
+    "Network",
+    "Office",
+    "Science",
+    "Settings",
+    "System",
+    "Utility",
+];
+
 pub struct SearchResultEntry {
     pub id: AppId,
     pub score: SearchScore,
+    /// Shown next to the name instead of the comment.
+    pub detail: Option<String>,
+    /// The last launches of this app failed.
+    pub broken: bool,
+    /// Set for entries that aren't apps, the id is made up then.
+    pub action: Option<EntryAction>,
+    /// Takes a second Enter, for entries that can't be undone like shutting down.
+    pub confirm: bool,
+}
+
+#[derive(Clone)]
+pub enum EntryAction {
+    OpenTerminal(PathBuf),
+    /// Copies a password, see [crate::secrets].
+    CopySecret(SecretEntry),
+    /// Takes a screenshot or picks a color once the launcher is closed.
+    Capture(Capture),
+    /// Flips a quick setting, which is currently on or off.
+    Toggle(Toggle, bool),
+    /// Turns a program started at login on or off, see `:autostart`.
+    Autostart(AutostartEntry),
+    /// Opens a bookmarked directory.
+    OpenDir(Bookmark, DirTarget),
+    /// Runs a command line typed after the terminal prefix in the user's shell.
+    RunCommand {
+        line: String,
+        terminal: bool,
+    },
+    /// Opens a search in the browser.
+    WebSearch(WebSearch),
+    /// Opens a recent project in the editor named `editor`.
+    OpenProject {
+        project: Project,
+        editor: String,
+    },
+    /// A result of a GNOME Shell search provider.
+    ProviderResult(ProviderResult),
+    /// Controls the media player that's playing.
+    Media(Player, MediaCommand),
+    /// Copies an instant answer like a conversion, see [crate::answers].
+    Answer(String),
+    /// Copies a color, written as `text`.
+    Color {
+        color: [u8; 3],
+        text: String,
+    },
+}
+
+impl EntryAction {
+    /// Offers a terminal for queries like `>~/src` or `/tmp` that name a directory.
+    pub fn from_query(query: &str, settings: &TerminalSettings) -> Option<SearchResultEntry> {
+        let path = match query.strip_prefix(settings.prefix.as_str()) {
+            Some(path) if !settings.prefix.is_empty() => path.trim(),
+            _ if query.starts_with(['/', '~']) => query,
+            _ => return None,
+        };
+        let dir = expand_home(Path::new(path));
+        if path.is_empty() || !dir.is_dir() {
+            return None;
+        }
+
+        Some(SearchResultEntry {
+            id: AppId::from_properties(&["terminal", &dir.to_string_lossy()]),
+            score: SearchScore::default(),
+            detail: None,
+            broken: false,
+            action: Some(EntryAction::OpenTerminal(dir)),
+            confirm: false,
+        })
+    }
+
+    /// Offers to run queries like `>htop` in the shell, in the background or in a terminal.
+    pub fn commands(query: &str, settings: &TerminalSettings) -> Vec<SearchResultEntry> {
+        let line = match query.strip_prefix(settings.prefix.as_str()) {
+            Some(line) if !settings.prefix.is_empty() => line.trim(),
+            _ => return Vec::new(),
+        };
+        if line.is_empty() {
+            return Vec::new();
+        }
+
+        [false, true]
+            .into_iter()
+            .map(|terminal| SearchResultEntry {
+                id: AppId::from_properties(&["command", line, &terminal.to_string()]),
+                score: SearchScore::default(),
+                detail: None,
+                broken: false,
+                action: Some(EntryAction::RunCommand {
+                    line: line.to_string(),
+                    terminal,
+                }),
+                confirm: false,
+            })
+            .collect()
+    }
+
+    /// The built-in actions, like taking a screenshot, that every word of `query` starts
+    /// a word of.
+    pub fn quick_actions(query: &str) -> Vec<SearchResultEntry> {
+        [Capture::Region, Capture::Screen, Capture::Color]
+            .into_iter()
+            .filter(|capture| starts_words(&capture_keywords(*capture), query))
+            .map(|capture| SearchResultEntry {
+                id: AppId::from_properties(&["quick-action", &format!("{capture:?}")]),
+                score: SearchScore::default(),
+                detail: None,
+                broken: false,
+                action: Some(EntryAction::Capture(capture)),
+                confirm: false,
+            })
+            .collect()
+    }
+}
+
+/// Whether every word of `query` starts a word of `text`, for the built-in entries.
+/// Queries shorter than 3 characters never match, they would match too much.
+pub fn starts_words(text: &str, query: &str) -> bool {
+    let query = query.to_lowercase();
+    if query.trim().chars().count() < 3 {
+        return false;
+    }
+    let text = text.to_lowercase();
+    let words: Vec<&str> = text.split_whitespace().collect();
+    query
+        .split_whitespace()
+        .all(|part| words.iter().any(|word| word.starts_with(part)))
+}
+
+/// What a quick action is found by, its label and a few words in English.
+fn capture_keywords(capture: Capture) -> String {
+    let (label, keywords) = match capture {
+        Capture::Region => ("capture-region", "screenshot region area snip"),
+        Capture::Screen => ("capture-screen", "screenshot screen full print"),
+        Capture::Color => ("pick-color", "color colour picker pipette eyedropper"),
+    };
+    format!("{} {keywords}", i18n::translate(label, None))
+}
+
+/// What [SearchEngine::track_new_apps] knows an app by, its file id or else its name.
+fn found_key(app: &App) -> String {
+    app.file_id().unwrap_or(&app.name).to_string()
 }
 
 pub struct SearchEngine {
     // Searching
-    matcher: SkimMatcherV2,
+    matcher: FoldingMatcher,
+    /// Applied to queries before matching, from the settings.
+    rewrites: Vec<QueryRewrite>,
+    /// Also match names that sound like the query, from the settings.
+    phonetic: bool,
+    /// Prepared for scoring, see [SearchEngine::index_apps].
+    index: HashMap<AppId, AppIndex>,
+    /// The apps worth scoring again when the query gets longer.
+    last_search: RefCell<Option<LastSearch>>,
 
     // Persistence
-    uses: HashMap<AppId, u32>,
-    uses_max: u32,
+    /// The launches of each app, each counting less the older it is.
+    frecency: HashMap<AppId, f32>,
+    frecency_max: f32,
+    /// After how many days a launch counts half as much, from the settings.
+    half_life_days: f32,
+    /// When each app was launched within [uses_max_age].
+    use_times: HashMap<AppId, Vec<DateTime<Utc>>>,
+    /// The most recently used apps, the latest first.
+    recent: Vec<AppId>,
+    pinned: Vec<AppId>,
+    /// How often launching an app failed in a row.
+    failures: HashMap<AppId, u32>,
+    /// The pids of apps that are still running since we launched them.
+    running: HashMap<AppId, u32>,
+    /// Apps whose latest launch is gone already, soon after it started.
+    crashed: HashSet<AppId>,
+    /// Apps found for the first time recently, see [SearchEngine::track_new_apps].
+    new_apps: HashSet<AppId>,
+    new_apps_boost: f32,
+    /// Boost apps usually launched around this time of day, from the settings.
+    time_of_day: bool,
+    /// Rank with rounded scores and a full tie break, from the settings.
+    deterministic: bool,
+    /// How many apps a search lists at most, from the settings.
+    max_results: Option<usize>,
+    /// Apps scoring below this share of the best one are left out, from the settings.
+    min_score: f32,
+    /// Multipliers from the settings, by file id or name.
+    boosts: HashMap<String, f32>,
+    /// Tags added in the ui.
+    user_tags: HashMap<AppId, Vec<String>>,
+    /// Apps picked below the top result, see [SearchEngine::record_choice].
+    choices: Vec<ChoiceEntry>,
+    /// Tags from the settings, with the file ids or names of their apps.
+    config_tags: HashMap<String, Vec<String>>,
+    /// The tag of apps Wine or Proton runs, from the settings.
+    wine_tag: Option<String>,
     config: Config<SearchData>,
+    /// Where the uses are shared with other machines.
+    sync_dir: Option<PathBuf>,
 }
 
 impl SearchEngine {
-    pub fn new(dir: &Path) -> eyre::Result<Self> {
-        create_dir_all(dir).wrap_err("Failed to create dir")?;
+    /// Reads the usage data in `dir`, launches older than several `half_life_days` are
+    /// forgotten.
+    pub fn new(dir: &Path, sync_dir: Option<&Path>, half_life_days: f32) -> eyre::Result<Self> {
+        create_private_dir(dir).wrap_err("Failed to create dir")?;
         let mut config = Config::new(dir.join("uses.json"));
         let data: &mut SearchData = config.get_mut().wrap_err("Failed to read config")?;
 
-        let mut uses = HashMap::new();
+        if let Some(sync_dir) = sync_dir {
+            let now = Local::now().to_utc();
+            let since = now.sub(uses_max_age(half_life_days));
+            match sync::merge(sync_dir, data, since) {
+                Ok(0) => {}
+                Ok(_) => config.flush_changes().wrap_err("Failed to save config")?,
+                Err(error) => warn!("Could not merge the synced uses: {error:?}"),
+            }
+        }
+        let data: &mut SearchData = config.get_mut().wrap_err("Failed to read config")?;
+
+        let mut use_times: HashMap<AppId, Vec<DateTime<Utc>>> = HashMap::new();
         for entry in &data.uses {
-            *uses.entry(entry.id.clone()).or_default() += 1;
+            use_times
+                .entry(entry.id.clone())
+                .or_default()
+                .push(entry.at);
+        }
+
+        let mut recent: Vec<AppId> = Vec::new();
+        for entry in data.uses.iter().rev() {
+            if !recent.contains(&entry.id) {
+                recent.push(entry.id.clone());
+            }
+        }
+        // Only the latest launch of an app counts.
+        let mut running = HashMap::new();
+        let mut crashed = HashSet::new();
+        let mut seen = HashSet::new();
+        let now = Local::now().to_utc();
+        for entry in data.history.iter().rev() {
+            if !seen.insert(entry.id.clone()) {
+                continue;
+            }
+            let (Some(pid), Some(start)) = (entry.pid, entry.start_time) else {
+                continue;
+            };
+            if platform::process_start(pid) == Some(start) {
+                running.insert(entry.id.clone(), pid);
+            } else if now - entry.at < TimeDelta::seconds(CRASH_WINDOW_SECS) {
+                crashed.insert(entry.id.clone());
+            }
         }
 
-        Ok(Self {
-            matcher: SkimMatcherV2::default().score_config(SkimScoreConfig {
-                ..SkimScoreConfig::default()
-            }),
-            uses_max: *uses.values().max().unwrap_or(&1),
-            uses,
+        let pinned = data.pinned.clone();
+        let failures = data.failures.clone();
+        let user_tags = data.tags.clone();
+        let choices = data.choices.clone();
+
+        let mut engine = Self {
+            matcher: matcher::build(MatcherKind::default()),
+            rewrites: Vec::new(),
+            phonetic: false,
+            index: HashMap::new(),
+            last_search: RefCell::new(None),
+            frecency: HashMap::new(),
+            frecency_max: 0.0,
+            half_life_days,
+            use_times,
+            time_of_day: false,
+            recent,
+            pinned,
+            failures,
+            running,
+            crashed,
+            new_apps: HashSet::new(),
+            new_apps_boost: 1.0,
+            deterministic: true,
+            max_results: None,
+            min_score: 0.0,
+            boosts: HashMap::new(),
+            user_tags,
+            choices,
+            config_tags: HashMap::new(),
+            wine_tag: None,
             config,
-        })
+            sync_dir: sync_dir.map(|v| v.to_path_buf()),
+        };
+        engine.update_frecency();
+        Ok(engine)
     }
 
-    pub fn record_use(&mut self, id: AppId) -> eyre::Result<()> {
+    /// Records a launch of `id`, `search_time` is how long it took since ignition was opened.
+    pub fn record_use(
+        &mut self,
+        id: AppId,
+        search_time: Option<Duration>,
+        args: &[String],
+    ) -> eyre::Result<()> {
         let data = self.config.get_mut().wrap_err("Failed to load config")?;
 
         // Add new entry
         let now = Local::now().to_utc();
-        data.uses.push(UseEntry { id, at: now });
+        data.history.push(HistoryEntry {
+            id: id.clone(),
+            at: now,
+            args: args.to_vec(),
+            pid: None,
+            start_time: None,
+        });
+        let overflow = data.history.len().saturating_sub(HISTORY_LIMIT);
+        data.history.drain(..overflow);
+
+        self.recent.retain(|v| v != &id);
+        self.recent.insert(0, id.clone());
+        self.use_times.entry(id.clone()).or_default().push(now);
+        data.uses.push(UseEntry {
+            id,
+            at: now,
+            search_ms: search_time.map(|v| v.as_millis() as u64),
+        });
 
         // Remove old
         let start_len = data.uses.len();
-        data.uses.retain(|e| e.at >= now.sub(TimeDelta::days(30)));
+        data.uses
+            .retain(|e| e.at >= now.sub(uses_max_age(self.half_life_days)));
+        data.dismissals
+            .retain(|at| *at >= now.sub(TimeDelta::days(30)));
 
         let removed_old = start_len - data.uses.len();
         if removed_old > 0 {
             info!("Purged {removed_old} old entries.");
         }
+        self.update_frecency();
 
         // Flush config
+        self.config
+            .flush_changes()
+            .wrap_err("Failed to save config")?;
+
+        if let Some(sync_dir) = &self.sync_dir {
+            let data = self.config.get_mut().wrap_err("Failed to load config")?;
+            if let Err(error) = sync::publish(sync_dir, data) {
+                warn!("Could not share the uses: {error:?}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Remembers that `id` was picked for `query` from `rank` in the results, so it ranks
+    /// higher for that query and ones like it. Only picks below the top result are learned
+    /// from, and picks for queries that were learned before to keep them up to date.
+    pub fn record_choice(&mut self, query: &str, id: &AppId, rank: usize) -> eyre::Result<()> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() || (rank == 0 && !self.choices.iter().any(|v| v.query == query)) {
+            return Ok(());
+        }
+        let data = self.config.get_mut().wrap_err("Failed to load config")?;
+
+        let now = Local::now().to_utc();
+        data.choices.push(ChoiceEntry {
+            query,
+            id: id.clone(),
+            at: now,
+        });
+        data.choices
+            .retain(|v| v.at >= now.sub(TimeDelta::days(CHOICES_MAX_DAYS)));
+        let overflow = data.choices.len().saturating_sub(CHOICES_LIMIT);
+        data.choices.drain(..overflow);
+        self.choices = data.choices.clone();
+
+        self.config
+            .flush_changes()
+            .wrap_err("Failed to save config")?;
+        Ok(())
+    }
+
+    /// The score multipliers learned from earlier picks for queries like `query`. Each pick
+    /// counts less the older it is and the less its query looks like this one.
+    fn learned_boosts(&self, query: &str) -> HashMap<&AppId, f32> {
+        let query = query.trim().to_lowercase();
+        let mut boosts = HashMap::new();
+        if query.is_empty() {
+            return boosts;
+        }
+        let now = Local::now().to_utc();
+        for choice in &self.choices {
+            // Queries are typed a letter at a time, so "fir" is like "firef".
+            let (short, long) = if choice.query.len() < query.len() {
+                (&choice.query, &query)
+            } else {
+                (&query, &choice.query)
+            };
+            if !long.starts_with(short.as_str()) {
+                continue;
+            }
+            let similarity = short.chars().count() as f32 / long.chars().count() as f32;
+            let age_days = (now - choice.at).num_hours() as f32 / 24.0;
+            let decay = 0.5f32.powf(age_days / CHOICES_HALF_LIFE_DAYS);
+            *boosts.entry(&choice.id).or_default() += similarity * decay;
+        }
+        for boost in boosts.values_mut() {
+            *boost = 1.0 + boost.min(CHOICES_MAX_COUNTED) * CHOICE_BOOST;
+        }
+        boosts
+    }
+
+    /// Score multipliers for apps mostly launched at times like `now`: within an hour of it,
+    /// on a weekday if it is one or on the weekend otherwise.
+    fn time_of_day_boosts(&self, now: DateTime<Local>) -> HashMap<&AppId, f32> {
+        let is_weekend = |at: &DateTime<Local>| at.weekday().number_from_monday() > 5;
+        let is_like_now = |at: &DateTime<Utc>| {
+            let at = at.with_timezone(&Local);
+            let hours = at.hour().abs_diff(now.hour());
+            is_weekend(&at) == is_weekend(&now) && hours.min(24 - hours) <= 1
+        };
+
+        let mut boosts = HashMap::new();
+        for (id, times) in &self.use_times {
+            let count = times.iter().filter(|at| is_like_now(at)).count();
+            if count < TIME_OF_DAY_MIN_USES {
+                continue;
+            }
+            let share = count as f32 / times.len() as f32;
+            boosts.insert(id, 1.0 + share * TIME_OF_DAY_BOOST);
+        }
+        boosts
+    }
+
+    /// Takes over the boosts and tags from the settings.
+    pub fn apply_settings(&mut self, settings: &Settings) {
+        self.last_search = RefCell::new(None);
+        self.matcher = matcher::build(settings.search.matcher);
+        self.deterministic = settings.search.deterministic;
+        self.max_results = settings.search.max_results;
+        self.min_score = settings.search.min_score;
+        self.rewrites = QueryRewrite::compile(&settings.search.rewrite);
+        self.phonetic = settings.search.phonetic;
+        self.time_of_day = settings.search.time_of_day;
+        self.half_life_days = settings.search.frecency_half_life_days;
+        self.update_frecency();
+        self.boosts = settings.boost.clone();
+        self.config_tags = settings
+            .tags
+            .iter()
+            .map(|(tag, apps)| (tag.to_lowercase(), apps.clone()))
+            .collect();
+        let wine_tag = settings.wine.tag.trim_start_matches('#').to_lowercase();
+        self.wine_tag = Some(wine_tag).filter(|v| !v.is_empty());
+    }
+
+    /// Remembers when `apps` were first found, those found in the last days count as new.
+    ///
+    /// Apps are told apart by their file, since their [AppId] changes with their name or
+    /// comment, like on an update.
+    pub fn track_new_apps(
+        &mut self,
+        apps: &AppManager,
+        settings: &NewAppsSettings,
+    ) -> eyre::Result<()> {
+        let data = self.config.get_mut().wrap_err("Failed to load config")?;
+        let now = Local::now().to_utc();
+        // On the first run, everything was installed before.
+        let found_at = if data.first_found.is_empty() {
+            DateTime::UNIX_EPOCH
+        } else {
+            now
+        };
+
+        let installed: HashMap<String, &AppId> = apps
+            .applications
+            .values()
+            .map(|app| (found_key(app), &app.id))
+            .collect();
+        let count = data.first_found.len();
+        // Uninstalled apps count as new again when they come back.
+        data.first_found
+            .retain(|key, _| installed.contains_key(key));
+        let mut changed = data.first_found.len() != count;
+        for key in installed.keys() {
+            if !data.first_found.contains_key(key) {
+                data.first_found.insert(key.clone(), found_at);
+                changed = true;
+            }
+        }
+
+        let since = now.sub(TimeDelta::days(settings.days as i64));
+        self.new_apps = data
+            .first_found
+            .iter()
+            .filter(|(_, at)| **at > since)
+            .filter_map(|(key, _)| installed.get(key).map(|id| (*id).clone()))
+            .collect();
+        self.new_apps_boost = settings.boost;
+
+        if changed {
+            self.config
+                .flush_changes()
+                .wrap_err("Failed to save config")?;
+        }
+        Ok(())
+    }
+
+    pub fn is_new(&self, id: &AppId) -> bool {
+        self.new_apps.contains(id)
+    }
+
+    /// Counts the uses of merged duplicates (see [AppManager::merged]) for the app they
+    /// were merged into.
+    pub fn apply_merged(&mut self, apps: &AppManager) {
+        for (duplicate, id) in &apps.merged {
+            if let Some(times) = self.use_times.remove(duplicate) {
+                self.use_times.entry(id.clone()).or_default().extend(times);
+            }
+            for entry in self.recent.iter_mut().chain(self.pinned.iter_mut()) {
+                if entry == duplicate {
+                    *entry = id.clone();
+                }
+            }
+        }
+        let mut seen = HashSet::new();
+        self.recent.retain(|v| seen.insert(v.clone()));
+        self.update_frecency();
+    }
+
+    /// Prepares what scoring needs of each app, again whenever the apps change.
+    pub fn index_apps(&mut self, apps: &AppManager) {
+        self.last_search = RefCell::new(None);
+        self.index = apps
+            .applications
+            .values()
+            .map(|app| (app.id.clone(), AppIndex::new(app)))
+            .collect();
+    }
+
+    /// The tags of `app`, both from the settings and the ui.
+    pub fn tags_of(&self, app: &App) -> Vec<&str> {
+        let mut tags: Vec<&str> = self.config_tags_of(app);
+        for tag in self.user_tags.get(&app.id).into_iter().flatten() {
+            if !tags.contains(&tag.as_str()) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+
+    /// The tags the settings give `app`, and the Wine tag, these can't be removed in the ui.
+    pub fn config_tags_of(&self, app: &App) -> Vec<&str> {
+        let mut tags: Vec<&str> = self
+            .config_tags
+            .iter()
+            .filter(|(_, apps)| {
+                apps.iter()
+                    .any(|v| Some(v.as_str()) == app.file_id() || *v == app.name)
+            })
+            .map(|(tag, _)| tag.as_str())
+            .collect();
+        if let Some(tag) = &self.wine_tag {
+            let wine = match self.index.get(&app.id) {
+                Some(index) => index.wine,
+                None => app.wine_prefix().is_some(),
+            };
+            if wine && !tags.contains(&tag.as_str()) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+
+    /// Every tag in use, sorted.
+    pub fn all_tags(&self) -> BTreeSet<&str> {
+        self.config_tags
+            .keys()
+            .chain(&self.wine_tag)
+            .chain(self.user_tags.values().flatten())
+            .map(|v| v.as_str())
+            .collect()
+    }
+
+    /// Adds or removes a tag the ui gave `id`.
+    pub fn toggle_tag(&mut self, id: &AppId, tag: &str) -> eyre::Result<()> {
+        let tag = tag.trim_start_matches('#').to_lowercase();
+        let data = self.config.get_mut().wrap_err("Failed to load config")?;
+        let tags = data.tags.entry(id.clone()).or_default();
+        if tags.contains(&tag) {
+            tags.retain(|v| *v != tag);
+        } else {
+            tags.push(tag);
+        }
+        data.tags.retain(|_, tags| !tags.is_empty());
+        self.user_tags = data.tags.clone();
+
         self.config
             .flush_changes()
             .wrap_err("Failed to save config")?;
         Ok(())
     }
 
+    /// Counts failed launches, one successful launch forgives all earlier failures.
+    pub fn record_launch_result(
+        &mut self,
+        id: &AppId,
+        success: bool,
+        pid: Option<u32>,
+    ) -> eyre::Result<()> {
+        let data = self.config.get_mut().wrap_err("Failed to load config")?;
+        let mut changed = false;
+        // The launch was recorded by the ui, before it closed. Without a start time a later
+        // process with the same pid couldn't be told apart.
+        let last = data.history.iter_mut().rev().find(|v| &v.id == id);
+        let process = pid.and_then(|pid| Some((pid, platform::process_start(pid)?)));
+        if let Some((entry, (pid, start))) = last.zip(process) {
+            entry.pid = Some(pid);
+            entry.start_time = Some(start);
+            changed = true;
+        }
+        if success {
+            changed |= data.failures.remove(id).is_some();
+        } else {
+            *data.failures.entry(id.clone()).or_default() += 1;
+            changed = true;
+        }
+        if !changed {
+            return Ok(());
+        }
+        self.failures = data.failures.clone();
+
+        self.config
+            .flush_changes()
+            .wrap_err("Failed to save config")?;
+        Ok(())
+    }
+
+    /// The pid of `id` if it is still running since we launched it.
+    pub fn running_pid(&self, id: &AppId) -> Option<u32> {
+        self.running.get(id).copied()
+    }
+
+    /// Whether the latest launch of `id` quit soon after it started, likely a crash.
+    pub fn has_crashed(&self, id: &AppId) -> bool {
+        self.crashed.contains(id)
+    }
+
+    /// Whether launching `id` failed repeatedly, it gets a penalty until it works again.
+    pub fn is_broken(&self, id: &AppId) -> bool {
+        self.failures
+            .get(id)
+            .is_some_and(|v| *v >= BROKEN_AFTER_FAILURES)
+    }
+
+    /// Records that ignition was closed without launching anything.
+    pub fn record_dismissal(&mut self) -> eyre::Result<()> {
+        let data = self.config.get_mut().wrap_err("Failed to load config")?;
+        let now = Local::now().to_utc();
+        data.dismissals.push(now);
+        data.dismissals
+            .retain(|at| *at >= now.sub(TimeDelta::days(30)));
+
+        self.config
+            .flush_changes()
+            .wrap_err("Failed to save config")?;
+        Ok(())
+    }
+
+    /// The launch log, the latest launch first.
+    pub fn history(&mut self) -> eyre::Result<Vec<HistoryEntry>> {
+        let data = self.config.get_mut().wrap_err("Failed to load config")?;
+        Ok(data.history.iter().rev().cloned().collect())
+    }
+
+    pub fn stats(&mut self, days: u32) -> eyre::Result<UsageStats> {
+        let data = self.config.get_mut().wrap_err("Failed to load config")?;
+        Ok(UsageStats::compute(data, days))
+    }
+
+    /// Pins or unpins an app to the top of the empty query view, returns whether it is pinned now.
+    pub fn toggle_pin(&mut self, id: &AppId) -> eyre::Result<bool> {
+        let data = self.config.get_mut().wrap_err("Failed to load config")?;
+        let pinned = if data.pinned.contains(id) {
+            data.pinned.retain(|v| v != id);
+            false
+        } else {
+            data.pinned.push(id.clone());
+            true
+        };
+        self.pinned = data.pinned.clone();
+
+        self.config
+            .flush_changes()
+            .wrap_err("Failed to save config")?;
+        Ok(pinned)
+    }
+
+    /// The empty query view, pinned, recently used and most used apps in their own sections.
+    pub fn home(&self, apps: &AppManager, settings: &HomeSettings) -> SearchResult {
+        let mut most_used: Vec<(&AppId, &f32)> = self.frecency.iter().collect();
+        most_used.sort_by(|(a_id, a), (b_id, b)| b.total_cmp(a).then(a_id.cmp(b_id)));
+        let most_used: Vec<&AppId> = most_used.into_iter().map(|(id, _)| id).collect();
+
+        let sections = [
+            (
+                SectionKind::Pinned,
+                self.pinned.iter().collect(),
+                settings.pinned,
+            ),
+            (
+                SectionKind::Recent,
+                self.recent.iter().collect(),
+                settings.recent,
+            ),
+            (SectionKind::MostUsed, most_used, settings.most_used),
+        ];
+
+        let mut result = SearchResult::default();
+        let mut seen = HashSet::new();
+        for (kind, ids, count) in sections {
+            let start = result.entries.len();
+            for id in ids
+                .into_iter()
+                .filter(|id| apps.applications.contains_key(*id))
+                .filter(|id| seen.insert((*id).clone()))
+                .take(count)
+            {
+                result.entries.push(SearchResultEntry {
+                    id: id.clone(),
+                    score: SearchScore::default(),
+                    detail: None,
+                    broken: self.is_broken(id),
+                    action: None,
+                    confirm: false,
+                });
+            }
+
+            if result.entries.len() > start {
+                result.sections.push(ResultSection { kind, start });
+            }
+        }
+
+        result
+    }
+
+    /// The empty query view as a category browser, every app under its first main category.
+    pub fn categories(&self, apps: &AppManager) -> SearchResult {
+        let mut by_category: BTreeMap<usize, Vec<&App>> = BTreeMap::new();
+        for app in apps.applications.values() {
+            let categories = app.categories.as_deref().unwrap_or_default();
+            let index = categories
+                .iter()
+                .find_map(|v| MAIN_CATEGORIES.iter().position(|main| main == v))
+                .unwrap_or(MAIN_CATEGORIES.len());
+            by_category.entry(index).or_default().push(app);
+        }
+
+        let mut result = SearchResult::default();
+        for (index, mut category) in by_category {
+            category.sort_by_cached_key(|app| app.name.to_lowercase());
+            result.sections.push(ResultSection {
+                kind: SectionKind::Category(MAIN_CATEGORIES.get(index).copied()),
+                start: result.entries.len(),
+            });
+            for app in category {
+                result.entries.push(SearchResultEntry {
+                    id: app.id.clone(),
+                    score: SearchScore::default(),
+                    detail: None,
+                    broken: self.is_broken(&app.id),
+                    action: None,
+                    confirm: false,
+                });
+            }
+        }
+        result
+    }
+
+    /// Scores the apps for `query`. When it only adds to the last query with the same filters,
+    /// only the apps that matched every word of that one, or came close to its best score,
+    /// are scored again.
     pub fn search(&self, query: String, apps: &AppManager) -> SearchResult {
-        let search_query = SearchQuery::from(query);
+        let raw_query = query.clone();
+        let search_query = SearchQuery::from(query, &self.rewrites);
+
+        // Unknown tags don't filter, so you can type one before tagging the first app with it.
+        let all_tags = self.all_tags();
+        let filter_tags: Vec<String> = search_query
+            .tags
+            .iter()
+            .filter(|tag| all_tags.contains(tag.as_str()))
+            .cloned()
+            .collect();
+
+        // Other filters can let through apps the last ones left out.
+        let last_search = self.last_search.take().filter(|last| {
+            last.filters == search_query.filters
+                && last.tags == filter_tags
+                && raw_query.starts_with(&last.query)
+                && search_query.text.starts_with(&last.text)
+        });
+        let candidates: Vec<&App> = match &last_search {
+            Some(last) => last
+                .candidates
+                .iter()
+                .filter_map(|id| apps.applications.get(id))
+                .collect(),
+            None => apps.applications.values().collect(),
+        };
 
+        let learned = self.learned_boosts(&search_query.text);
+        let time_of_day = if self.time_of_day {
+            self.time_of_day_boosts(Local::now())
+        } else {
+            HashMap::new()
+        };
         let mut results = Vec::new();
-        for entry in apps.applications.values() {
-            let score = self.score(entry, &search_query);
-            results.push(SearchResultEntry {
+        // Apps that don't match every word, only listed when none does.
+        let mut partial = Vec::new();
+        // Apps that match every word, which a longer query can still find.
+        let mut matched = HashSet::new();
+        let single_word = search_query.parts.iter().filter(|v| !v.is_empty()).count() < 2;
+        for entry in candidates {
+            if !filter_tags.is_empty() {
+                let tags = self.tags_of(entry);
+                if !filter_tags.iter().all(|tag| tags.contains(&tag.as_str())) {
+                    continue;
+                }
+            }
+            if !search_query.filters.iter().all(|v| v.matches(entry)) {
+                continue;
+            }
+
+            // Apps added since the last [SearchEngine::index_apps] still get found.
+            let built;
+            let index = match self.index.get(&entry.id) {
+                Some(index) => index,
+                None => {
+                    built = AppIndex::new(entry);
+                    &built
+                }
+            };
+
+            let mut score = self.score(entry, index, &search_query);
+            score.multiply("learned", learned.get(&entry.id).copied().unwrap_or(1.0));
+            score.multiply(
+                "time of day",
+                time_of_day.get(&entry.id).copied().unwrap_or(1.0),
+            );
+            let result = SearchResultEntry {
                 id: entry.id.clone(),
                 score,
+                detail: None,
+                broken: self.is_broken(&entry.id),
+                action: None,
+                confirm: false,
+            };
+            let matches_all_words = self.matches_all_words(index, &search_query);
+            if matches_all_words {
+                matched.insert(&entry.id);
+            }
+            // A single word that doesn't match still ranks by its other scores.
+            if matches_all_words || single_word {
+                results.push(result);
+            } else {
+                partial.push(result);
+            }
+        }
+
+        // The edit distances give nearly every app a little score, so only the apps that
+        // matched every word, or came close to the best one, are worth scoring again.
+        let best = results
+            .iter()
+            .chain(&partial)
+            .map(|v| v.score.score)
+            .fold(0.0, f32::max);
+        let candidates = results
+            .iter()
+            .chain(&partial)
+            .filter(|v| {
+                matched.contains(&v.id)
+                    || (v.score.score > 0.0 && v.score.score >= best * CANDIDATE_SHARE)
             })
+            .map(|v| v.id.clone())
+            .collect();
+        if !raw_query.trim().is_empty() {
+            *self.last_search.borrow_mut() = Some(LastSearch {
+                query: raw_query,
+                text: search_query.text.clone(),
+                filters: search_query.filters.clone(),
+                tags: filter_tags,
+                candidates,
+            });
+        }
+
+        if results.is_empty() {
+            results = partial;
         }
 
         #[derive(PartialEq)]
         struct SearchOrderKey<'a> {
             score: f32,
             name: &'a str,
+            /// Only set in the deterministic mode, like `id`.
+            file_id: &'a str,
+            id: Option<&'a AppId>,
         }
         impl Eq for SearchOrderKey<'_> {}
         impl PartialOrd for SearchOrderKey<'_> {
@@ -106,41 +996,82 @@ impl SearchEngine {
                     .score
                     .total_cmp(&self.score)
                     .then(self.name.cmp(other.name))
+                    .then(self.file_id.cmp(other.file_id))
+                    .then(self.id.cmp(&other.id))
             }
         }
-        results.sort_unstable_by_key(|v| SearchOrderKey {
-            score: v.score.score,
-            name: apps.applications.get(&v.id).map(|v| &*v.name).unwrap_or(""),
+        fn order_key<'a>(
+            v: &'a SearchResultEntry,
+            apps: &'a AppManager,
+            deterministic: bool,
+        ) -> SearchOrderKey<'a> {
+            let app = apps.applications.get(&v.id);
+            let name = app.map(|v| &*v.name).unwrap_or("");
+            if !deterministic {
+                return SearchOrderKey {
+                    score: v.score.score,
+                    name,
+                    file_id: "",
+                    id: None,
+                };
+            }
+            // Tiny differences from summing up the parts can't reorder apps that way.
+            SearchOrderKey {
+                score: (v.score.score / SCORE_QUANTUM).round() * SCORE_QUANTUM,
+                name,
+                file_id: app.and_then(|v| v.file_id()).unwrap_or(""),
+                id: Some(&v.id),
+            }
+        }
+        results.sort_unstable_by(|a, b| {
+            order_key(a, apps, self.deterministic).cmp(&order_key(b, apps, self.deterministic))
         });
+        let top = results.first().map_or(0.0, |v| v.score.score);
+        results.retain(|v| v.score.score >= top * self.min_score);
+        if let Some(max_results) = self.max_results {
+            results.truncate(max_results);
+        }
 
         SearchResult {
             query: search_query.text,
             entries: results,
+            sections: Vec::new(),
+            query_tags: search_query.tags,
         }
     }
 
-    pub fn score(&self, app: &App, query: &SearchQuery) -> SearchScore {
-        let mut result = SearchScore {
-            score: 0.0,
-            indices: Default::default(),
-        };
+    pub fn score(&self, app: &App, index: &AppIndex, query: &SearchQuery) -> SearchScore {
+        let mut result = SearchScore::default();
 
-        result.add(50.0, self.score_string(query, &app.name, true));
+        result.add("name", 50.0, self.score_string(query, &index.name, true));
+        let other_name = index
+            .search_names
+            .iter()
+            .map(|name| self.score_string(query, name, false))
+            .max_by(|a, b| a.score.total_cmp(&b.score));
+        if let Some(part) = other_name {
+            result.add("other name", 25.0, part);
+        }
+        if self.phonetic {
+            result.add("phonetic", 50.0, self.score_phonetic(query, &app.name));
+        }
+        result.add("initials", 50.0, self.score_initials(query, index));
         result.add(
+            "comment",
             0.2,
-            self.score_string(query, &app.comment.clone().unwrap_or_default(), false),
+            self.score_string(query, &index.comment, false),
         );
         result.add(
+            "generic name",
             1.0,
-            self.score_string(query, &app.generic_name.clone().unwrap_or_default(), false),
+            self.score_string(query, &index.generic_name, false),
         );
 
         // Go through keywords
-        let keywords = app.keywords.clone().unwrap_or_default();
-        let split: Vec<&str> = keywords.split(";").collect();
-        for &keyword in &split {
+        for keyword in &index.keywords {
             result.add(
-                0.5 / split.len() as f32,
+                "keywords",
+                0.5 / index.keywords.len() as f32,
                 self.score_string(query, keyword, false),
             );
         }
@@ -148,23 +1079,94 @@ impl SearchEngine {
         //
         let length_penalty = app.name.len() as f32 * 0.003 * result.score;
         if result.score > length_penalty {
-            result.score -= length_penalty;
+            result.add_score("length", -length_penalty);
         }
 
         if Self::is_penalized(app) {
-            result.score *= 0.9;
+            result.multiply("terminal or settings", 0.9);
+        }
+
+        if self.is_broken(&app.id) {
+            result.multiply("broken", 0.5);
         }
 
         let popularity = self.get_popularity(&app.id);
-        result.score *= 1.0 + popularity * 0.5;
-        result.score += popularity;
+        result.multiply("popular", 1.0 + popularity * 0.5);
+        result.add_score("popular", popularity);
+
+        // Tags work like keywords.
+        let tags = self.tags_of(app);
+        for tag in &tags {
+            result.add(
+                "tags",
+                0.5 / tags.len() as f32,
+                self.score_string(query, &Folded::new(tag), false),
+            );
+        }
+
+        result.multiply("boost", self.get_boost(app));
+        if self.is_new(&app.id) {
+            result.multiply("new", self.new_apps_boost);
+        }
 
         result
     }
 
+    /// Whether each word of a query with several matches the name, generic name, keywords
+    /// or comment of `app`.
+    /// Whether every word of `query` is found in one of the names or descriptions of the app.
+    fn matches_all_words(&self, index: &AppIndex, query: &SearchQuery) -> bool {
+        let mut words = query.parts.iter().filter(|v| !v.is_empty());
+        words.all(|word| {
+            index
+                .fields()
+                .any(|field| self.matcher.fuzzy_folded(field, word, false).is_some())
+        })
+    }
+
+    pub fn get_boost(&self, app: &App) -> f32 {
+        app.file_id()
+            .and_then(|id| self.boosts.get(id))
+            .or_else(|| self.boosts.get(&app.name))
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    /// How often `id` was launched in the last 30 days.
+    pub fn use_count(&self, id: &AppId) -> u32 {
+        let since = Local::now().to_utc().sub(TimeDelta::days(30));
+        self.use_times.get(id).map_or(0, |times| {
+            times.iter().filter(|at| **at >= since).count() as u32
+        })
+    }
+
+    /// The frecency of `id` compared to the most used app, from 0 to 1.
     pub fn get_popularity(&self, id: &AppId) -> f32 {
-        let uses = self.uses.get(id).copied().unwrap_or(0);
-        uses as f32 / self.uses_max as f32
+        if self.frecency_max <= 0.0 {
+            return 0.0;
+        }
+        self.frecency.get(id).copied().unwrap_or(0.0) / self.frecency_max
+    }
+
+    /// Sums up the launches of each app, a launch counts half as much after each half-life.
+    fn update_frecency(&mut self) {
+        let now = Local::now().to_utc();
+        let half_life = self.half_life_days.max(MIN_HALF_LIFE_DAYS);
+        self.frecency = self
+            .use_times
+            .iter()
+            .map(|(id, times)| {
+                let weight: f32 = times
+                    .iter()
+                    .map(|at| {
+                        let age_days = (now - *at).num_minutes().max(0) as f32 / (24.0 * 60.0);
+                        0.5f32.powf(age_days / half_life)
+                    })
+                    .sum();
+                (id.clone(), weight)
+            })
+            .collect();
+        self.frecency_max = self.frecency.values().copied().fold(0.0, f32::max);
     }
 
     fn is_penalized(app: &App) -> bool {
@@ -179,16 +1181,22 @@ impl SearchEngine {
         false
     }
 
-    fn score_string(&self, query: &SearchQuery, str: &str, with_pos: bool) -> SearchResultPart {
+    fn score_string(
+        &self,
+        query: &SearchQuery,
+        folded: &Folded,
+        with_pos: bool,
+    ) -> SearchResultPart {
         let mut part = SearchResultPart {
             score: 0.0,
             indices: Default::default(),
         };
+        let str = folded.original.as_str();
         if str.is_empty() {
             return part;
         }
 
-        self.score_full(str, &query.text, 1.0, with_pos, &mut part);
+        self.score_full(folded, &query.text, 1.0, with_pos, &mut part);
         if with_pos {
             let char_count = query.text.chars().count();
             for i in 0..char_count {
@@ -196,7 +1204,7 @@ impl SearchEngine {
                 new_query.delete_char_range(i..(i + 1));
 
                 self.score_full(
-                    str,
+                    folded,
                     &new_query,
                     0.75 / char_count as f32,
                     with_pos,
@@ -217,22 +1225,82 @@ impl SearchEngine {
             }
         }
 
-        let sub_strings: Vec<&str> = str.split(&[' ', '-']).collect();
-        self.score_small(&sub_strings, &[&query.text], with_pos, &mut part);
-        self.score_small(&[str], query.parts.as_slice(), with_pos, &mut part);
+        if let Some(range) = words::match_at_word_start(str, &query.text) {
+            let perfect = self
+                .matcher
+                .fuzzy(&query.text, &query.text, false)
+                .map_or(0.0, |(score, _)| score as f32);
+            part.score += perfect * WORD_START_WEIGHT;
+            if with_pos {
+                for index in range {
+                    part.indices.insert(index, 1.0);
+                }
+            }
+        }
+
+        let words = words::split(str);
+        self.score_small(folded, &words, &[&query.text], with_pos, &mut part);
+        let whole = Word {
+            start: 0,
+            text: str,
+        };
+        let parts = query.parts.as_slice();
+        self.score_small(folded, &[whole], parts, with_pos, &mut part);
 
         part
     }
 
+    /// Scores a name that sounds like the query as a fraction of typing it correctly.
+    fn score_phonetic(&self, query: &SearchQuery, name: &str) -> SearchResultPart {
+        let score = phonetic::matching_words(name, &query.text)
+            .into_iter()
+            .filter_map(|word| self.matcher.fuzzy(word, word, false))
+            .map(|(score, _)| score as f32 * PHONETIC_WEIGHT)
+            .fold(0.0, f32::max);
+        SearchResultPart {
+            score,
+            indices: Default::default(),
+        }
+    }
+
+    /// Scores a query made of the first letters of the words of the name, like "gimp" for
+    /// "GNU Image Manipulation Program", as a fraction of typing it correctly.
+    fn score_initials(&self, query: &SearchQuery, index: &AppIndex) -> SearchResultPart {
+        let mut part = SearchResultPart {
+            score: 0.0,
+            indices: Default::default(),
+        };
+        let typed = query.text.to_lowercase();
+        if typed.chars().count() < INITIALS_MIN_LEN || typed.contains(' ') {
+            return part;
+        }
+
+        let count = index.initial_positions.len();
+        if count < INITIALS_MIN_LEN || !index.initials.starts_with(&typed) {
+            return part;
+        }
+
+        let typed_count = typed.chars().count();
+        let perfect = self
+            .matcher
+            .fuzzy(&typed, &typed, false)
+            .map_or(0.0, |(score, _)| score as f32);
+        part.score = perfect * INITIALS_WEIGHT * typed_count as f32 / count as f32;
+        for &position in index.initial_positions.iter().take(typed_count) {
+            part.indices.insert(position, 1.0);
+        }
+        part
+    }
+
     fn score_full(
         &self,
-        string: &str,
+        string: &Folded,
         query: &str,
         boost: f32,
         with_pos: bool,
         result: &mut SearchResultPart,
     ) {
-        if let Some((search, indices)) = self.matcher.fuzzy(string, query, with_pos) {
+        if let Some((search, indices)) = self.matcher.fuzzy_folded(string, query, with_pos) {
             result.score += search as f32;
             if with_pos {
                 for index in indices {
@@ -243,18 +1311,22 @@ impl SearchEngine {
         }
     }
 
+    /// Scores each word against each query, the indices are those of the whole string.
     fn score_small<V: AsRef<str>>(
         &self,
-        strings: &[&str],
+        string: &Folded,
+        words: &[Word],
         queries: &[V],
         with_pos: bool,
         result: &mut SearchResultPart,
     ) {
-        let count = strings.len() * queries.len();
-        for string in strings {
+        let count = words.len() * queries.len();
+        for word in words {
+            let chars = word.start..word.start + word.text.chars().count();
             for query in queries {
                 if let Some((search, indices)) =
-                    self.matcher.fuzzy(string, query.as_ref(), with_pos)
+                    self.matcher
+                        .fuzzy_in(string, chars.clone(), query.as_ref(), with_pos)
                 {
                     result.score += (search as f32 / count as f32) * 0.01;
                     if with_pos {
@@ -269,16 +1341,82 @@ impl SearchEngine {
     }
 }
 
+/// The last query and the apps that matched it, see [SearchEngine::search].
+struct LastSearch {
+    query: String,
+    /// The query after the rewrites, without tags and operators.
+    text: String,
+    filters: Vec<QueryFilter>,
+    /// The tags that filtered, the known ones.
+    tags: Vec<String>,
+    candidates: Vec<AppId>,
+}
+
 pub struct SearchQuery {
     text: String,
     parts: Vec<String>,
+    /// The `#tag` words, without the `#`.
+    tags: Vec<String>,
+    /// Operators like `category:Games`, all of them have to match.
+    filters: Vec<QueryFilter>,
+}
+
+/// A `[[search.rewrite]]` rule of the settings.
+pub struct QueryRewrite {
+    regex: Regex,
+    replace: String,
+}
+
+impl QueryRewrite {
+    /// Leaves out rules with invalid patterns.
+    fn compile(rules: &[RewriteRule]) -> Vec<QueryRewrite> {
+        rules
+            .iter()
+            .filter_map(|rule| {
+                let pattern = &rule.pattern;
+                let regex = Regex::new(pattern)
+                    .inspect_err(|error| warn!("Invalid rewrite pattern {pattern:?}: {error}"))
+                    .ok()?;
+                Some(QueryRewrite {
+                    regex,
+                    replace: rule.replace.clone(),
+                })
+            })
+            .collect()
+    }
 }
 
 impl SearchQuery {
-    pub fn from(query: String) -> SearchQuery {
+    /// Parses `query` after applying the `rewrites` to it in order. Phrases, tags and
+    /// operators are taken out first, so rules only see the words.
+    pub fn from(query: String, rewrites: &[QueryRewrite]) -> SearchQuery {
+        let (query, mut filters) = filter::take_phrases(&query);
+        let mut tags = Vec::new();
+        let mut words = Vec::new();
+        for word in query.split(' ') {
+            if let Some(filter) = QueryFilter::parse(word) {
+                filters.extend(filter);
+                continue;
+            }
+            match word.strip_prefix('#').filter(|v| !v.is_empty()) {
+                Some(tag) => tags.push(tag.to_lowercase()),
+                None => words.push(word),
+            }
+        }
+        let mut text = words.join(" ").trim().to_string();
+        for rewrite in rewrites {
+            text = rewrite
+                .regex
+                .replace_all(&text, &rewrite.replace)
+                .trim()
+                .to_string();
+        }
+
         SearchQuery {
-            parts: query.split(' ').map(|v| v.to_string()).collect(),
-            text: query,
+            parts: text.split(' ').map(|v| v.to_string()).collect(),
+            text,
+            tags,
+            filters,
         }
     }
 }
@@ -286,27 +1424,235 @@ impl SearchQuery {
 #[derive(Serialize, Deserialize, Default)]
 pub struct SearchData {
     pub uses: Vec<UseEntry>,
+    #[serde(default)]
+    pub pinned: Vec<AppId>,
+    /// When ignition was closed without launching anything.
+    #[serde(default)]
+    pub dismissals: Vec<DateTime<Utc>>,
+    /// Every launch, unlike `uses` limited by count instead of age.
+    #[serde(default)]
+    pub history: Vec<HistoryEntry>,
+    /// The failed launches in a row of apps whose last launch failed.
+    #[serde(default)]
+    pub failures: HashMap<AppId, u32>,
+    /// Tags added in the ui, the ones from the settings aren't stored here.
+    #[serde(default)]
+    pub tags: HashMap<AppId, Vec<String>>,
+    /// When apps were first found by their file id, to tell which ones are new, see
+    /// [SearchEngine::track_new_apps].
+    #[serde(default)]
+    pub first_found: HashMap<String, DateTime<Utc>>,
+    /// Apps picked below the top result, by the query they were picked for.
+    #[serde(default)]
+    pub choices: Vec<ChoiceEntry>,
+}
+
+/// How much a query found at the start of a word scores on top, compared to typing the
+/// name exactly, so "code" finds VS Code before Barcode.
+const WORD_START_WEIGHT: f32 = 0.5;
+
+/// How much typing the initials of a name scores, compared to typing the name exactly.
+const INITIALS_WEIGHT: f32 = 0.8;
+
+/// Names with fewer words, and shorter queries, are left to the fuzzy matching.
+const INITIALS_MIN_LEN: usize = 2;
+
+/// Apps that don't match every word are scored again for a longer query when they had at
+/// least this share of the best score, see [SearchEngine::search].
+const CANDIDATE_SHARE: f32 = 0.5;
+
+/// Scores are rounded to this in the deterministic mode.
+const SCORE_QUANTUM: f32 = 0.01;
+
+/// How much a name that only sounds like the query scores, compared to typing it exactly.
+const PHONETIC_WEIGHT: f32 = 0.5;
+
+/// How much each earlier pick of an app for a query multiplies its score.
+const CHOICE_BOOST: f32 = 0.5;
+
+/// Picks of one app beyond this don't raise its score any further.
+const CHOICES_MAX_COUNTED: f32 = 3.0;
+
+/// After how many days a launch counts half as much, unless the settings say otherwise.
+pub const DEFAULT_HALF_LIFE_DAYS: f32 = 14.0;
+
+/// Shorter half-lives would forget launches within hours.
+const MIN_HALF_LIFE_DAYS: f32 = 0.5;
+
+/// Launches are forgotten after this many half-lives, by then they count 1/64 at most.
+const USES_KEPT_HALF_LIVES: f32 = 6.0;
+
+/// How long launches are kept with a half-life of `half_life_days`.
+pub fn uses_max_age(half_life_days: f32) -> TimeDelta {
+    let days = half_life_days.max(MIN_HALF_LIFE_DAYS) * USES_KEPT_HALF_LIVES;
+    TimeDelta::hours((days * 24.0) as i64)
 }
 
-#[derive(Serialize, Deserialize)]
+/// After how many days a pick counts half as much.
+const CHOICES_HALF_LIFE_DAYS: f32 = 14.0;
+
+/// Picks are forgotten after this many days, or when there are too many.
+const CHOICES_MAX_DAYS: i64 = 90;
+const CHOICES_LIMIT: usize = 500;
+
+/// How much an app only launched at times like now multiplies its score.
+const TIME_OF_DAY_BOOST: f32 = 0.5;
+
+/// How often an app has to be launched at times like now before it counts.
+const TIME_OF_DAY_MIN_USES: usize = 3;
+
+/// After how many failed launches in a row an app counts as broken.
+const BROKEN_AFTER_FAILURES: u32 = 2;
+
+/// How many launches the history keeps.
+const HISTORY_LIMIT: usize = 200;
+
+/// An app that is gone this soon after it was launched likely crashed.
+const CRASH_WINDOW_SECS: i64 = 120;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HistoryEntry {
+    pub id: AppId,
+    pub at: DateTime<Utc>,
+    /// The files or URLs the app was launched with.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// The process we started, if it was still running after launching.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    /// When that process started, see [platform::process_start].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ChoiceEntry {
+    /// The query lowercased, without tags.
+    pub query: String,
+    pub id: AppId,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct UseEntry {
     pub id: AppId,
     pub at: DateTime<Utc>,
+    /// Milliseconds from opening ignition to this launch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_ms: Option<u64>,
 }
 
 #[derive(Default)]
 pub struct SearchScore {
     pub score: f32,
     pub indices: BTreeMap<usize, f32>,
+    /// How the score came together, in order, for the explain mode.
+    pub steps: Vec<ScoreStep>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum ScoreStep {
+    Add(&'static str, f32),
+    Multiply(&'static str, f32),
 }
+
 impl SearchScore {
-    pub fn add(&mut self, boost: f32, mut part: SearchResultPart) {
-        self.score += part.score * boost;
+    pub fn add(&mut self, label: &'static str, boost: f32, mut part: SearchResultPart) {
+        self.add_score(label, part.score * boost);
         self.indices.append(&mut part.indices);
     }
+
+    /// Adds `value`, parts with the same label in a row are summed up.
+    pub fn add_score(&mut self, label: &'static str, value: f32) {
+        self.score += value;
+        if value == 0.0 {
+            return;
+        }
+        match self.steps.last_mut() {
+            Some(ScoreStep::Add(last, sum)) if *last == label => *sum += value,
+            _ => self.steps.push(ScoreStep::Add(label, value)),
+        }
+    }
+
+    pub fn multiply(&mut self, label: &'static str, factor: f32) {
+        self.score *= factor;
+        if factor != 1.0 {
+            self.steps.push(ScoreStep::Multiply(label, factor));
+        }
+    }
+
+    /// The steps like `name +812.4 · popular ×1.32`, for tuning the scoring.
+    pub fn explain(&self) -> String {
+        self.steps
+            .iter()
+            .map(|step| match step {
+                ScoreStep::Add(label, value) => format!("{label} {value:+.1}"),
+                ScoreStep::Multiply(label, factor) => format!("{label} ×{factor:.2}"),
+            })
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
 }
 
 pub struct SearchResultPart {
     score: f32,
     indices: BTreeMap<usize, f32>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{SearchEngine, DEFAULT_HALF_LIFE_DAYS};
+    use crate::apps::{App, AppId, AppManager};
+    use std::collections::HashMap;
+
+    fn apps(names: &[&str]) -> AppManager {
+        let applications = names
+            .iter()
+            .map(|name| {
+                let id = AppId::from_properties(&[name]);
+                let app = App {
+                    id: id.clone(),
+                    name: name.to_string(),
+                    ..App::default()
+                };
+                (id, app)
+            })
+            .collect();
+        AppManager {
+            applications,
+            merged: HashMap::new(),
+        }
+    }
+
+    fn candidates(search: &SearchEngine, query: &str, apps: &AppManager) -> Vec<String> {
+        search.search(query.to_string(), apps);
+        let last = search.last_search.borrow();
+        let mut names: Vec<String> = last
+            .iter()
+            .flat_map(|v| &v.candidates)
+            .map(|id| apps.applications[id].name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn refines_extended_queries() {
+        let dir = std::env::temp_dir().join(format!("ignition-test-{}", std::process::id()));
+        let mut search = SearchEngine::new(&dir, None, DEFAULT_HALF_LIFE_DAYS).unwrap();
+        let apps = apps(&["Firefox", "Files", "Fish", "Calculator", "Terminal"]);
+        search.index_apps(&apps);
+
+        let f = candidates(&search, "f", &apps);
+        assert!(!f.contains(&"Calculator".to_string()));
+        let fi = candidates(&search, "fi", &apps);
+        let fir = candidates(&search, "fir", &apps);
+        assert!(fir.len() < fi.len() && fi.len() <= f.len());
+        assert!(fir.iter().all(|v| fi.contains(v)));
+        assert_eq!(fir, ["Firefox"]);
+
+        // Deleting a letter searches every app again.
+        assert_eq!(candidates(&search, "fi", &apps), fi);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
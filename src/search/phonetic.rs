@@ -0,0 +1,25 @@
+//! Matching by how words sound, so "jimp" still finds GIMP and "creeta" finds Krita.
+
+use rphonetic::{Encoder, Metaphone};
+
+/// Queries shorter than this sound like too many names to be useful.
+const MIN_QUERY_LEN: usize = 3;
+
+/// The words of `name` that sound like `query`, the whole name counting as one more word
+/// so "libre office" finds LibreOffice.
+pub fn matching_words<'a>(name: &'a str, query: &str) -> Vec<&'a str> {
+    let query: String = query.chars().filter(|c| c.is_alphabetic()).collect();
+    if query.chars().count() < MIN_QUERY_LEN {
+        return Vec::new();
+    }
+    let metaphone = Metaphone::default();
+    let code = metaphone.encode(&query);
+    if code.len() < 2 {
+        return Vec::new();
+    }
+
+    std::iter::once(name)
+        .chain(name.split([' ', '-']))
+        .filter(|word| metaphone.encode(word) == code)
+        .collect()
+}
@@ -0,0 +1,72 @@
+use crate::apps::AppId;
+use crate::search::{SearchData, UseEntry};
+use chrono::{Local, NaiveDate, TimeDelta};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// How many days the statistics go back.
+const STATS_DAYS: i64 = 30;
+
+/// Statistics over the uses of the last 30 days.
+pub struct UsageStats {
+    /// The launch count of every used app, the most launched first.
+    pub most_used: Vec<(AppId, u32)>,
+    /// The launches of each of the last days in local time, the oldest first.
+    pub per_day: Vec<(NaiveDate, u32)>,
+    /// How long it takes from opening ignition to launching something on average.
+    pub average_search_to_launch: Option<Duration>,
+    pub launches: usize,
+    /// How often ignition was closed without launching anything.
+    pub dismissals: usize,
+}
+
+impl UsageStats {
+    /// The share of the times ignition was opened that ended in a launch.
+    pub fn conversion_rate(&self) -> Option<f32> {
+        let opened = self.launches + self.dismissals;
+        (opened > 0).then(|| self.launches as f32 / opened as f32)
+    }
+}
+
+impl UsageStats {
+    pub fn compute(data: &SearchData, days: u32) -> UsageStats {
+        // Launches are kept longer for ranking.
+        let since = Local::now().to_utc() - TimeDelta::days(STATS_DAYS);
+        let recent: Vec<&UseEntry> = data.uses.iter().filter(|v| v.at >= since).collect();
+
+        let mut uses: HashMap<&AppId, u32> = HashMap::new();
+        for entry in &recent {
+            *uses.entry(&entry.id).or_default() += 1;
+        }
+        let mut most_used: Vec<(AppId, u32)> = uses
+            .into_iter()
+            .map(|(id, count)| (id.clone(), count))
+            .collect();
+        most_used.sort_by(|(a_id, a), (b_id, b)| b.cmp(a).then(a_id.cmp(b_id)));
+
+        let today = Local::now().date_naive();
+        let mut per_day: Vec<(NaiveDate, u32)> = (0..days as i64)
+            .rev()
+            .map(|ago| (today - TimeDelta::days(ago), 0))
+            .collect();
+        for entry in &recent {
+            let date = entry.at.with_timezone(&Local).date_naive();
+            if let Some((_, count)) = per_day.iter_mut().find(|(day, _)| *day == date) {
+                *count += 1;
+            }
+        }
+
+        // Older entries were recorded before we kept track of this.
+        let times: Vec<u64> = recent.iter().filter_map(|v| v.search_ms).collect();
+        let average_search_to_launch = (!times.is_empty())
+            .then(|| Duration::from_millis(times.iter().sum::<u64>() / times.len() as u64));
+
+        UsageStats {
+            most_used,
+            per_day,
+            average_search_to_launch,
+            launches: recent.len(),
+            dismissals: data.dismissals.len(),
+        }
+    }
+}
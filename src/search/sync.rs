@@ -0,0 +1,94 @@
+//! Shares the usage data between machines through a directory that is synced
+//! by something else, like Syncthing or Nextcloud.
+//!
+//! Every machine writes its uses to its own `uses-<machine>.json`, so two machines
+//! never write the same file, and merges the files of the others into its own data.
+
+use crate::config::Config;
+use crate::paths::create_private_dir;
+use crate::search::{SearchData, UseEntry};
+use chrono::{DateTime, Utc};
+use eyre::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::env;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+#[derive(Serialize, Deserialize, Default)]
+struct SyncData {
+    uses: Vec<UseEntry>,
+}
+
+/// Adds the uses from the other machines' files to `data` that are newer than `since`,
+/// returns how many were new.
+pub fn merge(dir: &Path, data: &mut SearchData, since: DateTime<Utc>) -> eyre::Result<usize> {
+    let own_file = own_file(dir);
+    let mut seen: HashSet<(String, DateTime<Utc>)> = data
+        .uses
+        .iter()
+        .map(|entry| (entry.id.0.clone(), entry.at))
+        .collect();
+
+    let mut added = 0;
+    for entry in dir.read_dir().wrap_err("Failed to list sync dir")? {
+        let path = entry?.path();
+        let is_uses_file = path
+            .file_name()
+            .and_then(|v| v.to_str())
+            .is_some_and(|v| v.starts_with("uses-") && v.ends_with(".json"));
+        if !is_uses_file || path == own_file {
+            continue;
+        }
+
+        let other: SyncData = match Config::read_file(&path) {
+            Ok(other) => other,
+            Err(error) => {
+                warn!("Could not read {path:?}: {error:?}");
+                continue;
+            }
+        };
+        for entry in other.uses {
+            // Both sides only keep this much.
+            if entry.at < since {
+                continue;
+            }
+            if seen.insert((entry.id.0.clone(), entry.at)) {
+                data.uses.push(entry);
+                added += 1;
+            }
+        }
+    }
+
+    if added > 0 {
+        data.uses.sort_by_key(|entry| entry.at);
+        info!("Merged {added} uses from {dir:?}");
+    }
+    Ok(added)
+}
+
+/// Writes our uses to our file in the sync dir, for the other machines to merge.
+pub fn publish(dir: &Path, data: &SearchData) -> eyre::Result<()> {
+    create_private_dir(dir).wrap_err("Failed to create sync dir")?;
+    let sync_data = SyncData {
+        uses: data.uses.clone(),
+    };
+    Config::write_file(&own_file(dir), &sync_data)
+}
+
+fn own_file(dir: &Path) -> PathBuf {
+    dir.join(format!("uses-{}.json", machine_name()))
+}
+
+fn machine_name() -> String {
+    let name = env::var("HOSTNAME")
+        .or_else(|_| env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| read_to_string("/etc/hostname").ok())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "default".to_string());
+    // It ends up in a file name.
+    name.replace(['/', '\\', ':'], "_")
+}
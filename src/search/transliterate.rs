@@ -0,0 +1,190 @@
+//! Latin spellings of names in other scripts, so "telegram" finds "Телеграм". Each locale
+//! of `[search] transliterate` adds its own, see [by_locale].
+
+use crate::search::cjk;
+use tracing::warn;
+
+/// Spells names of a script with Latin letters.
+pub trait Transliteration {
+    /// The names `name` can be found by besides itself, empty when it has nothing to spell.
+    fn search_names(&self, name: &str) -> Vec<String>;
+}
+
+/// Pinyin and romaji, see [cjk::search_names].
+struct Cjk;
+
+impl Transliteration for Cjk {
+    fn search_names(&self, name: &str) -> Vec<String> {
+        cjk::search_names(name)
+    }
+}
+
+/// Replaces each letter in the table, others are kept.
+struct Table(&'static [(char, &'static str)]);
+
+impl Transliteration for Table {
+    fn search_names(&self, name: &str) -> Vec<String> {
+        let mut spelled = String::new();
+        let mut changed = false;
+        for c in name.chars().flat_map(char::to_lowercase) {
+            match self.0.iter().find(|(letter, _)| *letter == c) {
+                Some((_, latin)) => {
+                    spelled.push_str(latin);
+                    changed = true;
+                }
+                None => spelled.push(c),
+            }
+        }
+        if changed {
+            vec![spelled]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+const RUSSIAN: &[(char, &str)] = &[
+    ('а', "a"),
+    ('б', "b"),
+    ('в', "v"),
+    ('г', "g"),
+    ('д', "d"),
+    ('е', "e"),
+    ('ё', "e"),
+    ('ж', "zh"),
+    ('з', "z"),
+    ('и', "i"),
+    ('й', "y"),
+    ('к', "k"),
+    ('л', "l"),
+    ('м', "m"),
+    ('н', "n"),
+    ('о', "o"),
+    ('п', "p"),
+    ('р', "r"),
+    ('с', "s"),
+    ('т', "t"),
+    ('у', "u"),
+    ('ф', "f"),
+    ('х', "kh"),
+    ('ц', "ts"),
+    ('ч', "ch"),
+    ('ш', "sh"),
+    ('щ', "shch"),
+    ('ъ', ""),
+    ('ы', "y"),
+    ('ь', ""),
+    ('э', "e"),
+    ('ю', "yu"),
+    ('я', "ya"),
+];
+
+/// Like Russian, except for the letters Ukrainian reads differently or only it has.
+const UKRAINIAN: &[(char, &str)] = &[
+    ('г', "h"),
+    ('ґ', "g"),
+    ('е', "e"),
+    ('є', "ye"),
+    ('и', "y"),
+    ('і', "i"),
+    ('ї', "yi"),
+    ('а', "a"),
+    ('б', "b"),
+    ('в', "v"),
+    ('д', "d"),
+    ('ж', "zh"),
+    ('з', "z"),
+    ('й', "y"),
+    ('к', "k"),
+    ('л', "l"),
+    ('м', "m"),
+    ('н', "n"),
+    ('о', "o"),
+    ('п', "p"),
+    ('р', "r"),
+    ('с', "s"),
+    ('т', "t"),
+    ('у', "u"),
+    ('ф', "f"),
+    ('х', "kh"),
+    ('ц', "ts"),
+    ('ч', "ch"),
+    ('ш', "sh"),
+    ('щ', "shch"),
+    ('ь', ""),
+    ('ю', "yu"),
+    ('я', "ya"),
+];
+
+const GREEK: &[(char, &str)] = &[
+    ('α', "a"),
+    ('ά', "a"),
+    ('β', "v"),
+    ('γ', "g"),
+    ('δ', "d"),
+    ('ε', "e"),
+    ('έ', "e"),
+    ('ζ', "z"),
+    ('η', "i"),
+    ('ή', "i"),
+    ('θ', "th"),
+    ('ι', "i"),
+    ('ί', "i"),
+    ('ϊ', "i"),
+    ('ΐ', "i"),
+    ('κ', "k"),
+    ('λ', "l"),
+    ('μ', "m"),
+    ('ν', "n"),
+    ('ξ', "x"),
+    ('ο', "o"),
+    ('ό', "o"),
+    ('π', "p"),
+    ('ρ', "r"),
+    ('σ', "s"),
+    ('ς', "s"),
+    ('τ', "t"),
+    ('υ', "y"),
+    ('ύ', "y"),
+    ('ϋ', "y"),
+    ('ΰ', "y"),
+    ('φ', "f"),
+    ('χ', "ch"),
+    ('ψ', "ps"),
+    ('ω', "o"),
+    ('ώ', "o"),
+];
+
+/// The transliteration for a locale like `ru`, `uk`, `el`, `zh` or `ja`.
+pub fn by_locale(locale: &str) -> Option<Box<dyn Transliteration>> {
+    // Only the language counts, `zh-TW` is like `zh`.
+    let language = locale.split(['-', '_']).next().unwrap_or_default();
+    Some(match language.to_lowercase().as_str() {
+        "zh" | "ja" => Box::new(Cjk),
+        "ru" | "be" | "bg" => Box::new(Table(RUSSIAN)),
+        "uk" => Box::new(Table(UKRAINIAN)),
+        "el" => Box::new(Table(GREEK)),
+        _ => return None,
+    })
+}
+
+/// The transliterations of `locales`, unknown ones are left out.
+pub fn for_locales(locales: &[String]) -> Vec<Box<dyn Transliteration>> {
+    locales
+        .iter()
+        .filter_map(|locale| {
+            let found = by_locale(locale);
+            if found.is_none() {
+                warn!("No transliteration for the locale {locale:?}");
+            }
+            found
+        })
+        .collect()
+}
+
+/// Whether `name` has letters of a script some transliteration spells, so its localized
+/// names are worth searching too.
+pub fn is_other_script(name: &str) -> bool {
+    name.chars()
+        .any(|c| cjk::is_cjk(c) || matches!(c, '\u{0370}'..='\u{03ff}' | '\u{0400}'..='\u{04ff}'))
+}
@@ -0,0 +1,76 @@
+//! Splits names into words at spaces, `-`, `_` and `.`, and at CamelCase humps, keeping
+//! where each word starts so matches in a word can be highlighted in the whole name.
+
+use std::ops::Range;
+
+/// A word of a name.
+pub struct Word<'a> {
+    /// The char index in the name it starts at.
+    pub start: usize,
+    pub text: &'a str,
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, ' ' | '-' | '_' | '.')
+}
+
+/// The words of `text`, "VSCodeInsiders" is "VS", "Code" and "Insiders".
+pub fn split(text: &str) -> Vec<Word<'_>> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut words = Vec::new();
+    // The char index and byte offset of the current word.
+    let mut current: Option<(usize, usize)> = None;
+    for (index, &(offset, c)) in chars.iter().enumerate() {
+        if is_separator(c) {
+            if let Some((start, from)) = current.take() {
+                words.push(Word {
+                    start,
+                    text: &text[from..offset],
+                });
+            }
+            continue;
+        }
+
+        let previous = index.checked_sub(1).map(|i| chars[i].1);
+        let next = chars.get(index + 1).map(|(_, c)| *c);
+        // "eC" in "VSCode", and "SC" in "VSCode" since the "C" starts "Code".
+        let hump = c.is_uppercase()
+            && previous.is_some_and(|p| {
+                p.is_lowercase() || (p.is_uppercase() && next.is_some_and(char::is_lowercase))
+            });
+        if hump {
+            if let Some((start, from)) = current.take() {
+                words.push(Word {
+                    start,
+                    text: &text[from..offset],
+                });
+            }
+        }
+        current.get_or_insert((index, offset));
+    }
+    if let Some((start, from)) = current {
+        words.push(Word {
+            start,
+            text: &text[from..],
+        });
+    }
+    words
+}
+
+/// The chars of `text` that `query` matches at the start of a word, ignoring case. "code"
+/// matches in "Visual Studio Code" but not in "Barcode".
+pub fn match_at_word_start(text: &str, query: &str) -> Option<Range<usize>> {
+    let query: Vec<char> = query.chars().collect();
+    if query.is_empty() {
+        return None;
+    }
+    let chars: Vec<char> = text.chars().collect();
+    split(text).into_iter().find_map(|word| {
+        let rest = chars.get(word.start..word.start + query.len())?;
+        let matches = rest
+            .iter()
+            .zip(&query)
+            .all(|(c, q)| c.to_lowercase().eq(q.to_lowercase()));
+        matches.then(|| word.start..word.start + query.len())
+    })
+}
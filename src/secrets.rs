@@ -0,0 +1,252 @@
+//! Passwords from `pass`, the Secret Service and the Bitwarden CLI, searched by name and
+//! copied to the clipboard. The password itself is never shown or logged.
+
+use crate::apps::AppId;
+use crate::platform;
+use crate::search::{EntryAction, SearchResultEntry, SearchScore};
+use crate::settings::{SecretBackend, SecretSettings};
+use crossbeam::channel::{bounded, Receiver, TryRecvError};
+use eyre::{bail, Context};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread::{sleep, spawn};
+use std::time::Duration;
+use std::{env, fs, io};
+use tracing::{info, warn};
+
+/// A password by its name, without the password.
+#[derive(Clone, Debug)]
+pub struct SecretEntry {
+    pub backend: SecretBackend,
+    /// What the backend knows it by, the path in the store or the id of the item.
+    id: String,
+    pub name: String,
+}
+
+/// The entries of all backends, listed on a background thread since `bw` takes a while.
+#[derive(Default)]
+pub struct SecretIndex {
+    entries: Vec<SecretEntry>,
+    pending: Option<Receiver<Vec<SecretEntry>>>,
+}
+
+impl SecretIndex {
+    pub fn load(settings: &SecretSettings) -> SecretIndex {
+        if settings.backends.is_empty() {
+            return SecretIndex::default();
+        }
+        let backends = settings.backends.clone();
+        let (sender, receiver) = bounded(1);
+        spawn(move || {
+            let mut entries = Vec::new();
+            for backend in backends {
+                match list(backend) {
+                    Ok(mut list) => entries.append(&mut list),
+                    Err(error) => warn!("Could not list the passwords of {backend:?}: {error:?}"),
+                }
+            }
+            let _ = sender.send(entries);
+        });
+        SecretIndex {
+            entries: Vec::new(),
+            pending: Some(receiver),
+        }
+    }
+
+    /// Returns `true` once the entries arrived.
+    pub fn tick(&mut self) -> bool {
+        let Some(pending) = &self.pending else {
+            return false;
+        };
+        match pending.try_recv() {
+            Ok(entries) => {
+                info!("Found {} passwords", entries.len());
+                self.entries = entries;
+                self.pending = None;
+                true
+            }
+            Err(TryRecvError::Empty) => false,
+            Err(TryRecvError::Disconnected) => {
+                self.pending = None;
+                false
+            }
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// The entries with every word of `query` in their name, as results that copy them.
+    pub fn search(&self, query: &str) -> Vec<SearchResultEntry> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let mut entries: Vec<&SecretEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| {
+                let name = entry.name.to_lowercase();
+                words.iter().all(|word| name.contains(word.as_str()))
+            })
+            .collect();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+        entries
+            .into_iter()
+            .map(|entry| SearchResultEntry {
+                id: AppId::from_properties(&["secret", backend_name(entry.backend), &entry.id]),
+                score: SearchScore::default(),
+                detail: None,
+                broken: false,
+                action: Some(EntryAction::CopySecret(entry.clone())),
+                confirm: false,
+            })
+            .collect()
+    }
+}
+
+fn backend_name(backend: SecretBackend) -> &'static str {
+    match backend {
+        SecretBackend::Pass => "pass",
+        SecretBackend::SecretService => "secret-service",
+        SecretBackend::Bitwarden => "bitwarden",
+    }
+}
+
+/// Copies the password of `entry` and clears the clipboard again after `clear_after`,
+/// unless something else was copied since. Blocks until then.
+pub fn copy(entry: &SecretEntry, clear_after: Duration) -> eyre::Result<()> {
+    let secret = reveal(entry)?;
+    platform::set_clipboard_text(&secret).wrap_err("Failed to copy the password")?;
+    info!("Copied the password of {}", entry.name);
+    if clear_after.is_zero() {
+        return Ok(());
+    }
+
+    sleep(clear_after);
+    match platform::clipboard_text() {
+        Ok(text) if text == secret => {
+            platform::set_clipboard_text("").wrap_err("Failed to clear the clipboard")?;
+            info!("Cleared the clipboard");
+        }
+        Ok(_) => info!("Something else was copied, leaving the clipboard"),
+        Err(error) => warn!("Could not read the clipboard to clear it: {error:?}"),
+    }
+    Ok(())
+}
+
+fn list(backend: SecretBackend) -> eyre::Result<Vec<SecretEntry>> {
+    let entries = match backend {
+        SecretBackend::Pass => list_pass()?,
+        SecretBackend::SecretService => list_secret_service()?,
+        SecretBackend::Bitwarden => list_bitwarden()?,
+    };
+    Ok(entries
+        .into_iter()
+        .map(|(id, name)| SecretEntry { backend, id, name })
+        .collect())
+}
+
+fn reveal(entry: &SecretEntry) -> eyre::Result<String> {
+    match entry.backend {
+        SecretBackend::Pass => {
+            let output = run_secret("pass", &["show", &entry.id])?;
+            // The password is the first line, the rest are notes like the username.
+            Ok(output.lines().next().unwrap_or_default().to_string())
+        }
+        SecretBackend::SecretService => reveal_secret_service(&entry.id),
+        SecretBackend::Bitwarden => run_secret("bw", &["get", "password", &entry.id]),
+    }
+}
+
+/// Runs a command that prints a secret, its output never ends up in the error.
+fn run_secret(program: &str, args: &[&str]) -> eyre::Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .wrap_err_with(|| format!("Failed to run {program}"))?;
+    if !output.status.success() {
+        bail!("{program} exited with {}", output.status);
+    }
+    String::from_utf8(output.stdout).wrap_err_with(|| format!("{program} printed invalid UTF-8"))
+}
+
+fn pass_dir() -> Option<PathBuf> {
+    env::var_os("PASSWORD_STORE_DIR")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".password-store")))
+}
+
+/// The `.gpg` files in the password store, by their path without the extension.
+fn list_pass() -> eyre::Result<Vec<(String, String)>> {
+    let Some(dir) = pass_dir() else {
+        bail!("Could not find the password store");
+    };
+    let mut names = Vec::new();
+    find_pass_entries(&dir, &dir, &mut names)
+        .wrap_err_with(|| format!("Failed to read the password store {dir:?}"))?;
+    Ok(names.into_iter().map(|name| (name.clone(), name)).collect())
+}
+
+fn find_pass_entries(root: &Path, dir: &Path, out: &mut Vec<String>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        // Leaves out `.git` and `.gpg-id`.
+        if path
+            .file_name()
+            .is_some_and(|v| v.to_string_lossy().starts_with('.'))
+        {
+            continue;
+        }
+        if path.is_dir() {
+            find_pass_entries(root, &path, out)?;
+        } else if path.extension().is_some_and(|v| v == "gpg") {
+            if let Ok(name) = path.with_extension("").strip_prefix(root) {
+                out.push(name.to_string_lossy().to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct BitwardenItem {
+    id: String,
+    name: String,
+    #[serde(rename = "type")]
+    kind: u8,
+}
+
+/// The logins in the vault, `bw list` prints the passwords too but we only keep the names.
+fn list_bitwarden() -> eyre::Result<Vec<(String, String)>> {
+    let output = run_secret("bw", &["list", "items"])?;
+    let items: Vec<BitwardenItem> =
+        serde_json::from_str(&output).wrap_err("Failed to parse the output of bw")?;
+    Ok(items
+        .into_iter()
+        .filter(|item| item.kind == 1)
+        .map(|item| (item.id, item.name))
+        .collect())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn list_secret_service() -> eyre::Result<Vec<(String, String)>> {
+    platform::secret_service::items()
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn reveal_secret_service(id: &str) -> eyre::Result<String> {
+    platform::secret_service::secret(id)
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn list_secret_service() -> eyre::Result<Vec<(String, String)>> {
+    bail!("The Secret Service only exists on Linux")
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn reveal_secret_service(_id: &str) -> eyre::Result<String> {
+    bail!("The Secret Service only exists on Linux")
+}
@@ -0,0 +1,741 @@
+use crate::i18n;
+use crate::paths::{expand_home, write_private};
+use crate::search::{transliterate, DEFAULT_HALF_LIFE_DAYS};
+use eyre::{bail, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::fs::{create_dir_all, read_to_string};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, TableLike};
+use tracing::{error, info};
+
+/// The user settings, read from `config.toml` in the config dir.
+///
+/// Every field has a default, so a missing file or a missing key is never an error.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub window: WindowSettings,
+    pub home: HomeSettings,
+    pub new_apps: NewAppsSettings,
+    pub animation: AnimationSettings,
+    pub sync: SyncSettings,
+    pub terminal: TerminalSettings,
+    pub launch: LaunchSettings,
+    pub search: SearchSettings,
+    pub icons: IconSettings,
+    pub fonts: FontSettings,
+    /// Colors replacing the bundled Catppuccin ones by name, like `text = "#ffffff"`.
+    pub theme: HashMap<String, String>,
+    pub keys: KeySettings,
+    pub secrets: SecretSettings,
+    pub bookmarks: BookmarkSettings,
+    pub web: WebSearchSettings,
+    pub projects: ProjectSettings,
+    pub search_providers: SearchProviderSettings,
+    pub appimages: AppImageSettings,
+    pub wine: WineSettings,
+    /// More directories to find apps in, between the user's and the system's.
+    pub app_dirs: Vec<PathBuf>,
+    /// Apps without a desktop entry (Linux only).
+    pub apps: Vec<SimpleEntry>,
+    /// Show apps with the same Exec line once, like a browser installed twice, and Flatpaks
+    /// with the name of a native app.
+    pub merge_duplicates: bool,
+    /// Globs on the path, file id or name of apps to leave out, like `*-handler`.
+    pub ignore: Vec<String>,
+    /// How many seconds the results of slow app providers are reused, by provider name.
+    pub cache_ttl: HashMap<String, u64>,
+    /// Score multipliers for apps, by file id (like `org.gnome.Nautilus`) or name.
+    pub boost: HashMap<String, f32>,
+    /// Display names for apps, by file id or name. Older settings had these, they are moved
+    /// into the `name` of [Settings::overrides] when loading.
+    #[serde(skip_serializing)]
+    pub rename: HashMap<String, String>,
+    /// Hide, rename or re-icon apps without touching their files, by file id or name.
+    pub overrides: HashMap<String, AppOverride>,
+    /// Tags with the file ids or names of their apps, searchable with `#tag`.
+    pub tags: HashMap<String, Vec<String>>,
+    pub wrappers: Vec<LaunchWrapper>,
+    /// Resource limits for apps, by file id or name.
+    pub resources: HashMap<String, ResourceLimits>,
+    /// The settings as they were loaded, so saving writes only what changed since.
+    /// `None` when the file couldn't be read, which must not be replaced with the defaults.
+    #[serde(skip)]
+    loaded: Option<toml::Table>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct WindowSettings {
+    /// On X11, bypass the window manager (override-redirect) and grab the keyboard,
+    /// so we get keys above fullscreen windows and in WMs that don't focus us.
+    pub x11_grab: bool,
+    /// Always open on this output (like `DP-1`) instead of the one with the cursor.
+    pub output: Option<String>,
+    pub layout: Layout,
+    /// Mark apps that already have open windows, X11 only.
+    pub running_indicator: bool,
+    /// What the result rows show, see [crate::ui::template].
+    pub row_template: Option<String>,
+    pub renderer: Renderer,
+    /// X11 only: right away show a window the X server fills with the theme colors, and
+    /// replace it once the apps are loaded and the renderer drew the first frame.
+    pub placeholder: bool,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Layout {
+    /// The results as a vertical list.
+    #[default]
+    List,
+    /// A compact horizontal row of icons, like a dock. It opens centered like the list,
+    /// ignition can't dock at a screen edge as a layer-shell surface.
+    Bar,
+}
+
+/// What draws the window.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Renderer {
+    /// wgpu, through Vulkan, Metal or DirectX.
+    #[default]
+    Wgpu,
+    /// OpenGL, which sets up a lot faster than wgpu, so the window shows up sooner.
+    Glow,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchSettings {
+    pub matcher: MatcherKind,
+    /// Round scores and break ties by file id, so apps that score about the same
+    /// always come in the same order and the top hit doesn't flicker.
+    pub deterministic: bool,
+    /// Also find apps whose names sound like the query, like GIMP for "jimp".
+    pub phonetic: bool,
+    /// Boost apps usually launched around this time, on weekdays or on the weekend.
+    pub time_of_day: bool,
+    /// After how many days a launch counts half as much when ranking apps.
+    pub frecency_half_life_days: f32,
+    /// How many apps a search lists at most, all of them when unset.
+    pub max_results: Option<usize>,
+    /// Apps scoring below this share of the best one (0 to 1) are left out instead of faded.
+    pub min_score: f32,
+    /// The locales whose names are also found spelled in Latin letters, like `ru`. The
+    /// user's locale when it has a transliteration.
+    pub transliterate: Vec<String>,
+    /// Applied to the query in order before matching.
+    pub rewrite: Vec<RewriteRule>,
+}
+
+/// Replaces what `pattern` (a regex) matches in the query with `replace`, which can
+/// refer to groups like `$1`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RewriteRule {
+    pub pattern: String,
+    pub replace: String,
+}
+
+impl Default for SearchSettings {
+    fn default() -> Self {
+        SearchSettings {
+            matcher: MatcherKind::default(),
+            deterministic: true,
+            phonetic: false,
+            time_of_day: false,
+            frecency_half_life_days: DEFAULT_HALF_LIFE_DAYS,
+            max_results: None,
+            min_score: 0.0,
+            // Only the user's own script, the others would add spellings nobody types.
+            transliterate: i18n::user_locale()
+                .filter(|locale| transliterate::by_locale(locale).is_some())
+                .into_iter()
+                .collect(),
+            rewrite: Vec::new(),
+        }
+    }
+}
+
+/// Another icon font instead of the bundled Material Icons, like a Nerd Font.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct IconSettings {
+    pub font: Option<PathBuf>,
+    /// The glyphs to draw instead of the bundled ones, by icon name (like `search`).
+    pub glyphs: HashMap<String, char>,
+    /// The icon theme to find app icons in, instead of the desktop's (Linux only).
+    pub theme: Option<String>,
+}
+
+impl IconSettings {
+    /// The theme setting or the icon theme of the desktop.
+    pub fn theme(&self) -> Option<String> {
+        self.theme.clone().or_else(crate::platform::icon_theme)
+    }
+}
+
+/// Fonts of the system to use before the bundled Mukta, found with fontconfig (Linux only).
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct FontSettings {
+    /// Family names or aliases like `sans-serif`. The first one is used for text, the others
+    /// for characters it lacks.
+    pub families: Vec<String>,
+}
+
+/// Shortcuts of the window like `Ctrl+P`, `Ctrl+Shift+H` or `F5`. An empty one is off.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct KeySettings {
+    pub pin: String,
+    pub details: String,
+    pub explain: String,
+    pub stats: String,
+    pub history: String,
+    pub settings: String,
+    pub reload_icons: String,
+    pub rescan: String,
+}
+
+impl Default for KeySettings {
+    fn default() -> Self {
+        KeySettings {
+            pin: "Ctrl+P".to_string(),
+            details: "Ctrl+I".to_string(),
+            explain: "Ctrl+D".to_string(),
+            stats: "Ctrl+S".to_string(),
+            history: "Ctrl+H".to_string(),
+            settings: "Ctrl+,".to_string(),
+            reload_icons: "Ctrl+R".to_string(),
+            rescan: "F5".to_string(),
+        }
+    }
+}
+
+/// The fuzzy matching backend.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatcherKind {
+    /// The matcher of skim, what ignition always used.
+    #[default]
+    Skim,
+    /// The matcher of nucleo (used by helix), faster and closer to fzf's ranking.
+    Nucleo,
+}
+
+/// The view shown while the query is empty.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct HomeSettings {
+    /// Show the sections below, otherwise all apps are listed.
+    pub enabled: bool,
+    pub pinned: usize,
+    pub recent: usize,
+    pub most_used: usize,
+}
+
+impl Default for HomeSettings {
+    fn default() -> Self {
+        HomeSettings {
+            enabled: true,
+            pinned: 10,
+            recent: 5,
+            most_used: 5,
+        }
+    }
+}
+
+/// How apps that were installed recently stand out.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct NewAppsSettings {
+    /// For how many days after they were first found apps count as new, 0 turns it off.
+    pub days: u32,
+    /// Score multiplier for new apps.
+    pub boost: f32,
+}
+
+impl Default for NewAppsSettings {
+    fn default() -> Self {
+        NewAppsSettings {
+            days: 3,
+            boost: 1.5,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnimationSettings {
+    /// Turns off animations that move or scale things.
+    pub reduce_motion: bool,
+    /// How long the window takes to fade in on open and out on close.
+    pub transition_ms: u64,
+}
+
+impl Default for AnimationSettings {
+    fn default() -> Self {
+        AnimationSettings {
+            reduce_motion: false,
+            transition_ms: 120,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct SyncSettings {
+    /// A directory synced between machines, the usage data of all of them is merged.
+    pub dir: Option<PathBuf>,
+}
+
+impl SyncSettings {
+    pub fn dir(&self) -> Option<PathBuf> {
+        self.dir.as_deref().map(expand_home)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalSettings {
+    /// The terminal emulator, also for `Terminal=true` apps. `$TERMINAL` or the first common
+    /// one installed when unset.
+    pub command: Option<String>,
+    /// Offers to open a terminal in the directory typed after this, a plain path works too.
+    /// Anything else after it is offered to run as a command.
+    pub prefix: String,
+}
+
+impl Default for TerminalSettings {
+    fn default() -> Self {
+        TerminalSettings {
+            command: None,
+            prefix: ">".to_string(),
+        }
+    }
+}
+
+impl TerminalSettings {
+    /// The terminal setting, `$TERMINAL` or the first common terminal emulator installed.
+    pub fn command(&self) -> Option<String> {
+        self.command
+            .clone()
+            .or_else(|| env::var("TERMINAL").ok())
+            .or_else(crate::platform::default_terminal)
+    }
+}
+
+/// AppImages to list as apps, with the name and icon of their embedded desktop entry
+/// (Linux only).
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppImageSettings {
+    /// The directories to find `*.AppImage` files in, not their subdirectories.
+    pub dirs: Vec<PathBuf>,
+}
+
+impl Default for AppImageSettings {
+    fn default() -> Self {
+        AppImageSettings {
+            dirs: vec![PathBuf::from("~/Applications")],
+        }
+    }
+}
+
+/// The desktop entries Wine and Proton create for Windows programs.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct WineSettings {
+    /// The tag these apps get, none when empty.
+    pub tag: String,
+    /// Show a program installed in several Wine prefixes once.
+    pub collapse_prefixes: bool,
+}
+
+impl Default for WineSettings {
+    fn default() -> Self {
+        WineSettings {
+            tag: "windows".to_string(),
+            collapse_prefixes: false,
+        }
+    }
+}
+
+/// Results from the GNOME Shell search providers of apps like Files, Calculator or Characters.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchProviderSettings {
+    pub enabled: bool,
+    /// Desktop ids of providers to leave out, like `org.gnome.Software.desktop`.
+    pub disabled: Vec<String>,
+    /// How many results of each provider are shown.
+    pub max_results: usize,
+}
+
+impl Default for SearchProviderSettings {
+    fn default() -> Self {
+        SearchProviderSettings {
+            enabled: true,
+            disabled: Vec::new(),
+            max_results: 3,
+        }
+    }
+}
+
+/// Recent projects of VS Code or one of its forks, like VSCodium or Cursor.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ProjectSettings {
+    pub enabled: bool,
+    /// Opens a project, like `codium` or `cursor --new-window`.
+    pub command: String,
+    /// The editor's directory in the config directory, like `VSCodium`. Known for `code`,
+    /// `code-oss`, `codium` and `cursor`.
+    pub config_dir: Option<String>,
+    /// Shown in the entries, defaults to what `config_dir` is for the known editors.
+    pub name: Option<String>,
+}
+
+impl Default for ProjectSettings {
+    fn default() -> Self {
+        ProjectSettings {
+            enabled: true,
+            command: "code".to_string(),
+            config_dir: None,
+            name: None,
+        }
+    }
+}
+
+/// Offers to search the web when no app matches the query well.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebSearchSettings {
+    pub enabled: bool,
+    /// The search URL, `{}` is replaced with the query.
+    pub url: String,
+    /// Offer it when the best app scores below this. Matching the start of a name scores
+    /// in the thousands, matching only a comment or keyword scores well below 100.
+    pub threshold: f32,
+    /// URL templates for `!name` in the query, by name. A bang always offers its search.
+    pub bangs: HashMap<String, String>,
+}
+
+impl Default for WebSearchSettings {
+    fn default() -> Self {
+        let bangs = [
+            ("yt", "https://www.youtube.com/results?search_query={}"),
+            ("gh", "https://github.com/search?q={}"),
+            ("w", "https://en.wikipedia.org/w/index.php?search={}"),
+        ];
+        WebSearchSettings {
+            enabled: true,
+            url: "https://duckduckgo.com/?q={}".to_string(),
+            threshold: 100.0,
+            bangs: bangs
+                .into_iter()
+                .map(|(name, url)| (name.to_string(), url.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// Directories offered to open in the file manager, a terminal or an editor.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct BookmarkSettings {
+    /// Include the bookmarks of the GTK file chooser and Files (Nautilus).
+    pub gtk: bool,
+    /// More directories, `~` works.
+    pub dirs: Vec<PathBuf>,
+    /// Opens a directory in an editor, like `code`. When unset `$VISUAL` or `$EDITOR`
+    /// runs in a terminal.
+    pub editor: Option<String>,
+}
+
+impl Default for BookmarkSettings {
+    fn default() -> Self {
+        BookmarkSettings {
+            gtk: true,
+            dirs: Vec::new(),
+            editor: None,
+        }
+    }
+}
+
+/// Passwords found by name and copied to the clipboard, they are never shown.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecretSettings {
+    /// Where to look for passwords, nothing is searched while this is empty.
+    pub backends: Vec<SecretBackend>,
+    /// Searches the passwords instead of the apps for the query typed after this.
+    pub prefix: String,
+    /// Seconds until the clipboard is cleared again, 0 leaves the password there.
+    pub clear_after: u64,
+}
+
+impl Default for SecretSettings {
+    fn default() -> Self {
+        SecretSettings {
+            backends: Vec::new(),
+            prefix: "pw ".to_string(),
+            clear_after: 30,
+        }
+    }
+}
+
+impl SecretSettings {
+    /// The name searched for, when `query` starts with the prefix.
+    pub fn query<'a>(&self, query: &'a str) -> Option<&'a str> {
+        if self.backends.is_empty() || self.prefix.is_empty() {
+            return None;
+        }
+        query.trim_start().strip_prefix(self.prefix.as_str())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretBackend {
+    /// The standard unix password manager, `pass`.
+    Pass,
+    /// The keyring of the desktop, like GNOME Keyring or KWallet (Linux only).
+    SecretService,
+    /// The Bitwarden CLI `bw`, it has to be unlocked with `BW_SESSION` set.
+    Bitwarden,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct LaunchSettings {
+    /// Start apps with the environment of a login shell instead of the one ignition got,
+    /// which can be stale when ignition runs as a daemon.
+    pub login_env: bool,
+    /// Show a notification when an app was started.
+    pub notify: bool,
+}
+
+/// Starts the apps in some categories through a command, like games through `gamemoderun`.
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct LaunchWrapper {
+    /// Desktop entry categories, like `Game` or `Development`.
+    pub categories: Vec<String>,
+    pub command: Vec<String>,
+    pub env: HashMap<String, String>,
+}
+
+/// An app defined in the settings, for commands that have no desktop entry.
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct SimpleEntry {
+    pub name: String,
+    /// Run like the Exec line of a desktop entry, `%U` takes the files to open.
+    pub exec: String,
+    /// An icon name of the theme or a path.
+    pub icon: Option<String>,
+    pub comment: Option<String>,
+    pub keywords: Vec<String>,
+    pub categories: Vec<String>,
+    /// Runs in the `[terminal]`.
+    pub terminal: bool,
+}
+
+/// Changes to an app that leave its desktop entry, shortcut or bundle alone.
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct AppOverride {
+    /// Leaves the app out, like `ignore`.
+    pub hidden: bool,
+    /// Shown and searched instead of its name.
+    pub name: Option<String>,
+    /// An icon name of the theme or a path.
+    pub icon: Option<String>,
+}
+
+/// Applied by starting the app in its own scope with `systemd-run` (Linux only).
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct ResourceLimits {
+    pub nice: Option<i32>,
+    /// From -1000 (never killed) to 1000 (killed first) when memory runs out.
+    pub oom_score_adjust: Option<i32>,
+    /// Like `4G`, the scope is killed above this.
+    pub memory_max: Option<String>,
+}
+
+impl ResourceLimits {
+    /// The `systemd-run` command that applies these, empty when nothing is set.
+    pub fn command(&self) -> Vec<String> {
+        let mut properties = Vec::new();
+        if let Some(oom_score_adjust) = self.oom_score_adjust {
+            properties.push(format!("OOMScoreAdjust={oom_score_adjust}"));
+        }
+        if let Some(memory_max) = &self.memory_max {
+            properties.push(format!("MemoryMax={memory_max}"));
+        }
+        if properties.is_empty() && self.nice.is_none() {
+            return Vec::new();
+        }
+
+        let mut command: Vec<String> = ["systemd-run", "--user", "--scope", "--quiet", "--collect"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        if let Some(nice) = self.nice {
+            command.push(format!("--nice={nice}"));
+        }
+        for property in properties {
+            command.push("-p".to_string());
+            command.push(property);
+        }
+        command
+    }
+}
+
+impl Settings {
+    /// Writes what changed since loading to `path`, the rest of the file stays as the user
+    /// wrote it, comments included.
+    pub fn save(&mut self, path: &Path) -> eyre::Result<()> {
+        let Some(loaded) = &self.loaded else {
+            bail!("The settings couldn't be loaded, saving would replace them with the defaults");
+        };
+        self.ignore.retain(|v| !v.trim().is_empty());
+        let current = toml::Table::try_from(&*self).wrap_err("Failed to serialize settings")?;
+
+        let mut document = match read_to_string(path) {
+            Ok(string) => string
+                .parse::<DocumentMut>()
+                .wrap_err("Failed to parse the settings")?,
+            Err(error) if error.kind() == ErrorKind::NotFound => DocumentMut::new(),
+            Err(error) => return Err(error).wrap_err("Failed to read the settings"),
+        };
+        apply_changes(document.as_table_mut(), loaded, &current);
+
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir).wrap_err("Failed to create config dir")?;
+        }
+        // The settings can hold things like the sync dir or password backends.
+        write_private(path, document.to_string()).wrap_err("Failed to write settings")?;
+        info!("Saved settings to {path:?}");
+        self.loaded = Some(current);
+        Ok(())
+    }
+
+    /// Moves `rename` into `overrides`, a name in an override wins.
+    fn migrate(&mut self) {
+        for (app, name) in std::mem::take(&mut self.rename) {
+            let changes = self.overrides.entry(app).or_default();
+            changes.name.get_or_insert(name);
+        }
+    }
+
+    pub fn load(path: &Path) -> Settings {
+        let string = match read_to_string(path) {
+            Ok(value) => value,
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                return Settings::default().remember_loaded();
+            }
+            Err(error) => {
+                error!("Could not read settings {path:?}: {error}");
+                return Settings::default();
+            }
+        };
+
+        match toml::from_str::<Settings>(&string) {
+            Ok(mut settings) => {
+                info!("Loaded settings from {path:?}");
+                settings.migrate();
+                settings.remember_loaded()
+            }
+            Err(error) => {
+                error!("Could not parse settings {path:?}: {error}");
+                Settings::default()
+            }
+        }
+    }
+
+    fn remember_loaded(mut self) -> Settings {
+        self.loaded = toml::Table::try_from(&self)
+            .inspect_err(|error| error!("Could not serialize settings: {error}"))
+            .ok();
+        self
+    }
+}
+
+/// Writes the keys that differ between `before` and `after` into `table`, and removes the
+/// ones that are gone. Untouched keys keep their formatting and comments.
+fn apply_changes(table: &mut dyn TableLike, before: &toml::Table, after: &toml::Table) {
+    for (key, value) in after {
+        let old = before.get(key);
+        if old == Some(value) {
+            continue;
+        }
+        if let (Some(toml::Value::Table(old)), toml::Value::Table(new)) = (old, value) {
+            if let Some(inner) = table.get_mut(key).and_then(Item::as_table_like_mut) {
+                apply_changes(inner, old, new);
+                continue;
+            }
+        }
+        match (table.get_mut(key), to_item(value)) {
+            // Assigning in place keeps the comments around the key and after the value.
+            (Some(Item::Value(existing)), Item::Value(mut new)) => {
+                *new.decor_mut() = existing.decor().clone();
+                *existing = new;
+            }
+            (_, item) => {
+                table.insert(key, item);
+            }
+        }
+    }
+    for key in before.keys().filter(|v| !after.contains_key(*v)) {
+        table.remove(key);
+    }
+}
+
+fn to_item(value: &toml::Value) -> Item {
+    match value {
+        toml::Value::Table(table) => {
+            let mut new = toml_edit::Table::new();
+            for (key, value) in table {
+                new.insert(key, to_item(value));
+            }
+            Item::Table(new)
+        }
+        toml::Value::Array(array) if !array.is_empty() && array.iter().all(|v| v.is_table()) => {
+            let mut new = toml_edit::ArrayOfTables::new();
+            for value in array {
+                if let Item::Table(table) = to_item(value) {
+                    new.push(table);
+                }
+            }
+            Item::ArrayOfTables(new)
+        }
+        value => Item::Value(to_value(value)),
+    }
+}
+
+fn to_value(value: &toml::Value) -> toml_edit::Value {
+    match value {
+        toml::Value::String(value) => value.into(),
+        toml::Value::Integer(value) => (*value).into(),
+        toml::Value::Float(value) => (*value).into(),
+        toml::Value::Boolean(value) => (*value).into(),
+        toml::Value::Datetime(value) => (*value).into(),
+        toml::Value::Array(array) => array
+            .iter()
+            .map(to_value)
+            .collect::<toml_edit::Array>()
+            .into(),
+        toml::Value::Table(table) => table
+            .iter()
+            .map(|(key, value)| (key, to_value(value)))
+            .collect::<toml_edit::InlineTable>()
+            .into(),
+    }
+}
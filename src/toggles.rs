@@ -0,0 +1,112 @@
+//! Quick settings like dark mode as entries, which show whether they are on and flip them.
+
+use crate::apps::AppId;
+use crate::i18n::tr;
+use crate::platform::{self, Toggle};
+use crate::search::{starts_words, EntryAction, SearchResultEntry, SearchScore};
+use crossbeam::channel::{bounded, Receiver};
+use std::collections::HashMap;
+use std::thread::spawn;
+use tracing::debug;
+
+/// The states of the quick settings, read on a background thread the first time a query
+/// finds one.
+#[derive(Default)]
+pub struct ToggleStates {
+    states: Option<HashMap<Toggle, bool>>,
+    pending: Option<Receiver<HashMap<Toggle, bool>>>,
+}
+
+impl ToggleStates {
+    /// Returns `true` once the states arrived.
+    pub fn tick(&mut self) -> bool {
+        let Some(pending) = &self.pending else {
+            return false;
+        };
+        let Ok(states) = pending.try_recv() else {
+            return false;
+        };
+        self.states = Some(states);
+        self.pending = None;
+        true
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// The quick settings found by `query`, settings the desktop doesn't have are left out.
+    pub fn search(&mut self, query: &str) -> Vec<SearchResultEntry> {
+        let found: Vec<Toggle> = Toggle::ALL
+            .into_iter()
+            .filter(|toggle| starts_words(&keywords(*toggle), query))
+            .collect();
+        if found.is_empty() {
+            return Vec::new();
+        }
+        let Some(states) = &self.states else {
+            self.load();
+            return Vec::new();
+        };
+
+        found
+            .into_iter()
+            .filter_map(|toggle| {
+                let on = *states.get(&toggle)?;
+                let state = if on {
+                    tr!("toggle-on")
+                } else {
+                    tr!("toggle-off")
+                };
+                Some(SearchResultEntry {
+                    id: AppId::from_properties(&["toggle", &format!("{toggle:?}")]),
+                    score: SearchScore::default(),
+                    detail: Some(state),
+                    broken: false,
+                    action: Some(EntryAction::Toggle(toggle, on)),
+                    confirm: false,
+                })
+            })
+            .collect()
+    }
+
+    fn load(&mut self) {
+        if self.pending.is_some() {
+            return;
+        }
+        let (sender, receiver) = bounded(1);
+        spawn(move || {
+            let mut states = HashMap::new();
+            for toggle in Toggle::ALL {
+                match platform::toggle_state(toggle) {
+                    Ok(on) => {
+                        states.insert(toggle, on);
+                    }
+                    Err(error) => debug!("Could not read {toggle:?}: {error:?}"),
+                }
+            }
+            let _ = sender.send(states);
+        });
+        self.pending = Some(receiver);
+    }
+}
+
+pub fn label(toggle: Toggle) -> String {
+    match toggle {
+        Toggle::NightLight => tr!("toggle-night-light"),
+        Toggle::DoNotDisturb => tr!("toggle-do-not-disturb"),
+        Toggle::DarkMode => tr!("toggle-dark-mode"),
+        Toggle::AirplaneMode => tr!("toggle-airplane-mode"),
+    }
+}
+
+/// What a quick setting is found by, its label and a few words in English.
+fn keywords(toggle: Toggle) -> String {
+    let keywords = match toggle {
+        Toggle::NightLight => "night light blue filter redshift",
+        Toggle::DoNotDisturb => "do not disturb dnd notifications",
+        Toggle::DarkMode => "dark mode theme light",
+        Toggle::AirplaneMode => "airplane flight mode wifi bluetooth radio",
+    };
+    format!("{} {keywords}", label(toggle))
+}
@@ -0,0 +1,166 @@
+//! The launcher in a terminal (`ignition --tui`), for SSH sessions and TTYs. It ranks,
+//! records and launches like the window, through [crate::core].
+
+use crate::apps::AppManager;
+use crate::core::{self, Core};
+use crate::i18n::tr;
+use crate::paths::Paths;
+use crate::search::{SearchEngine, SearchResult};
+use crate::settings::Settings;
+use eyre::Context;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::time::Instant;
+use tracing::warn;
+
+struct Tui<'a> {
+    apps: &'a AppManager,
+    search: &'a SearchEngine,
+    settings: &'a Settings,
+    query: String,
+    results: SearchResult,
+    list: ListState,
+}
+
+/// What was picked, with the query and its place in the results to learn from.
+struct Pick {
+    index: usize,
+    query: String,
+}
+
+pub fn run(paths: &Paths, settings: &Settings) -> eyre::Result<()> {
+    let Core { apps, mut search } = Core::load(paths, settings)?;
+    let opened_at = Instant::now();
+
+    let mut tui = Tui {
+        apps: &apps,
+        search: &search,
+        settings,
+        query: String::new(),
+        results: SearchResult::default(),
+        list: ListState::default(),
+    };
+    tui.search();
+    let mut terminal = ratatui::init();
+    let picked = tui.run(&mut terminal);
+    ratatui::restore();
+    let results = tui.results;
+
+    let Some(pick) = picked.wrap_err("Failed to run the terminal ui")? else {
+        return search.record_dismissal();
+    };
+    let Some(entry) = results.entries.get(pick.index) else {
+        return Ok(());
+    };
+    let Some(app) = apps.applications.get(&entry.id) else {
+        return Ok(());
+    };
+
+    let launch = core::launch_request(app, Vec::new(), settings);
+    let Pick { index, query } = pick;
+    if let Err(error) = search.record_choice(&query, &app.id, index) {
+        warn!("Could not remember the pick for {query:?}: {error:?}");
+    }
+    search
+        .record_use(app.id.clone(), Some(opened_at.elapsed()), &[])
+        .wrap_err("Failed to record the launch")?;
+    drop(search);
+    core::run_launch(paths, &launch)
+}
+
+impl Tui<'_> {
+    fn search(&mut self) {
+        let query = self.query.trim();
+        let mut results = SearchResult::default();
+        if query.is_empty() && self.settings.home.enabled {
+            results = self.search.home(self.apps, &self.settings.home);
+        }
+        if results.entries.is_empty() {
+            results = self.search.search(query.to_string(), self.apps);
+        }
+        self.results = results;
+        let first = (!self.results.entries.is_empty()).then_some(0);
+        self.list.select(first);
+    }
+
+    /// Shows the launcher until something is picked, `None` when it is closed.
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> eyre::Result<Option<Pick>> {
+        loop {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .wrap_err("Failed to draw")?;
+            let Event::Key(key) = event::read().wrap_err("Failed to read input")? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Char('c') if ctrl => return Ok(None),
+                KeyCode::Enter => {
+                    let Some(index) = self.list.selected() else {
+                        continue;
+                    };
+                    return Ok(Some(Pick {
+                        index,
+                        query: self.results.query.clone(),
+                    }));
+                }
+                KeyCode::Up => self.list.select_previous(),
+                KeyCode::Char('p') if ctrl => self.list.select_previous(),
+                KeyCode::Down => self.list.select_next(),
+                KeyCode::Char('n') if ctrl => self.list.select_next(),
+                KeyCode::Char('u') if ctrl => {
+                    self.query.clear();
+                    self.search();
+                }
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.search();
+                }
+                KeyCode::Char(c) if !ctrl => {
+                    self.query.push(c);
+                    self.search();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [bar, list] =
+            Layout::vertical([Constraint::Length(2), Constraint::Min(0)]).areas(frame.area());
+
+        let query = if self.query.is_empty() {
+            Span::raw(tr!("search-placeholder")).dim()
+        } else {
+            Span::raw(&self.query)
+        };
+        frame.render_widget(Paragraph::new(Line::from(vec!["> ".bold(), query])), bar);
+        let cursor = 2 + self.query.chars().count() as u16;
+        frame.set_cursor_position((bar.x + cursor.min(bar.width), bar.y));
+
+        let items = self.results.entries.iter().filter_map(|entry| {
+            let app = self.apps.applications.get(&entry.id)?;
+            let mut line = vec![Span::raw(&app.name)];
+            let detail = app.generic_name.as_ref().or(app.comment.as_ref());
+            if let Some(detail) = detail {
+                line.push(Span::raw(format!("  {detail}")).dim());
+            }
+            if self.search.is_new(&app.id) {
+                line.push(Span::raw(format!("  {}", tr!("app-new"))).yellow());
+            }
+            Some(ListItem::new(Line::from(line)))
+        });
+        let items = List::new(items)
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(items, list, &mut self.list);
+    }
+}
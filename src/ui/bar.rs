@@ -0,0 +1,189 @@
+use crate::apps::icons::AppIconManager;
+use crate::apps::running::RunningApps;
+use crate::apps::{AppId, AppManager};
+use crate::i18n::tr;
+use crate::search::SearchResult;
+use crate::ui::details::show_details;
+use crate::ui::framework::{draw_icon, glyph, Colors};
+use crate::ui::results::{action_label, ResultsEvent};
+use eframe::egui;
+use eframe::egui::{Align2, FontFamily, FontId, Rect, Rounding, Sense, Ui, Vec2};
+
+const BAR_CELL_SIZE: f32 = 56.0;
+const BAR_ICON_SIZE: f32 = 36.0;
+const BAR_LABEL_HEIGHT: f32 = 24.0;
+/// The height of the results in the bar layout, below the search bar.
+pub const BAR_HEIGHT: f32 = BAR_CELL_SIZE + BAR_LABEL_HEIGHT + 12.0;
+
+/// The compact layout, shows the results as a horizontal row of icons
+/// with the name of the selected one underneath.
+pub struct BarWidget<'a> {
+    pub apps: &'a AppManager,
+    pub app_icons: &'a AppIconManager,
+    pub running: &'a RunningApps,
+    pub results: &'a SearchResult,
+    pub selected: Option<usize>,
+    /// Show the details of the selected entry, instead of only on hover.
+    pub show_details: bool,
+    /// The entry waiting for a second press.
+    pub confirming: Option<&'a AppId>,
+}
+
+impl BarWidget<'_> {
+    pub fn ui(&self, ui: &mut Ui) -> Vec<ResultsEvent> {
+        let mut events = Vec::new();
+        let rect = ui.available_rect_before_wrap().shrink2(Vec2::new(8.0, 6.0));
+
+        let columns = ((rect.width() / BAR_CELL_SIZE).floor() as usize).max(1);
+        // Scroll so the selected entry is always in view.
+        let first = self
+            .selected
+            .map(|selected| (selected + 1).saturating_sub(columns))
+            .unwrap_or(0);
+        let last = (first + columns).min(self.results.entries.len());
+
+        let selected_t = ui.ctx().animate_value_with_time(
+            ui.id().with("animated-bar-selected"),
+            self.selected
+                .map(|v| v as f32 - first as f32)
+                .unwrap_or(-1.0),
+            0.15,
+        );
+
+        if selected_t >= 0.0 {
+            let highlight_rect = Rect::from_min_size(
+                rect.min + Vec2::new(selected_t * BAR_CELL_SIZE, 0.0),
+                Vec2::splat(BAR_CELL_SIZE),
+            );
+            ui.painter()
+                .rect_filled(highlight_rect.shrink(2.0), Rounding::same(8.0), Colors::BG);
+        }
+
+        let mut hit_boxes = Vec::new();
+        for i in first..last {
+            let entry = &self.results.entries[i];
+            let cell_rect = Rect::from_min_size(
+                rect.min + Vec2::new((i - first) as f32 * BAR_CELL_SIZE, 0.0),
+                Vec2::splat(BAR_CELL_SIZE),
+            );
+            let icon_rect = Rect::from_center_size(cell_rect.center(), Vec2::splat(BAR_ICON_SIZE));
+
+            if let Some(action) = &entry.action {
+                ui.painter()
+                    .rect_filled(icon_rect, Rounding::same(6.0), Colors::surface0());
+                draw_icon(
+                    ui.painter(),
+                    action_label(action).0,
+                    icon_rect.center(),
+                    24.0,
+                    Colors::text(),
+                );
+            } else if let Some(icon) = self.app_icons.read_icon(&entry.id) {
+                let string = format!("file://{}", icon.to_str().unwrap());
+                egui::Image::from_uri(string)
+                    .rounding(Rounding::same(6.0))
+                    .paint_at(ui, icon_rect);
+            } else if let Some(app) = self.apps.applications.get(&entry.id) {
+                // Apps without an icon get their first letter.
+                let letter = app.name.chars().next().unwrap_or('?').to_uppercase();
+                ui.painter()
+                    .rect_filled(icon_rect, Rounding::same(6.0), Colors::surface0());
+                ui.painter().text(
+                    icon_rect.center(),
+                    Align2::CENTER_CENTER,
+                    letter,
+                    FontId::new(20.0, FontFamily::Proportional),
+                    Colors::text(),
+                );
+            }
+            if let Some(app) = self.apps.applications.get(&entry.id) {
+                if self.running.is_running(app) {
+                    ui.painter().circle_filled(
+                        icon_rect.center_bottom() + Vec2::new(0.0, 5.0),
+                        2.0,
+                        Colors::text(),
+                    );
+                }
+            }
+
+            if entry.broken {
+                draw_icon(
+                    ui.painter(),
+                    glyph!("warning"),
+                    icon_rect.right_top(),
+                    16.0,
+                    Colors::yellow(),
+                );
+            }
+
+            hit_boxes.push((cell_rect, entry.id.clone()));
+        }
+
+        let selected_entry = self
+            .selected
+            .and_then(|selected| self.results.entries.get(selected));
+        let selected_app = selected_entry.and_then(|entry| self.apps.applications.get(&entry.id));
+        let mut label = match selected_entry.and_then(|entry| entry.action.as_ref()) {
+            Some(action) => Some(action_label(action).1),
+            None => selected_app.map(|app| app.name.clone()),
+        };
+        let mut label_color = Colors::text();
+        if selected_entry.is_some_and(|entry| self.confirming == Some(&entry.id)) {
+            label = Some(tr!("confirm-entry"));
+            label_color = Colors::red();
+        }
+        if let Some(label) = label {
+            let label_rect = Rect::from_min_size(
+                rect.min + Vec2::new(0.0, BAR_CELL_SIZE),
+                Vec2::new(rect.width(), BAR_LABEL_HEIGHT),
+            );
+            ui.painter().text(
+                label_rect.left_center(),
+                Align2::LEFT_CENTER,
+                label,
+                FontId::new(16.0, FontFamily::Proportional),
+                label_color,
+            );
+        }
+
+        let mut hovered = None;
+        let mut tooltip_in = None;
+        ui.input(|input| {
+            let Some(pos) = input.pointer.hover_pos() else {
+                return;
+            };
+            for (rect, id) in &hit_boxes {
+                if !rect.contains(pos) {
+                    continue;
+                }
+                if input.pointer.is_moving() {
+                    events.push(ResultsEvent::Hovered(id.clone()));
+                }
+                let still = input.pointer.time_since_last_movement();
+                let delay = ui.style().interaction.tooltip_delay;
+                if still > delay {
+                    hovered = Some(id.clone());
+                } else {
+                    tooltip_in = Some(delay - still);
+                }
+                if input.pointer.primary_pressed() {
+                    events.push(ResultsEvent::Pressed(id.clone()));
+                }
+            }
+        });
+
+        // Nothing else repaints while the pointer rests, so wake up when the tooltip is due.
+        if let Some(seconds) = tooltip_in {
+            ui.ctx().request_repaint_after_secs(seconds);
+        }
+
+        if let Some(app) = hovered.and_then(|id| self.apps.applications.get(&id)) {
+            show_details(ui, app, None);
+        } else if let Some(app) = selected_app.filter(|_| self.show_details) {
+            show_details(ui, app, Some(rect.left_bottom()));
+        }
+
+        ui.allocate_rect(rect, Sense::click());
+        events
+    }
+}
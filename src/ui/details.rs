@@ -0,0 +1,59 @@
+use crate::apps::{App, AppSource};
+use crate::i18n::tr;
+use crate::ui::framework::Colors;
+use eframe::egui;
+use eframe::egui::{Grid, Id, Pos2, RichText, Ui};
+
+/// Shows the metadata of `app` in a tooltip, at `position` or else at the pointer.
+///
+/// Mostly useful for figuring out which desktop entry a result comes from.
+pub fn show_details(ui: &Ui, app: &App, position: Option<Pos2>) {
+    let id = Id::new("app-details").with(&app.id);
+    let add_contents = |ui: &mut Ui| details_ui(ui, app);
+    match position {
+        Some(position) => {
+            egui::show_tooltip_at(ui.ctx(), ui.layer_id(), id, position, add_contents);
+        }
+        None => {
+            egui::show_tooltip_at_pointer(ui.ctx(), ui.layer_id(), id, add_contents);
+        }
+    }
+}
+
+fn details_ui(ui: &mut Ui, app: &App) {
+    ui.label(RichText::new(&app.name).color(Colors::text()).strong());
+    Grid::new("app-details-grid")
+        .num_columns(2)
+        .spacing([12.0, 4.0])
+        .show(ui, |ui| {
+            let row = |ui: &mut Ui, key: String, value: &str| {
+                ui.label(RichText::new(key).color(Colors::subtext0()));
+                ui.label(RichText::new(value).color(Colors::text()).monospace());
+                ui.end_row();
+            };
+
+            row(ui, tr!("details-path"), &app.path.to_string_lossy());
+            row(ui, tr!("details-exec"), &app.exec);
+            if let Some(categories) = &app.categories {
+                let categories: Vec<&str> = categories
+                    .iter()
+                    .map(|v| v.as_str())
+                    .filter(|v| !v.is_empty())
+                    .collect();
+                row(ui, tr!("details-categories"), &categories.join(", "));
+            }
+            row(ui, tr!("details-source"), &source_name(app.source));
+        });
+}
+
+fn source_name(source: AppSource) -> String {
+    match source {
+        AppSource::DesktopEntry => tr!("source-desktop-entry"),
+        AppSource::Link => tr!("source-link"),
+        AppSource::StartMenu => tr!("source-start-menu"),
+        AppSource::StartApps => tr!("source-start-apps"),
+        AppSource::AppBundle => tr!("source-app-bundle"),
+        AppSource::AppImage => tr!("source-appimage"),
+        AppSource::Settings => tr!("source-settings"),
+    }
+}
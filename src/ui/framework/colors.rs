@@ -1,36 +1,98 @@
+use crate::color;
 use eframe::egui::Color32;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use tracing::warn;
 
-pub struct Colors {
-    
+/// Colors of the theme replacing the bundled ones, by name like `text` or `blue`.
+static THEME: RwLock<Option<HashMap<String, Color32>>> = RwLock::new(None);
+
+/// Takes the colors of the `[theme]` settings. Called again when the settings view changes
+/// them, so they show right away.
+pub fn set_theme(theme: &HashMap<String, String>) {
+    let colors = theme
+        .iter()
+        .filter_map(|(name, value)| {
+            if Colors::by_name(name).is_none() {
+                warn!("Unknown theme color {name:?}");
+                return None;
+            }
+            let Some([r, g, b]) = color::parse(value) else {
+                warn!("Invalid theme color {name} = {value:?}");
+                return None;
+            };
+            Some((name.clone(), Color32::from_rgb(r, g, b)))
+        })
+        .collect();
+    *THEME.write().unwrap() = Some(colors);
+}
+
+fn themed(name: &str, default: Color32) -> Color32 {
+    THEME
+        .read()
+        .unwrap()
+        .as_ref()
+        .and_then(|theme| theme.get(name))
+        .copied()
+        .unwrap_or(default)
 }
 
-#[allow(dead_code)]
+/// Defines a function for each color of the palette, which the theme can replace by its name.
+macro_rules! palette {
+    ($($name:ident = $r:literal, $g:literal, $b:literal;)*) => {
+        #[allow(dead_code)]
+        impl Colors {
+            /// The names of the colors a theme can replace.
+            pub const NAMES: &[&str] = &[$(stringify!($name)),*];
+
+            $(
+                pub fn $name() -> Color32 {
+                    themed(stringify!($name), Color32::from_rgb($r, $g, $b))
+                }
+            )*
+
+            pub fn by_name(name: &str) -> Option<Color32> {
+                match name {
+                    $(stringify!($name) => Some(Colors::$name()),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+pub struct Colors {}
+
 impl Colors {
-    pub const BG: Color32 =  Color32::from_rgba_premultiplied(0x18, 0x18, 0x25, 10);
-    pub const ROSEWATER: Color32 = Color32::from_rgb(0xf5, 0xe0, 0xdc);
-    pub const FLAMINGO: Color32 = Color32::from_rgb(0xf2, 0xcd, 0xcd);
-    pub const PINK: Color32 = Color32::from_rgb(0xf5, 0xc2, 0xe7);
-    pub const MAUVE: Color32 = Color32::from_rgb(0xcb, 0xa6, 0xf7);
-    pub const RED: Color32 = Color32::from_rgb(0xf3, 0x8b, 0xa8);
-    pub const MAROON: Color32 = Color32::from_rgb(0xeb, 0xa0, 0xac);
-    pub const PEACH: Color32 = Color32::from_rgb(0xfa, 0xb3, 0x87);
-    pub const YELLOW: Color32 = Color32::from_rgb(0xf9, 0xe2, 0xaf);
-    pub const GREEN: Color32 = Color32::from_rgb(0xa6, 0xe3, 0xa1);
-    pub const TEAL: Color32 = Color32::from_rgb(0x94, 0xe2, 0xd5);
-    pub const SKY: Color32 = Color32::from_rgb(0x89, 0xdc, 0xeb);
-    pub const SAPPHIRE: Color32 = Color32::from_rgb(0x74, 0xc7, 0xec);
-    pub const BLUE: Color32 = Color32::from_rgb(0x89, 0xb4, 0xfa);
-    pub const LAVENDER: Color32 = Color32::from_rgb(0xb4, 0xbe, 0xfe);
-    pub const TEXT: Color32 = Color32::from_rgb(0xcd, 0xd6, 0xf4);
-    pub const SUBTEXT1: Color32 = Color32::from_rgb(0xba, 0xc2, 0xde);
-    pub const SUBTEXT0: Color32 = Color32::from_rgb(0xa6, 0xad, 0xc8);
-    pub const OVERLAY2: Color32 = Color32::from_rgb(0x93, 0x99, 0xb2);
-    pub const OVERLAY1: Color32 = Color32::from_rgb(0x7f, 0x84, 0x9c);
-    pub const OVERLAY0: Color32 = Color32::from_rgb(0x6c, 0x70, 0x86);
-    pub const SURFACE2: Color32 = Color32::from_rgb(0x58, 0x5b, 0x70);
-    pub const SURFACE1: Color32 = Color32::from_rgb(0x45, 0x47, 0x5a);
-    pub const SURFACE0: Color32 = Color32::from_rgb(0x31, 0x32, 0x44);
-    pub const BASE: Color32 = Color32::from_rgb(0x1e, 0x1e, 0x2e);
-    pub const MANTLE: Color32 = Color32::from_rgb(0x18, 0x18, 0x25);
-    pub const CRUST: Color32 = Color32::from_rgb(0x11, 0x11, 0x1b);
-}
\ No newline at end of file
+    /// Barely tints what is below the window, so it isn't part of the theme.
+    pub const BG: Color32 = Color32::from_rgba_premultiplied(0x18, 0x18, 0x25, 10);
+}
+
+palette! {
+    rosewater = 0xf5, 0xe0, 0xdc;
+    flamingo = 0xf2, 0xcd, 0xcd;
+    pink = 0xf5, 0xc2, 0xe7;
+    mauve = 0xcb, 0xa6, 0xf7;
+    red = 0xf3, 0x8b, 0xa8;
+    maroon = 0xeb, 0xa0, 0xac;
+    peach = 0xfa, 0xb3, 0x87;
+    yellow = 0xf9, 0xe2, 0xaf;
+    green = 0xa6, 0xe3, 0xa1;
+    teal = 0x94, 0xe2, 0xd5;
+    sky = 0x89, 0xdc, 0xeb;
+    sapphire = 0x74, 0xc7, 0xec;
+    blue = 0x89, 0xb4, 0xfa;
+    lavender = 0xb4, 0xbe, 0xfe;
+    text = 0xcd, 0xd6, 0xf4;
+    subtext1 = 0xba, 0xc2, 0xde;
+    subtext0 = 0xa6, 0xad, 0xc8;
+    overlay2 = 0x93, 0x99, 0xb2;
+    overlay1 = 0x7f, 0x84, 0x9c;
+    overlay0 = 0x6c, 0x70, 0x86;
+    surface2 = 0x58, 0x5b, 0x70;
+    surface1 = 0x45, 0x47, 0x5a;
+    surface0 = 0x31, 0x32, 0x44;
+    base = 0x1e, 0x1e, 0x2e;
+    mantle = 0x18, 0x18, 0x25;
+    crust = 0x11, 0x11, 0x1b;
+}
@@ -2,24 +2,38 @@ use std::fs::read;
 use std::path::Path;
 use std::sync::Arc;
 use eframe::egui::{FontData, FontDefinitions, FontFamily, FontTweak};
+use tracing::warn;
+use crate::platform;
 
 macro_rules! load_font {
-    ($PATH:literal) => {
+    ($PATH:literal) => {{
         #[allow(clippy::diverging_sub_expression)]
-        'load: {
+        let font = 'load: {
             #[cfg(debug_assertions)]
             break 'load load_font($PATH.replace("../", ""));
             #[cfg(not(debug_assertions))]
             break 'load load_font_static(include_bytes!($PATH));
-        }
-    };
+        };
+        font
+    }};
 }
-pub fn load_fonts() -> FontDefinitions {
+/// Loads the bundled fonts, with `icon_font` replacing the bundled icons if it can be read.
+/// The `system_families` found on the system come before the bundled text font, in order.
+pub fn load_fonts(icon_font: Option<&Path>, system_families: &[String]) -> FontDefinitions {
     let mut fonts = FontDefinitions::empty();
 
+    let icons = icon_font.and_then(|path| {
+        read(path)
+            .inspect_err(|error| warn!("Could not read the icon font {path:?}: {error}"))
+            .ok()
+    });
+    let icons = match icons {
+        Some(font) => FontData::from_owned(font),
+        None => load_font!("../../../assets/Icons.ttf"),
+    };
     add_font(
         &mut fonts,
-        load_font!("../../../assets/Icons.ttf").tweak(FontTweak {
+        icons.tweak(FontTweak {
             scale: 1.0,
             y_offset_factor: 0.0,
             y_offset: 0.0,
@@ -57,9 +71,49 @@ pub fn load_fonts() -> FontDefinitions {
         .families
         .insert(FontFamily::Monospace, vec!["Roboto-Regular".to_string()]);
 
+    add_system_fonts(&mut fonts, system_families);
     fonts
 }
 
+/// Puts the system fonts in front of the bundled ones, which still cover what they lack.
+fn add_system_fonts(fonts: &mut FontDefinitions, families: &[String]) {
+    let mut regular = Vec::new();
+    let mut bold = Vec::new();
+    for family in families {
+        for (is_bold, names) in [(false, &mut regular), (true, &mut bold)] {
+            let Some((path, index)) = platform::find_font(family, is_bold) else {
+                warn!("Could not find the font {family:?}");
+                continue;
+            };
+            let font = match read(&path) {
+                Ok(font) => font,
+                Err(error) => {
+                    warn!("Could not read the font {path:?}: {error}");
+                    continue;
+                }
+            };
+            let name = format!("{family}-{}", if is_bold { "Bold" } else { "Regular" });
+            let font = FontData {
+                index,
+                ..FontData::from_owned(font)
+            };
+            fonts.font_data.insert(name.clone(), font);
+            names.push(name);
+        }
+    }
+
+    let families = [
+        (FontFamily::Proportional, &regular),
+        (FontFamily::Monospace, &regular),
+        (FontFamily::Name(Arc::from("Roboto-Regular")), &regular),
+        (FontFamily::Name(Arc::from("Roboto-Bold")), &bold),
+    ];
+    for (family, names) in families {
+        let list = fonts.families.entry(family).or_default();
+        list.splice(0..0, names.iter().cloned());
+    }
+}
+
 #[allow(unused)]
 fn load_font_static(font: &'static [u8]) -> FontData {
     FontData::from_static(font)
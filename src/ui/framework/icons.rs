@@ -1,6 +1,32 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
 use eframe::egui::{Align2, Color32, FontFamily, FontId, Painter, Pos2, Rect};
 use eframe::egui::text::LayoutJob;
 
+/// Glyphs of a custom icon font replacing the bundled ones, by icon name.
+static GLYPHS: OnceLock<HashMap<String, char>> = OnceLock::new();
+
+pub fn set_glyphs(glyphs: HashMap<String, char>) {
+    let _ = GLYPHS.set(glyphs);
+}
+
+/// The codepoint of the icon `name`, `default` unless the settings remap it.
+pub fn glyph_of(name: &str, default: u32) -> u32 {
+    GLYPHS
+        .get()
+        .and_then(|glyphs| glyphs.get(name))
+        .map(|v| *v as u32)
+        .unwrap_or(default)
+}
+
+/// Like `icon!`, but remappable in the settings by the icon's name.
+macro_rules! glyph {
+    ($name:tt) => {
+        $crate::ui::framework::glyph_of($name, splinter_icon::icon!($name))
+    };
+}
+pub(crate) use glyph;
+
 pub fn draw_icon(painter: &Painter, icon: u32, pos: Pos2, size: f32, color: Color32) {
     let icon = char::from_u32(icon).expect("Could not parse icon char");
     let text = icon.to_string();
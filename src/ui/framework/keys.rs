@@ -0,0 +1,44 @@
+use eframe::egui::{Event, Key, KeyboardShortcut, Modifiers};
+
+/// Reads shortcuts like `Ctrl+P`, `Ctrl+Shift+H` or `F5`. `Cmd` is Ctrl on Linux and Windows
+/// and ⌘ on macOS.
+pub fn parse_shortcut(text: &str) -> Option<KeyboardShortcut> {
+    let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+    // `Ctrl++` splits into an empty part before the plus key.
+    let key = match parts.pop()? {
+        "" if parts.last() == Some(&"") => {
+            parts.pop();
+            "+"
+        }
+        key => key,
+    };
+    let mut modifiers = Modifiers::NONE;
+    for part in parts {
+        modifiers = modifiers
+            | match part.to_lowercase().as_str() {
+                "ctrl" | "control" => Modifiers::CTRL,
+                "alt" | "option" => Modifiers::ALT,
+                "shift" => Modifiers::SHIFT,
+                "cmd" | "command" | "super" => Modifiers::COMMAND,
+                _ => return None,
+            };
+    }
+    let key = Key::from_name(key).or_else(|| Key::from_name(&key.to_uppercase()))?;
+    Some(KeyboardShortcut::new(modifiers, key))
+}
+
+/// Whether `event` presses `shortcut`, see [parse_shortcut]. Never for invalid ones.
+pub fn shortcut_pressed(event: &Event, shortcut: &str) -> bool {
+    let Event::Key {
+        key,
+        pressed: true,
+        modifiers,
+        ..
+    } = event
+    else {
+        return false;
+    };
+    parse_shortcut(shortcut).is_some_and(|shortcut| {
+        shortcut.logical_key == *key && modifiers.matches_logically(shortcut.modifiers)
+    })
+}
@@ -1,7 +1,9 @@
 mod colors;
 mod fonts;
 mod icons;
+mod keys;
 
 pub use colors::*;
 pub use fonts::*;
-pub use icons::*;
\ No newline at end of file
+pub use icons::*;
+pub use keys::*;
\ No newline at end of file
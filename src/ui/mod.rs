@@ -1,3 +1,8 @@
+pub mod bar;
+pub mod details;
 pub mod framework;
 pub mod search_bar;
+pub mod settings;
+pub mod stats;
+pub mod template;
 pub mod results;
@@ -1,24 +1,171 @@
 use eframe::egui;
-use eframe::egui::{Align2, Color32, FontFamily, FontId, NumExt, Pos2, Rect, Rounding, ScrollArea, Sense, Stroke, TextFormat, Ui, Vec2};
+use eframe::egui::{Align, Align2, Checkbox, Color32, FontFamily, FontId, NumExt, Pos2, Rect, RichText, Rounding, ScrollArea, Sense, Stroke, TextFormat, Ui, Vec2};
+use std::collections::BTreeSet;
 use eframe::egui::scroll_area::ScrollBarVisibility;
 use eframe::egui::text::{LayoutJob, TextWrapping};
 use eframe::emath::easing;
-use splinter_icon::icon;
 use crate::{ENTRY_HEIGHT, ENTRY_SPACING, IMAGE_SIZE};
-use crate::apps::{AppId, AppManager};
+use crate::apps::{App, AppId, AppManager};
+use crate::bookmarks::DirTarget;
 use crate::apps::icons::AppIconManager;
-use crate::search::{SearchResult, SearchResultEntry};
-use crate::ui::framework::{draw_icon, Colors};
+use crate::apps::running::RunningApps;
+use crate::i18n::tr;
+use crate::media::{self, MediaCommand};
+use crate::platform::Capture;
+use crate::search::{EntryAction, SearchEngine, SearchResult, SearchResultEntry, SectionKind};
+use crate::toggles;
+use crate::ui::details::show_details;
+use crate::ui::template::{Field, RowTemplate};
+use crate::ui::framework::{draw_icon, glyph, Colors};
+
+const SECTION_HEADER_HEIGHT: f32 = 24.0;
+
+fn section_title(kind: SectionKind) -> String {
+    match kind {
+        SectionKind::Pinned => tr!("section-pinned"),
+        SectionKind::Recent => tr!("section-recent"),
+        SectionKind::MostUsed => tr!("section-most-used"),
+        SectionKind::Category(category) => match category {
+            Some("AudioVideo") => tr!("category-audio-video"),
+            Some("Development") => tr!("category-development"),
+            Some("Education") => tr!("category-education"),
+            Some("Game") => tr!("category-game"),
+            Some("Graphics") => tr!("category-graphics"),
+            Some("Network") => tr!("category-network"),
+            Some("Office") => tr!("category-office"),
+            Some("Science") => tr!("category-science"),
+            Some("Settings") => tr!("category-settings"),
+            Some("System") => tr!("category-system"),
+            Some("Utility") => tr!("category-utility"),
+            _ => tr!("category-other"),
+        },
+    }
+}
+
+/// The icon and text shown for entries that aren't apps.
+pub fn action_label(action: &EntryAction) -> (u32, String) {
+    match action {
+        EntryAction::OpenTerminal(dir) => (
+            glyph!("terminal"),
+            tr!("open-terminal", dir = dir.display().to_string()),
+        ),
+        EntryAction::CopySecret(entry) => {
+            (glyph!("key"), tr!("copy-secret", name = entry.name.clone()))
+        }
+        EntryAction::Capture(capture) => match capture {
+            Capture::Region => (glyph!("screenshot_region"), tr!("capture-region")),
+            Capture::Screen => (glyph!("screenshot_monitor"), tr!("capture-screen")),
+            Capture::Color => (glyph!("colorize"), tr!("pick-color")),
+        },
+        EntryAction::Toggle(toggle, on) => {
+            let icon = if *on {
+                glyph!("toggle_on")
+            } else {
+                glyph!("toggle_off")
+            };
+            (icon, toggles::label(*toggle))
+        }
+        EntryAction::Autostart(entry) => {
+            let icon = if entry.enabled {
+                glyph!("toggle_on")
+            } else {
+                glyph!("toggle_off")
+            };
+            (icon, entry.name.clone())
+        }
+        EntryAction::RunCommand { line, terminal } => {
+            let command = line.clone();
+            if *terminal {
+                (
+                    glyph!("terminal"),
+                    tr!("run-command-terminal", command = command),
+                )
+            } else {
+                (glyph!("play_arrow"), tr!("run-command", command = command))
+            }
+        }
+        EntryAction::OpenProject { project, editor } => {
+            let label = tr!(
+                "open-project",
+                name = project.name.as_str(),
+                editor = editor.as_str()
+            );
+            (glyph!("code"), label)
+        }
+        EntryAction::ProviderResult(result) => (glyph!("manage_search"), result.name.clone()),
+        EntryAction::Media(player, command) => {
+            let icon = match command {
+                MediaCommand::PlayPause if player.playing => glyph!("pause"),
+                MediaCommand::PlayPause => glyph!("play_arrow"),
+                MediaCommand::Next => glyph!("skip_next"),
+                MediaCommand::Previous => glyph!("skip_previous"),
+                MediaCommand::Raise => glyph!("music_note"),
+            };
+            (icon, media::label(player, *command))
+        }
+        EntryAction::Answer(text) => (glyph!("calculate"), text.clone()),
+        EntryAction::Color { text, .. } => {
+            (glyph!("palette"), tr!("copy-color", color = text.as_str()))
+        }
+        EntryAction::WebSearch(search) => {
+            let query = search.query.clone();
+            let label = match &search.bang {
+                Some(_) => tr!("web-search-site", site = search.site(), query = query),
+                None => tr!("web-search", query = query),
+            };
+            (glyph!("travel_explore"), label)
+        }
+        EntryAction::OpenDir(bookmark, target) => {
+            let name = bookmark.name.clone();
+            match target {
+                DirTarget::FileManager => (glyph!("folder_open"), tr!("open-dir", dir = name)),
+                DirTarget::Terminal => (glyph!("terminal"), tr!("open-dir-terminal", dir = name)),
+                DirTarget::Editor => (glyph!("edit"), tr!("open-dir-editor", dir = name)),
+            }
+        }
+    }
+}
 
 pub enum ResultsEvent {
     Hovered(AppId),
     Pressed(AppId),
+    /// Picked a tag in the context menu of an app.
+    ToggleTag(AppId, String),
+    /// Focus the window of an app that is still running from its last launch.
+    SwitchTo(AppId),
+    /// Open the log with the output of launched apps.
+    ShowLaunchLog,
+    /// Open the desktop entry of an app in an editor, `copy` it to the user's entries first.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    EditEntry {
+        app: AppId,
+        copy: bool,
+    },
+    /// Start an app at login, by copying its desktop entry to the autostart dir.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    AddToAutostart(AppId),
+    /// Uninstall the package of an app, or show its info, in a terminal.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    ManagePackage {
+        app: AppId,
+        uninstall: bool,
+    },
 }
 pub struct ResultsWidget<'a> {
     pub apps: &'a AppManager,
+    pub search: &'a SearchEngine,
     pub app_icons: &'a AppIconManager,
+    pub running: &'a RunningApps,
     pub results: &'a SearchResult,
     pub selected: Option<usize>,
+    /// Show the details of the selected entry, instead of only on hover.
+    pub show_details: bool,
+    /// Show how the score of each entry came together.
+    pub explain: bool,
+    /// The entry waiting for a second press.
+    pub confirming: Option<&'a AppId>,
+    /// What the rows show instead of the name and the comment of the selected entry.
+    pub template: Option<&'a RowTemplate>,
 }
 
 impl ResultsWidget<'_> {
@@ -26,16 +173,26 @@ impl ResultsWidget<'_> {
         let mut events = Vec::new();
         let row_height = ENTRY_HEIGHT + ENTRY_SPACING;
         let num_rows = self.results.entries.len();
+        let sections = &self.results.sections;
+        let headers_height = sections.len() as f32 * SECTION_HEADER_HEIGHT;
+
+        // The offset of a row, which is pushed down by the section headers above it.
+        let row_offset = |i: usize| {
+            let headers = sections.iter().filter(|section| section.start <= i).count();
+            i as f32 * row_height + headers as f32 * SECTION_HEADER_HEIGHT
+        };
 
         let rect = ui.max_rect();
         ScrollArea::vertical()
-            .max_height(row_height * num_rows as f32)
+            .max_height(row_height * num_rows as f32 + headers_height)
             .enable_scrolling(false)
             .scroll_bar_visibility(ScrollBarVisibility::AlwaysHidden)
             .show_viewport(ui, |ui, viewport| {
                 ui.set_height(row_height);
 
-                let first_item = (viewport.min.y / row_height).floor().at_least(0.0f32) as usize;
+                let first_item = ((viewport.min.y - headers_height) / row_height)
+                    .floor()
+                    .at_least(0.0f32) as usize;
                 let last_item = (viewport.max.y / row_height).ceil() as usize + 1;
                 let last_item = last_item.at_most(num_rows);
 
@@ -71,7 +228,11 @@ impl ResultsWidget<'_> {
                 } else {
                     easing::cubic_in(current_selected.fract())
                 };
-                current_selected = min_rect.top() + current_selected * row_height + 9.0;
+                current_selected = {
+                    let from = row_offset(current_selected.floor() as usize);
+                    let to = row_offset(current_selected.ceil() as usize);
+                    min_rect.top() + from + (to - from) * current_selected.fract() + 9.0
+                };
                 let highlight_rect = {
                     let mut rect = ui.clip_rect().shrink(8.0);
                     rect.min.y = current_selected - 4.0;
@@ -87,20 +248,21 @@ impl ResultsWidget<'_> {
                         highlight_rect,
                         Rounding::same(6.0),
                         Colors::BG.gamma_multiply(selected_opacity),
-                        Stroke::new(0.0, Colors::SURFACE0),
+                        Stroke::new(0.0, Colors::surface0()),
                     );
                     draw_icon(
                         p,
-                        icon!("play_arrow"),
+                        glyph!("play_arrow"),
                         highlight_rect.right_center() - Vec2::new(20.0, 0.0),
                         24.0,
-                        Colors::SUBTEXT0.gamma_multiply(selected_opacity),
+                        Colors::subtext0().gamma_multiply(selected_opacity),
                     );
                 }
                 let top_score = self
                     .results
                     .entries
-                    .first()
+                    .iter()
+                    .find(|v| v.action.is_none())
                     .map(|v| v.score.score)
                     .unwrap_or(1.0);
                 //let mut selected_rect = None;
@@ -109,7 +271,17 @@ impl ResultsWidget<'_> {
                 for i in first_item..last_item {
                     let entry = &self.results.entries[i];
                     let x = min_rect.left();
-                    let y = min_rect.top() + i as f32 * row_height + 9.0;
+                    let y = min_rect.top() + row_offset(i) + 9.0;
+
+                    if let Some(section) = sections.iter().find(|section| section.start == i) {
+                        ui.painter().text(
+                            Pos2::new(x + 12.0, y - SECTION_HEADER_HEIGHT / 2.0),
+                            Align2::LEFT_CENTER,
+                            section_title(section.kind),
+                            FontId::new(14.0, FontFamily::Proportional),
+                            Colors::overlay1(),
+                        );
+                    }
 
                     let rect = Rect::from_min_size(
                         Pos2::new(x, y),
@@ -124,7 +296,7 @@ impl ResultsWidget<'_> {
 
                     let mut opacity =
                         0.4 + (entry.score.score.max(0.001) / top_score.max(0.001)) * 0.6;
-                    if self.results.query.is_empty() {
+                    if self.results.query.is_empty() || entry.action.is_some() {
                         opacity = 1.0;
                     }
 
@@ -150,6 +322,9 @@ impl ResultsWidget<'_> {
                     used_rect = used_rect.union(rect);
                 }
 
+                let mut hovered = None;
+                let mut tooltip_in = None;
+                let mut under_pointer = None;
                 ui.input(|input| {
                     if let Some(pos) = input.pointer.hover_pos() {
                         //if self.mouse_lock_from.elapsed() > Duration::from_millis(300) {
@@ -166,11 +341,19 @@ impl ResultsWidget<'_> {
                                         continue;
                                     };
 
+                                    under_pointer = Some(id.clone());
                                     if input.pointer.is_moving() {
                                         events.push(ResultsEvent::Hovered(id.clone()));
                                     }
+                                    let still = input.pointer.time_since_last_movement();
+                                    let delay = ui.style().interaction.tooltip_delay;
+                                    if still > delay {
+                                        hovered = Some(id.clone());
+                                    } else {
+                                        tooltip_in = Some(delay - still);
+                                    }
 
-                                    if input.pointer.primary_down() {
+                                    if input.pointer.primary_pressed() {
                                         events.push(ResultsEvent::Pressed(id.clone()));
                                     }
                                 }
@@ -179,12 +362,145 @@ impl ResultsWidget<'_> {
                     }
                 });
 
-                ui.allocate_rect(used_rect, Sense::click());
+                // Nothing else repaints while the pointer rests, so wake up when the tooltip is due.
+                if let Some(seconds) = tooltip_in {
+                    ui.ctx().request_repaint_after_secs(seconds);
+                }
+
+                if let Some(app) = hovered.and_then(|id| self.apps.applications.get(&id)) {
+                    show_details(ui, app, None);
+                } else if self.show_details {
+                    let selected = self.selected.and_then(|v| self.results.entries.get(v));
+                    let row =
+                        selected.and_then(|entry| hit_boxes.iter().find(|(_, id)| id == &entry.id));
+                    if let Some((rect, id)) = row {
+                        if let Some(app) = self.apps.applications.get(id) {
+                            show_details(ui, app, Some(rect.left_bottom()));
+                        }
+                    }
+                }
+
+                let response = ui.allocate_rect(used_rect, Sense::click());
+                let menu_id = ui.id().with("tags-menu-app");
+                if response.secondary_clicked() {
+                    ui.data_mut(|data| data.insert_temp(menu_id, under_pointer));
+                }
+                response.context_menu(|ui| {
+                    let app = ui
+                        .data(|data| data.get_temp::<Option<AppId>>(menu_id))
+                        .flatten()
+                        .and_then(|id| self.apps.applications.get(&id));
+                    match app {
+                        Some(app) => {
+                            self.tags_menu(ui, app, &mut events);
+                            self.process_menu(ui, app, &mut events);
+                            #[cfg(all(unix, not(target_os = "macos")))]
+                            self.entry_menu(ui, app, &mut events);
+                        }
+                        None => ui.close_menu(),
+                    }
+                });
             });
 
         events
     }
 
+    /// Lists every known tag, and the `#tags` in the query, to toggle on `app`.
+    fn tags_menu(&self, ui: &mut Ui, app: &App, events: &mut Vec<ResultsEvent>) {
+        ui.label(RichText::new(tr!("tags-menu-title")).color(Colors::subtext0()));
+
+        let mut tags: BTreeSet<&str> = self.search.all_tags();
+        let query_tags = self.results.query_tags.iter().map(|v| v.as_str());
+        tags.extend(query_tags);
+        if tags.is_empty() {
+            ui.label(RichText::new(tr!("tags-menu-empty")).color(Colors::overlay1()));
+        }
+
+        let app_tags = self.search.tags_of(app);
+        let config_tags = self.search.config_tags_of(app);
+        for tag in tags {
+            let mut checked = app_tags.contains(&tag);
+            // The settings can't be changed from here.
+            let enabled = !config_tags.contains(&tag);
+            let response = ui.add_enabled(enabled, Checkbox::new(&mut checked, format!("#{tag}")));
+            if response.changed() {
+                events.push(ResultsEvent::ToggleTag(app.id.clone(), tag.to_string()));
+            }
+        }
+    }
+
+    /// Switching to the app when its last launch still runs, or its output when it crashed.
+    fn process_menu(&self, ui: &mut Ui, app: &App, events: &mut Vec<ResultsEvent>) {
+        let running = self.search.running_pid(&app.id).is_some();
+        let crashed = self.search.has_crashed(&app.id);
+        if !running && !crashed {
+            return;
+        }
+        ui.separator();
+        if running && ui.button(tr!("switch-to-app")).clicked() {
+            events.push(ResultsEvent::SwitchTo(app.id.clone()));
+            ui.close_menu();
+        }
+        if crashed && ui.button(tr!("show-launch-log")).clicked() {
+            events.push(ResultsEvent::ShowLaunchLog);
+            ui.close_menu();
+        }
+    }
+
+    /// Editing the desktop entry, system entries are better overridden with a copy,
+    /// and managing the package the app came from.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn entry_menu(&self, ui: &mut Ui, app: &App, events: &mut Vec<ResultsEvent>) {
+        // AppImages carry their desktop entry inside, apps of the settings have none.
+        use crate::apps::AppSource;
+        if matches!(app.source, AppSource::AppImage | AppSource::Settings) {
+            return;
+        }
+        ui.separator();
+        let mut copy = None;
+        if crate::platform::user_entry_path(&app.path).is_none() {
+            if ui.button(tr!("edit-entry")).clicked() {
+                copy = Some(false);
+            }
+        } else {
+            if ui.button(tr!("edit-entry-copy")).clicked() {
+                copy = Some(true);
+            }
+            if ui.button(tr!("edit-entry-system")).clicked() {
+                copy = Some(false);
+            }
+        }
+
+        if let Some(copy) = copy {
+            events.push(ResultsEvent::EditEntry {
+                app: app.id.clone(),
+                copy,
+            });
+            ui.close_menu();
+        }
+
+        if ui.button(tr!("add-to-autostart")).clicked() {
+            events.push(ResultsEvent::AddToAutostart(app.id.clone()));
+            ui.close_menu();
+        }
+
+        ui.separator();
+        let mut uninstall = None;
+        if ui.button(tr!("package-info")).clicked() {
+            uninstall = Some(false);
+        }
+        if ui.button(tr!("package-uninstall")).clicked() {
+            uninstall = Some(true);
+        }
+        if let Some(uninstall) = uninstall {
+            events.push(ResultsEvent::ManagePackage {
+                app: app.id.clone(),
+                uninstall,
+            });
+            ui.close_menu();
+        }
+    }
+
     fn draw_entry(
         &self,
         ui: &mut Ui,
@@ -194,15 +510,70 @@ impl ResultsWidget<'_> {
         entry: &SearchResultEntry,
     ) -> Rect {
         let bg_rect = rect.expand(3.0);
-        let Some(app) = self.apps.applications.get(&entry.id) else {
-            return bg_rect;
-        };
 
         rect.max.x -= 4.0;
         rect.min.x += 10.0;
         rect = rect.shrink2(Vec2::new(1.0, 0.0));
 
         let image_width = rect.height();
+        if let Some(action) = &entry.action {
+            let text_color = Colors::subtext0()
+                .lerp_to_gamma(Colors::text(), selected)
+                .gamma_multiply(opacity);
+            let (icon, text) = action_label(action);
+            let icon_center = rect.left_center() + Vec2::new(image_width / 2.0, 0.0);
+            match action {
+                EntryAction::Color { color, .. } => {
+                    let swatch = Rect::from_center_size(icon_center, Vec2::splat(IMAGE_SIZE * 0.8));
+                    let [r, g, b] = *color;
+                    let color = Color32::from_rgb(r, g, b).gamma_multiply(opacity);
+                    ui.painter().rect(
+                        swatch,
+                        Rounding::same(4.0),
+                        color,
+                        Stroke::new(1.0, Colors::overlay1().gamma_multiply(opacity)),
+                    );
+                }
+                _ => draw_icon(ui.painter(), icon, icon_center, IMAGE_SIZE, text_color),
+            }
+            let mut text_rect = ui.painter().text(
+                rect.left_center() + Vec2::new(image_width + 2.0, 0.0),
+                Align2::LEFT_CENTER,
+                text,
+                FontId::new(18.0, FontFamily::Proportional),
+                text_color,
+            );
+            if let Some(detail) = &entry.detail {
+                text_rect = ui.painter().text(
+                    text_rect.right_center() + Vec2::new(8.0, 0.0),
+                    Align2::LEFT_CENTER,
+                    detail,
+                    FontId::new(14.0, FontFamily::Proportional),
+                    Colors::overlay1().gamma_multiply(opacity),
+                );
+            }
+            if self.confirming == Some(&entry.id) {
+                ui.painter().text(
+                    text_rect.right_center() + Vec2::new(8.0, 0.0),
+                    Align2::LEFT_CENTER,
+                    tr!("confirm-entry"),
+                    FontId::new(18.0, FontFamily::Proportional),
+                    Colors::red().gamma_multiply(opacity),
+                );
+            }
+            return bg_rect;
+        }
+
+        let Some(app) = self.apps.applications.get(&entry.id) else {
+            return bg_rect;
+        };
+        if self.running.is_running(app) {
+            ui.painter().circle_filled(
+                rect.left_center() - Vec2::new(5.0, 0.0),
+                2.0,
+                Colors::text().gamma_multiply(opacity),
+            );
+        }
         if let Some(icon) = self.app_icons.read_icon(&entry.id) {
             let string = format!("file://{}", icon.to_str().unwrap());
             let image = egui::Image::from_uri(string)
@@ -216,8 +587,8 @@ impl ResultsWidget<'_> {
             );
         }
 
-        let text_color = Colors::SUBTEXT0
-            .lerp_to_gamma(Colors::TEXT, selected)
+        let text_color = Colors::subtext0()
+            .lerp_to_gamma(Colors::text(), selected)
             .gamma_multiply(opacity);
         rect = rect.with_min_x(rect.min.x + image_width + 2.0);
 
@@ -230,40 +601,112 @@ impl ResultsWidget<'_> {
                 ..LayoutJob::default()
             };
 
-            for (i, char) in app.name.chars().enumerate() {
-                let value = entry.score.indices.get(&i).unwrap_or(&0.0);
-                let value = if *value > 0.0 { *value } else { 0.0 };
-                let text_color = text_color.lerp_to_gamma(Colors::ROSEWATER, value);
+            match self.template {
+                Some(template) => {
+                    for segment in template.render(app) {
+                        let style = segment.style;
+                        let font = FontId::new(
+                            if style.small { 14.0 } else { 18.0 },
+                            if style.bold {
+                                FontFamily::Name("Roboto-Bold".into())
+                            } else {
+                                FontFamily::Proportional
+                            },
+                        );
+                        let mut color = if style.accent {
+                            Colors::mauve().gamma_multiply(opacity)
+                        } else {
+                            text_color
+                        };
+                        if style.dim {
+                            color = color.gamma_multiply(0.5);
+                        }
+                        if segment.field == Some(Field::Name) {
+                            append_name(&mut job, app, entry, &font, color);
+                            continue;
+                        }
+                        job.append(
+                            &segment.text,
+                            0.0,
+                            TextFormat {
+                                font_id: font,
+                                color,
+                                valign: Align::Center,
+                                ..TextFormat::default()
+                            },
+                        )
+                    }
+                }
+                None => append_name(&mut job, app, entry, &font, text_color),
+            }
+
+            if self.search.is_new(&entry.id) {
                 job.append(
-                    &char.to_string(),
-                    0.0,
+                    &tr!("app-new"),
+                    8.0,
                     TextFormat {
-                        font_id: font.clone(),
+                        font_id: FontId::new(12.0, FontFamily::Proportional),
+                        color: Colors::peach().gamma_multiply(opacity),
+                        valign: Align::Center,
+                        ..TextFormat::default()
+                    },
+                )
+            }
 
-                        color: text_color,
+            if self.explain && !entry.score.steps.is_empty() {
+                job.append(
+                    &format!("{:.1} = {}", entry.score.score, entry.score.explain()),
+                    8.0,
+                    TextFormat {
+                        font_id: FontId::new(10.0, FontFamily::Monospace),
+                        color: Colors::yellow().gamma_multiply(opacity),
+                        valign: Align::Center,
                         ..TextFormat::default()
                     },
                 )
             }
 
-            // DEBUG
-            //{
-            //    job.append(
-            //        &format!(
-            //            "{}: {}",
-            //            self.search.get_popularity(&entry.id),
-            //            entry.score.score
-            //        ),
-            //        8.0,
-            //        TextFormat {
-            //            font_id: font.clone(),
-            //            color: text_color,
-            //            ..TextFormat::default()
-            //        },
-            //    )
-            //}
-
-            if let Some(comment) = app.comment.as_ref() {
+            if self.confirming == Some(&entry.id) {
+                job.append(
+                    &format!(" {}", tr!("confirm-entry")),
+                    8.0,
+                    TextFormat {
+                        font_id: font.clone(),
+                        color: Colors::red().gamma_multiply(opacity),
+                        ..TextFormat::default()
+                    },
+                )
+            } else if let Some(detail) = entry.detail.as_ref() {
+                job.append(
+                    &format!(" {detail}"),
+                    8.0,
+                    TextFormat {
+                        font_id: font.clone(),
+                        color: text_color.gamma_multiply(0.5),
+                        ..TextFormat::default()
+                    },
+                )
+            } else if selected > 0.0 && self.search.running_pid(&entry.id).is_some() {
+                job.append(
+                    &format!(" {}", tr!("app-running")),
+                    8.0,
+                    TextFormat {
+                        font_id: font.clone(),
+                        color: Colors::green().gamma_multiply(opacity * selected),
+                        ..TextFormat::default()
+                    },
+                )
+            } else if selected > 0.0 && self.search.has_crashed(&entry.id) {
+                job.append(
+                    &format!(" {}", tr!("app-crashed")),
+                    8.0,
+                    TextFormat {
+                        font_id: font.clone(),
+                        color: Colors::red().gamma_multiply(opacity * selected),
+                        ..TextFormat::default()
+                    },
+                )
+            } else if let (None, Some(comment)) = (self.template, app.comment.as_ref()) {
                 if selected > 0.0 {
                     job.append(
                         &format!(" {comment}"),
@@ -281,9 +724,42 @@ impl ResultsWidget<'_> {
 
         {
             let rect = Align2::LEFT_CENTER.anchor_size(rect.left_center(), galley.size());
+            if entry.broken {
+                draw_icon(
+                    p,
+                    glyph!("warning"),
+                    rect.right_center() + Vec2::new(16.0, 0.0),
+                    18.0,
+                    Colors::yellow().gamma_multiply(opacity),
+                );
+            }
             p.galley(rect.min, galley, Color32::RED);
         }
 
         bg_rect
     }
-}
\ No newline at end of file
+}
+
+/// The name of `app`, with the characters that matched the query highlighted.
+fn append_name(
+    job: &mut LayoutJob,
+    app: &App,
+    entry: &SearchResultEntry,
+    font: &FontId,
+    text_color: Color32,
+) {
+    for (i, char) in app.name.chars().enumerate() {
+        let value = entry.score.indices.get(&i).unwrap_or(&0.0);
+        let value = if *value > 0.0 { *value } else { 0.0 };
+        let text_color = text_color.lerp_to_gamma(Colors::rosewater(), value);
+        job.append(
+            &char.to_string(),
+            0.0,
+            TextFormat {
+                font_id: font.clone(),
+                color: text_color,
+                ..TextFormat::default()
+            },
+        )
+    }
+}
@@ -1,7 +1,8 @@
 use eframe::egui::{Align, Align2, Color32, FontFamily, FontId, FontSelection, Margin, Response, Rounding, Stroke, TextBuffer, TextEdit, Ui, Vec2, Widget};
 use eframe::egui::text_edit::TextEditOutput;
-use splinter_icon::icon;
-use crate::ui::framework::{draw_icon, Colors};
+use crate::i18n::tr;
+use crate::SEARCH_BAR_HEIGHT;
+use crate::ui::framework::{draw_icon, glyph, Colors};
 
 pub struct SearchBarMessage {
     pub text: String,
@@ -21,7 +22,7 @@ pub struct  SearchBarResponse {
 impl SearchBarWidget<'_> {
     pub fn ui(self, ui: &mut Ui) -> TextEditOutput {
         let mut rect = ui.clip_rect();
-        rect.set_height(64.0);
+        rect.set_height(SEARCH_BAR_HEIGHT);
 
         let p = ui.painter();
 
@@ -43,14 +44,14 @@ impl SearchBarWidget<'_> {
 
         p.line_segment(
             [rect.left_bottom(), rect.right_bottom()],
-            Stroke::new(1.0, Colors::SUBTEXT0.gamma_multiply(0.3)),
+            Stroke::new(1.0, Colors::subtext0().gamma_multiply(0.3)),
         );
 
         let font = FontId::new(18.0, FontFamily::Proportional);
         let output = TextEdit::singleline(self.query)
             .frame(false)
             .vertical_align(Align::Center)
-            .text_color(Colors::TEXT)
+            .text_color(Colors::text())
             .font(FontSelection::FontId(font.clone()))
             .margin(Margin {
                 left: 8.0 + 32.0 + 6.0,
@@ -79,19 +80,19 @@ impl SearchBarWidget<'_> {
 
         draw_icon(
             p,
-            icon!("search"),
+            glyph!("search"),
             rect.left_center() + Vec2::new(18.0 + 12.0, -1.0),
             18.0,
-            Colors::TEXT,
+            Colors::text(),
         );
 
         if self.query.as_str().is_empty() {
             p.text(
                 output.text_clip_rect.left_center(),
                 Align2::LEFT_CENTER,
-                "Search for a program",
+                tr!("search-placeholder"),
                 font,
-                Colors::SUBTEXT0,
+                Colors::subtext0(),
             );
         }
 
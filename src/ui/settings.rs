@@ -0,0 +1,237 @@
+use crate::color::{format_color, ColorFormat};
+use crate::i18n::tr;
+use crate::settings::{MatcherKind, Settings};
+use crate::ui::framework::{parse_shortcut, set_theme, Colors};
+use eframe::egui::{ComboBox, DragValue, Grid, Response, RichText, ScrollArea, TextEdit, Ui, Vec2};
+
+/// The settings view (Ctrl+,), edits the settings that take effect without a restart.
+pub struct SettingsWidget<'a> {
+    pub settings: &'a mut Settings,
+}
+
+pub struct SettingsOutput {
+    /// Something was changed, the search has to pick it up.
+    pub changed: bool,
+    /// Save was pressed.
+    pub save: bool,
+}
+
+impl SettingsWidget<'_> {
+    pub fn ui(self, ui: &mut Ui) -> SettingsOutput {
+        let settings = self.settings;
+        let mut changed = false;
+        let mut save = false;
+
+        ScrollArea::vertical().show(ui, |ui| {
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                ui.add_space(16.0);
+                ui.vertical(|ui| {
+                    ui.spacing_mut().item_spacing = Vec2::new(12.0, 6.0);
+                    // Every widget reports whether it changed a setting.
+                    let mut track = |response: Response| changed |= response.changed();
+
+                    section(ui, tr!("settings-search"));
+                    Grid::new("settings-search").show(ui, |ui| {
+                        ui.label(tr!("settings-matcher"));
+                        let matcher = &mut settings.search.matcher;
+                        ComboBox::from_id_salt("settings-matcher")
+                            .selected_text(matcher_name(*matcher))
+                            .show_ui(ui, |ui| {
+                                for kind in [MatcherKind::Skim, MatcherKind::Nucleo] {
+                                    track(ui.selectable_value(matcher, kind, matcher_name(kind)));
+                                }
+                            });
+                        ui.end_row();
+                        let search = &mut settings.search;
+                        ui.label(tr!("settings-half-life"));
+                        track(
+                            ui.add(
+                                DragValue::new(&mut search.frecency_half_life_days)
+                                    .range(1.0..=365.0)
+                                    .speed(0.5),
+                            ),
+                        );
+                        ui.end_row();
+                        ui.label(tr!("settings-min-score"));
+                        track(
+                            ui.add(
+                                DragValue::new(&mut search.min_score)
+                                    .range(0.0..=1.0)
+                                    .speed(0.01),
+                            ),
+                        );
+                        ui.end_row();
+                    });
+                    let search = &mut settings.search;
+                    track(ui.checkbox(&mut search.deterministic, tr!("settings-deterministic")));
+                    track(ui.checkbox(&mut search.phonetic, tr!("settings-phonetic")));
+                    track(ui.checkbox(&mut search.time_of_day, tr!("settings-time-of-day")));
+
+                    section(ui, tr!("settings-results"));
+                    let web = &mut settings.web;
+                    track(ui.checkbox(&mut web.enabled, tr!("settings-web")));
+                    let providers = &mut settings.search_providers;
+                    track(ui.checkbox(&mut providers.enabled, tr!("settings-providers")));
+                    Grid::new("settings-results").show(ui, |ui| {
+                        ui.label(tr!("settings-web-threshold"));
+                        track(
+                            ui.add(
+                                DragValue::new(&mut settings.web.threshold).range(0.0..=10000.0),
+                            ),
+                        );
+                        ui.end_row();
+                        ui.label(tr!("settings-providers-max"));
+                        let providers = &mut settings.search_providers;
+                        track(ui.add(DragValue::new(&mut providers.max_results).range(1..=20)));
+                        ui.end_row();
+                    });
+
+                    section(ui, tr!("settings-home"));
+                    let home = &mut settings.home;
+                    track(ui.checkbox(&mut home.enabled, tr!("settings-home-enabled")));
+                    Grid::new("settings-home").show(ui, |ui| {
+                        for (label, value) in [
+                            (tr!("settings-home-pinned"), &mut home.pinned),
+                            (tr!("settings-home-recent"), &mut home.recent),
+                            (tr!("settings-home-most-used"), &mut home.most_used),
+                        ] {
+                            ui.label(label);
+                            track(ui.add(DragValue::new(value).range(0..=50)));
+                            ui.end_row();
+                        }
+                    });
+
+                    section(ui, tr!("settings-new-apps"));
+                    Grid::new("settings-new-apps").show(ui, |ui| {
+                        let new_apps = &mut settings.new_apps;
+                        ui.label(tr!("settings-new-apps-days"));
+                        track(ui.add(DragValue::new(&mut new_apps.days).range(0..=30)));
+                        ui.end_row();
+                        ui.label(tr!("settings-new-apps-boost"));
+                        track(
+                            ui.add(
+                                DragValue::new(&mut new_apps.boost)
+                                    .range(1.0..=5.0)
+                                    .speed(0.05),
+                            ),
+                        );
+                        ui.end_row();
+                    });
+
+                    section(ui, tr!("settings-launch"));
+                    let launch = &mut settings.launch;
+                    track(ui.checkbox(&mut launch.notify, tr!("settings-launch-notify")));
+                    track(ui.checkbox(&mut launch.login_env, tr!("settings-launch-login-env")));
+
+                    section(ui, tr!("settings-window"));
+                    track(ui.checkbox(
+                        &mut settings.window.running_indicator,
+                        tr!("settings-running-indicator"),
+                    ));
+                    let animation = &mut settings.animation;
+                    track(ui.checkbox(&mut animation.reduce_motion, tr!("settings-reduce-motion")));
+                    Grid::new("settings-window").show(ui, |ui| {
+                        ui.label(tr!("settings-transition"));
+                        track(ui.add(DragValue::new(&mut animation.transition_ms).range(0..=1000)));
+                        ui.end_row();
+                    });
+
+                    section(ui, tr!("settings-theme"));
+                    let theme = &mut settings.theme;
+                    let mut theme_changed = false;
+                    Grid::new("settings-theme").show(ui, |ui| {
+                        for name in Colors::NAMES {
+                            ui.label(*name);
+                            let color = Colors::by_name(name).unwrap_or_default();
+                            let mut rgb = [color.r(), color.g(), color.b()];
+                            ui.horizontal(|ui| {
+                                let response = ui.color_edit_button_srgb(&mut rgb);
+                                if response.changed() {
+                                    let hex = format_color(rgb, ColorFormat::Hex);
+                                    theme.insert(name.to_string(), hex);
+                                    theme_changed = true;
+                                }
+                                track(response);
+                                if theme.contains_key(*name) {
+                                    let mut reset = ui.small_button(tr!("settings-theme-reset"));
+                                    if reset.clicked() {
+                                        theme.remove(*name);
+                                        reset.mark_changed();
+                                        theme_changed = true;
+                                    }
+                                    track(reset);
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+                    // Shown right away, the rest of the settings is read when drawing.
+                    if theme_changed {
+                        set_theme(theme);
+                    }
+
+                    section(ui, tr!("settings-keys"));
+                    let keys = &mut settings.keys;
+                    Grid::new("settings-keys").show(ui, |ui| {
+                        for (label, value) in [
+                            (tr!("settings-key-pin"), &mut keys.pin),
+                            (tr!("settings-key-details"), &mut keys.details),
+                            (tr!("settings-key-explain"), &mut keys.explain),
+                            (tr!("settings-key-stats"), &mut keys.stats),
+                            (tr!("settings-key-history"), &mut keys.history),
+                            (tr!("settings-key-settings"), &mut keys.settings),
+                            (tr!("settings-key-reload-icons"), &mut keys.reload_icons),
+                            (tr!("settings-key-rescan"), &mut keys.rescan),
+                        ] {
+                            ui.label(label);
+                            ui.horizontal(|ui| {
+                                let edit = TextEdit::singleline(value).desired_width(120.0);
+                                track(ui.add(edit));
+                                if !value.trim().is_empty() && parse_shortcut(value).is_none() {
+                                    ui.label(
+                                        RichText::new(tr!("settings-key-invalid"))
+                                            .color(Colors::red()),
+                                    );
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+
+                    section(ui, tr!("settings-apps"));
+                    track(ui.checkbox(
+                        &mut settings.merge_duplicates,
+                        tr!("settings-merge-duplicates"),
+                    ));
+                    ui.label(tr!("settings-ignore"));
+                    // Empty lines stay while typing, they are dropped when saving.
+                    let mut ignore = settings.ignore.join("\n");
+                    let response = ui.text_edit_multiline(&mut ignore);
+                    if response.changed() {
+                        settings.ignore = ignore.split('\n').map(String::from).collect();
+                    }
+                    track(response);
+
+                    ui.add_space(8.0);
+                    save = ui.button(tr!("settings-save")).clicked();
+                    ui.add_space(8.0);
+                });
+            });
+        });
+
+        SettingsOutput { changed, save }
+    }
+}
+
+fn section(ui: &mut Ui, title: String) {
+    ui.add_space(6.0);
+    ui.label(RichText::new(title).size(13.0).color(Colors::overlay1()));
+}
+
+fn matcher_name(kind: MatcherKind) -> &'static str {
+    match kind {
+        MatcherKind::Skim => "skim",
+        MatcherKind::Nucleo => "nucleo",
+    }
+}
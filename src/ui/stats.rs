@@ -0,0 +1,131 @@
+use crate::apps::AppManager;
+use crate::i18n::tr;
+use crate::search::stats::UsageStats;
+use crate::ui::framework::Colors;
+use eframe::egui::{Align2, FontFamily, FontId, Pos2, Rect, Rounding, Sense, Ui, Vec2};
+
+/// How many days the launches per day chart shows.
+pub const STATS_DAYS: u32 = 14;
+const MOST_USED_COUNT: usize = 8;
+const ROW_HEIGHT: f32 = 24.0;
+
+/// The `:stats` view, shows what you launch and how often.
+pub struct StatsWidget<'a> {
+    pub apps: &'a AppManager,
+    pub stats: &'a UsageStats,
+}
+
+impl StatsWidget<'_> {
+    pub fn ui(&self, ui: &mut Ui) {
+        let rect = ui
+            .available_rect_before_wrap()
+            .shrink2(Vec2::new(24.0, 12.0));
+        let painter = ui.painter();
+        let font = FontId::new(16.0, FontFamily::Proportional);
+        let small_font = FontId::new(13.0, FontFamily::Proportional);
+
+        let mut summary = tr!("stats-launches", count = self.stats.launches);
+        if let Some(average) = self.stats.average_search_to_launch {
+            summary += &format!(
+                "  ·  {}",
+                tr!(
+                    "stats-average-time",
+                    seconds = format!("{:.1}", average.as_secs_f32())
+                )
+            );
+        }
+        painter.text(
+            rect.left_top(),
+            Align2::LEFT_TOP,
+            summary,
+            font.clone(),
+            Colors::text(),
+        );
+
+        // Most launched apps, as horizontal bars next to their name.
+        let mut y = rect.top() + ROW_HEIGHT * 1.5;
+        painter.text(
+            Pos2::new(rect.left(), y),
+            Align2::LEFT_TOP,
+            tr!("stats-most-used"),
+            small_font.clone(),
+            Colors::overlay1(),
+        );
+        y += ROW_HEIGHT;
+
+        let max = self.stats.most_used.first().map(|(_, v)| *v).unwrap_or(1);
+        let name_width = rect.width() * 0.35;
+        for (id, count) in self.stats.most_used.iter().take(MOST_USED_COUNT) {
+            let Some(app) = self.apps.applications.get(id) else {
+                continue;
+            };
+            let center_y = y + ROW_HEIGHT / 2.0;
+            painter.text(
+                Pos2::new(rect.left(), center_y),
+                Align2::LEFT_CENTER,
+                &app.name,
+                font.clone(),
+                Colors::subtext1(),
+            );
+
+            let bar_width = (rect.width() - name_width - 48.0) * (*count as f32 / max as f32);
+            let bar = Rect::from_min_size(
+                Pos2::new(rect.left() + name_width, y + 5.0),
+                Vec2::new(bar_width.max(2.0), ROW_HEIGHT - 10.0),
+            );
+            painter.rect_filled(bar, Rounding::same(4.0), Colors::mauve());
+            painter.text(
+                Pos2::new(bar.right() + 8.0, center_y),
+                Align2::LEFT_CENTER,
+                count.to_string(),
+                small_font.clone(),
+                Colors::subtext0(),
+            );
+            y += ROW_HEIGHT;
+        }
+
+        // Launches per day, as a column chart along the bottom.
+        y += ROW_HEIGHT / 2.0;
+        painter.text(
+            Pos2::new(rect.left(), y),
+            Align2::LEFT_TOP,
+            tr!("stats-per-day"),
+            small_font.clone(),
+            Colors::overlay1(),
+        );
+        y += ROW_HEIGHT;
+
+        let chart = Rect::from_min_max(
+            Pos2::new(rect.left(), y),
+            Pos2::new(rect.right(), rect.bottom() - ROW_HEIGHT),
+        );
+        let days = self.stats.per_day.len().max(1);
+        let column_width = chart.width() / days as f32;
+        let max = self
+            .stats
+            .per_day
+            .iter()
+            .map(|(_, v)| *v)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        for (i, (date, count)) in self.stats.per_day.iter().enumerate() {
+            let x = chart.left() + i as f32 * column_width;
+            let height = chart.height() * (*count as f32 / max as f32);
+            let column = Rect::from_min_max(
+                Pos2::new(x + 4.0, chart.bottom() - height.max(2.0)),
+                Pos2::new(x + column_width - 4.0, chart.bottom()),
+            );
+            painter.rect_filled(column, Rounding::same(4.0), Colors::blue());
+            painter.text(
+                Pos2::new(column.center().x, chart.bottom() + 4.0),
+                Align2::CENTER_TOP,
+                date.format("%d").to_string(),
+                small_font.clone(),
+                Colors::overlay1(),
+            );
+        }
+
+        ui.allocate_rect(rect, Sense::hover());
+    }
+}
@@ -0,0 +1,175 @@
+//! Row templates like `"{name}[  —  {generic_name:dim}]"`, picking what a result shows.
+//!
+//! `{field}` or `{field:style,...}` inserts a field of the app, and `[...]` is left out
+//! when a field in it is empty. `\` escapes the next character.
+
+use crate::apps::App;
+use eyre::{bail, eyre};
+use std::borrow::Cow;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Field {
+    Name,
+    GenericName,
+    Comment,
+    Keywords,
+    Categories,
+    FileId,
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct FieldStyle {
+    pub dim: bool,
+    pub bold: bool,
+    pub small: bool,
+    pub accent: bool,
+}
+
+#[derive(Debug)]
+enum Part {
+    Text(String),
+    Field(Field, FieldStyle),
+    Optional(Vec<Part>),
+}
+
+#[derive(Debug)]
+pub struct RowTemplate {
+    parts: Vec<Part>,
+}
+
+/// A piece of a rendered row, `field` is `None` for the text between fields.
+pub struct Segment<'a> {
+    pub text: Cow<'a, str>,
+    pub field: Option<Field>,
+    pub style: FieldStyle,
+}
+
+impl RowTemplate {
+    pub fn parse(template: &str) -> eyre::Result<RowTemplate> {
+        let parts = parse_parts(&mut template.chars().peekable(), false)?;
+        Ok(RowTemplate { parts })
+    }
+
+    /// The pieces of the row of `app`, in order.
+    pub fn render<'a>(&'a self, app: &'a App) -> Vec<Segment<'a>> {
+        let mut segments = Vec::new();
+        render_parts(&self.parts, app, &mut segments);
+        segments
+    }
+}
+
+fn parse_parts(chars: &mut Peekable<Chars>, nested: bool) -> eyre::Result<Vec<Part>> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let flush = |parts: &mut Vec<Part>, text: &mut String| {
+        if !text.is_empty() {
+            parts.push(Part::Text(std::mem::take(text)));
+        }
+    };
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => text.extend(chars.next()),
+            '{' => {
+                flush(&mut parts, &mut text);
+                let mut spec = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => spec.push(c),
+                        None => bail!("Unclosed {{ in the row template"),
+                    }
+                }
+                parts.push(parse_field(&spec)?);
+            }
+            '[' => {
+                flush(&mut parts, &mut text);
+                parts.push(Part::Optional(parse_parts(chars, true)?));
+            }
+            ']' if nested => {
+                flush(&mut parts, &mut text);
+                return Ok(parts);
+            }
+            ']' => bail!("Unmatched ] in the row template"),
+            c => text.push(c),
+        }
+    }
+    if nested {
+        bail!("Unclosed [ in the row template");
+    }
+    flush(&mut parts, &mut text);
+    Ok(parts)
+}
+
+fn parse_field(spec: &str) -> eyre::Result<Part> {
+    let (name, styles) = spec.split_once(':').unwrap_or((spec, ""));
+    let field = match name.trim() {
+        "name" => Field::Name,
+        "generic_name" => Field::GenericName,
+        "comment" => Field::Comment,
+        "keywords" => Field::Keywords,
+        "categories" => Field::Categories,
+        "file_id" => Field::FileId,
+        name => return Err(eyre!("Unknown field {name:?} in the row template")),
+    };
+    let mut style = FieldStyle::default();
+    for name in styles.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+        match name {
+            "dim" => style.dim = true,
+            "bold" => style.bold = true,
+            "small" => style.small = true,
+            "accent" => style.accent = true,
+            name => bail!("Unknown style {name:?} in the row template"),
+        }
+    }
+    Ok(Part::Field(field, style))
+}
+
+fn field_value(field: Field, app: &App) -> Cow<'_, str> {
+    match field {
+        Field::Name => Cow::Borrowed(&app.name),
+        Field::GenericName => Cow::Borrowed(app.generic_name.as_deref().unwrap_or_default()),
+        Field::Comment => Cow::Borrowed(app.comment.as_deref().unwrap_or_default()),
+        Field::Keywords => {
+            let keywords = app.keywords.as_deref().unwrap_or_default();
+            let keywords: Vec<&str> = keywords.split(';').filter(|v| !v.is_empty()).collect();
+            Cow::Owned(keywords.join(", "))
+        }
+        Field::Categories => Cow::Owned(app.categories.as_deref().unwrap_or_default().join(", ")),
+        Field::FileId => Cow::Borrowed(app.file_id().unwrap_or_default()),
+    }
+}
+
+/// Whether all fields in `parts` had a value.
+fn render_parts<'a>(parts: &'a [Part], app: &'a App, segments: &mut Vec<Segment<'a>>) -> bool {
+    let mut complete = true;
+    for part in parts {
+        match part {
+            Part::Text(text) => segments.push(Segment {
+                text: Cow::Borrowed(text),
+                field: None,
+                style: FieldStyle::default(),
+            }),
+            Part::Field(field, style) => {
+                let text = field_value(*field, app);
+                if text.trim().is_empty() {
+                    complete = false;
+                    continue;
+                }
+                segments.push(Segment {
+                    text,
+                    field: Some(*field),
+                    style: *style,
+                });
+            }
+            Part::Optional(parts) => {
+                let mut optional = Vec::new();
+                if render_parts(parts, app, &mut optional) {
+                    segments.append(&mut optional);
+                }
+            }
+        }
+    }
+    complete
+}
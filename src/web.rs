@@ -0,0 +1,93 @@
+//! Searching the web when no app matches, `!yt`-style bangs in the query pick the site.
+
+use crate::apps::AppId;
+use crate::search::{EntryAction, SearchResultEntry, SearchScore};
+use crate::settings::WebSearchSettings;
+
+#[derive(Clone, Debug)]
+pub struct WebSearch {
+    /// The bang that picked the site, `None` for the default search.
+    pub bang: Option<String>,
+    /// The query without the bang.
+    pub query: String,
+    pub url: String,
+}
+
+impl WebSearch {
+    /// The host of the URL without `www.`, like `github.com`.
+    pub fn site(&self) -> &str {
+        let rest = self
+            .url
+            .split_once("://")
+            .map_or(&*self.url, |(_, rest)| rest);
+        let host = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+        host.strip_prefix("www.").unwrap_or(host)
+    }
+}
+
+/// The entry searching the web for `query`, offered when it has a bang or the best app
+/// only scored `top_score`, below the threshold.
+pub fn entry(
+    query: &str,
+    top_score: f32,
+    settings: &WebSearchSettings,
+) -> Option<SearchResultEntry> {
+    if !settings.enabled {
+        return None;
+    }
+    let search = parse(query, settings)?;
+    if search.bang.is_none() && top_score >= settings.threshold {
+        return None;
+    }
+
+    Some(SearchResultEntry {
+        id: AppId::from_properties(&["web", &search.url]),
+        score: SearchScore::default(),
+        detail: None,
+        broken: false,
+        action: Some(EntryAction::WebSearch(search)),
+        confirm: false,
+    })
+}
+
+/// Takes the first word like `!yt` that names a bang out of the query.
+fn parse(query: &str, settings: &WebSearchSettings) -> Option<WebSearch> {
+    let mut bang = None;
+    let mut words = Vec::new();
+    for word in query.split_whitespace() {
+        let name = word.strip_prefix('!').map(str::to_lowercase);
+        match name.filter(|name| bang.is_none() && settings.bangs.contains_key(name)) {
+            Some(name) => bang = Some(name),
+            None => words.push(word),
+        }
+    }
+    let query = words.join(" ");
+    if query.is_empty() {
+        return None;
+    }
+
+    let template = match &bang {
+        Some(name) => &settings.bangs[name],
+        None => &settings.url,
+    };
+    Some(WebSearch {
+        url: template.replace("{}", &encode(&query)),
+        bang,
+        query,
+    })
+}
+
+/// Percent-encodes `text` for a query string.
+fn encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b' ' => encoded.push('+'),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}